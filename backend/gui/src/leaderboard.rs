@@ -0,0 +1,46 @@
+//! Leaderboard view for the multiplayer snake game GUI
+//!
+//! This module loads the players' ratings from the server and lists them,
+//! best first, with their wins and number of rated matches.
+
+use crate::types::LeaderboardEntry;
+use crate::ui::UIManager;
+use gloo_net::http::Request;
+use wasm_bindgen::prelude::*;
+
+/// Load the leaderboard and show it in place of the live view
+pub async fn load_leaderboard() -> Result<(), JsValue> {
+    let ui = UIManager::new()?;
+    ui.show_connecting()?;
+
+    match fetch_leaderboard().await {
+        Ok(entries) => ui.show_leaderboard(&entries),
+        Err(e) => {
+            ui.show_error(
+                &e.as_string()
+                    .unwrap_or_else(|| "Failed to load leaderboard".to_string()),
+            )?;
+            Err(e)
+        }
+    }
+}
+
+/// Download the players ordered by rating
+async fn fetch_leaderboard() -> Result<Vec<LeaderboardEntry>, JsValue> {
+    let response = Request::get("/leaderboard")
+        .send()
+        .await
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "Leaderboard could not be loaded (HTTP {})",
+            response.status()
+        )));
+    }
+
+    response
+        .json::<Vec<LeaderboardEntry>>()
+        .await
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
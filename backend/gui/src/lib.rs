@@ -8,6 +8,9 @@ use web_sys::{console, window, Document, Element};
 
 mod types;
 mod canvas;
+mod leaderboard;
+mod live;
+mod replay;
 mod ui;
 
 // When the `console_error_panic_hook` feature is enabled, we can call the
@@ -41,6 +44,18 @@ fn init_app() -> Result<(), JsValue> {
         }
     };
     
+    // A `?replay=<id>` query opens a recorded game instead of the live view
+    if let Some(replay_id) = query_param("replay")? {
+        play_replay(replay_id);
+        return Ok(());
+    }
+    
+    // A `?view=leaderboard` query shows player ratings instead
+    if query_param("view")?.as_deref() == Some("leaderboard") {
+        show_leaderboard();
+        return Ok(());
+    }
+    
     // Set up basic styling
     inject_styles(&document)?;
     
@@ -204,20 +219,45 @@ fn connect_websocket() -> Result<(), JsValue> {
     let hostname = location.hostname()?;
     let port = location.port()?;
     
-    let ws_url = if port.is_empty() {
-        format!("ws://{}/gui", hostname)
+    let host = if port.is_empty() {
+        hostname
     } else {
-        format!("ws://{}:{}/gui", hostname, port)
+        format!("{}:{}", hostname, port)
     };
+    let scheme = if location.protocol()? == "https:" { "wss" } else { "ws" };
+    let room = query_param("room")?.unwrap_or_else(|| "default".to_string());
 
-    console::log_1(&format!("Connecting to: {}", ws_url).into());
-    
-    // For now, just show a simple message that WebSocket connection would happen here
-    // In a full implementation, you would use gloo-net or web_sys WebSocket API
+    // Only the admin token unlocks game control; everyone else spectates
+    let ws_url = match query_param("token")? {
+        Some(token) => format!(
+            "{}://{}/gui?room={}&token={}&delta=true",
+            scheme, host, room, token
+        ),
+        None => format!("{}://{}/spectate?room={}&delta=true", scheme, host, room),
+    };
+
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = live::run(ws_url).await {
+            console::error_1(&e);
+        }
+    });
     
     Ok(())
 }
 
+/// Read a query parameter from the page URL, if present and not empty
+fn query_param(name: &str) -> Result<Option<String>, JsValue> {
+    let search = window().unwrap().location().search()?;
+    let prefix = format!("{}=", name);
+    
+    Ok(search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(prefix.as_str()))
+        .filter(|value| !value.is_empty())
+        .map(str::to_string))
+}
+
 fn create_element(tag: &str, class_name: Option<&str>) -> Result<Element, JsValue> {
     let window = window().unwrap();
     let document = window.document().unwrap();
@@ -244,6 +284,31 @@ pub fn start_game() {
 #[wasm_bindgen]
 pub fn start_game_from_js() {
     console::log_1(&"Starting game from JavaScript!".into());
-    // This function will be called from the UI when the start button is clicked
-    // In a full implementation, this would send a StartGame message via WebSocket
-}
\ No newline at end of file
+    
+    // The server only accepts it from a GUI connected with the admin token
+    if !live::send(types::ClientMessage::StartGame { seed: None }) {
+        console::warn_1(&"Not connected to the server".into());
+    }
+}
+
+#[wasm_bindgen]
+pub fn play_replay(replay_id: String) {
+    console::log_1(&format!("Loading replay {}", replay_id).into());
+    
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = replay::load_replay(&replay_id).await {
+            console::error_1(&e);
+        }
+    });
+}
+
+#[wasm_bindgen]
+pub fn show_leaderboard() {
+    console::log_1(&"Loading leaderboard".into());
+    
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = leaderboard::load_leaderboard().await {
+            console::error_1(&e);
+        }
+    });
+}
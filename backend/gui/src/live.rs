@@ -0,0 +1,206 @@
+//! Live view of a game room for the multiplayer snake game GUI
+//!
+//! This module connects to the server over WebSocket and shows the lobby,
+//! the running game and its results as the server reports them. With the
+//! admin token the lobby's start button starts the game; without it the
+//! room is only watched. Game updates arrive as deltas on top of the last
+//! keyframe; after a missed one a full state is requested. A dropped
+//! connection is retried after a delay that doubles with every failed
+//! attempt.
+
+use crate::canvas::GameRenderer;
+use crate::types::*;
+use crate::ui::UIManager;
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::{SinkExt, StreamExt};
+use gloo::timers::future::TimeoutFuture;
+use gloo_net::websocket::{futures::WebSocket, Message};
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use web_sys::console;
+
+thread_local! {
+    /// Messages for the server, while connected
+    static OUTGOING: RefCell<Option<UnboundedSender<ClientMessage>>> = const { RefCell::new(None) };
+}
+
+/// Send a message to the server, returning whether there is a connection
+pub fn send(message: ClientMessage) -> bool {
+    OUTGOING.with(|outgoing| {
+        outgoing
+            .borrow()
+            .as_ref()
+            .is_some_and(|sender| sender.unbounded_send(message).is_ok())
+    })
+}
+
+/// Lobby players and game board shown for the room
+struct LiveView {
+    ui: UIManager,
+    /// Renderer of the board, while a game or its results are shown
+    renderer: Option<GameRenderer>,
+    /// Players in the lobby, in join order
+    players: Vec<LobbyPlayer>,
+    /// Game state deltas apply to, with the `seq` of the update it is from
+    game: Option<(u64, GameState)>,
+}
+
+/// Follow a room over WebSocket for as long as the page is open
+pub async fn run(url: String) -> Result<(), JsValue> {
+    let mut view = LiveView {
+        ui: UIManager::new()?,
+        renderer: None,
+        players: Vec::new(),
+        game: None,
+    };
+    let mut delay = constants::RECONNECT_DELAY_MS;
+
+    loop {
+        console::log_1(&format!("Connecting to: {}", url).into());
+        match WebSocket::open(&url) {
+            // Only a connection the server answered on starts the backoff over
+            Ok(socket) => {
+                if view.follow(socket).await {
+                    delay = constants::RECONNECT_DELAY_MS;
+                }
+            }
+            Err(e) => console::error_1(&format!("Failed to connect: {}", e).into()),
+        }
+        OUTGOING.with(|outgoing| outgoing.borrow_mut().take());
+
+        console::log_1(&format!("Connection lost - reconnecting in {}ms", delay).into());
+        view.renderer = None;
+        view.game = None;
+        view.ui.show_connecting()?;
+        TimeoutFuture::new(delay).await;
+        delay = (delay * 2).min(constants::MAX_RECONNECT_DELAY_MS);
+    }
+}
+
+impl LiveView {
+    /// Show what the server reports until the connection closes, returning
+    /// whether any message arrived
+    async fn follow(&mut self, socket: WebSocket) -> bool {
+        let (mut write, mut read) = socket.split();
+        let (sender, mut outgoing) = mpsc::unbounded::<ClientMessage>();
+        OUTGOING.with(|slot| *slot.borrow_mut() = Some(sender));
+
+        // The writer stops once the sender is dropped on disconnect
+        wasm_bindgen_futures::spawn_local(async move {
+            while let Some(message) = outgoing.next().await {
+                let Ok(json) = serde_json::to_string(&message) else {
+                    continue;
+                };
+                if write.send(Message::Text(json)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut answered = false;
+        while let Some(message) = read.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Bytes(_)) => continue,
+                Err(e) => {
+                    console::error_1(&format!("WebSocket error: {}", e).into());
+                    break;
+                }
+            };
+            answered = true;
+
+            let result = serde_json::from_str::<ServerMessage>(&text)
+                .map_err(|e| JsValue::from_str(&format!("Unreadable server message: {}", e)))
+                .and_then(|message| self.handle(message));
+            if let Err(e) = result {
+                console::error_1(&e);
+            }
+        }
+
+        answered
+    }
+
+    /// Update the page for a message from the server
+    fn handle(&mut self, message: ServerMessage) -> Result<(), JsValue> {
+        match message {
+            ServerMessage::LobbyState { mut players, phase } => {
+                players.sort_by_key(|player| player.color_index);
+                self.players = players;
+
+                // Players leaving mid-game must not hide the board or the results
+                if self.renderer.is_none()
+                    || matches!(phase, RoomPhase::Lobby | RoomPhase::Countdown)
+                {
+                    self.show_lobby()?;
+                }
+            }
+            ServerMessage::GameStarted { game_state, .. } => {
+                self.show_state(&game_state)?;
+            }
+            ServerMessage::GameUpdate {
+                game_state, seq, ..
+            } => {
+                self.show_state(&game_state)?;
+                self.game = seq.map(|seq| (seq, game_state));
+            }
+            ServerMessage::GameDelta { seq, delta, .. } => {
+                let Some((last, mut state)) = self.game.take() else {
+                    // Still waiting for the keyframe asked for
+                    return Ok(());
+                };
+
+                if last + 1 == seq && state.apply_delta(&delta) {
+                    self.show_state(&state)?;
+                    self.game = Some((seq, state));
+                } else {
+                    console::log_1(&format!("Missed an update before {}", seq).into());
+                    send(ClientMessage::Resync);
+                }
+            }
+            ServerMessage::GameEnded {
+                winner,
+                final_state,
+                ..
+            } => {
+                self.show_state(&final_state)?;
+                self.ui.show_game_ended(winner)?;
+            }
+            // The results are over and the lobby is open for the next game
+            ServerMessage::PhaseChanged {
+                phase: RoomPhase::Lobby,
+            } => {
+                self.show_lobby()?;
+            }
+            ServerMessage::Error { message } => {
+                console::error_1(&format!("Server error: {}", message).into());
+            }
+            message => {
+                console::log_1(&format!("Received {:?}", message).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn show_lobby(&mut self) -> Result<(), JsValue> {
+        self.renderer = None;
+        self.ui.show_lobby(&self.players)
+    }
+
+    /// Draw a game state, switching to the game view first if needed
+    fn show_state(&mut self, game_state: &GameState) -> Result<(), JsValue> {
+        if self.renderer.is_none() {
+            self.ui.show_game()?;
+            let renderer = GameRenderer::new()?;
+            self.ui
+                .get_canvas_container()?
+                .append_child(renderer.get_canvas())?;
+            self.renderer = Some(renderer);
+        }
+
+        if let Some(renderer) = &self.renderer {
+            renderer.render(game_state, &self.players)?;
+        }
+        self.ui.update_game_info(game_state, &self.players)
+    }
+}
@@ -0,0 +1,296 @@
+//! Replay playback mode for the multiplayer snake game GUI
+//!
+//! This module loads a recorded game from the server and steps through it
+//! with play/pause, single steps, speed control and a tick scrubber, drawing
+//! each tick with the same renderer used for live games.
+
+use crate::canvas::GameRenderer;
+use crate::types::*;
+use crate::ui::UIManager;
+use gloo::timers::callback::Interval;
+use gloo_net::http::Request;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use wasm_bindgen::prelude::*;
+use web_sys::{window, Event, HtmlInputElement, HtmlSelectElement, KeyboardEvent, MouseEvent};
+
+/// Playback position and settings for a loaded replay
+pub struct ReplayPlayer {
+    replay: ReplayFrames,
+    frame: usize,
+    playing: bool,
+    speed: f64,
+}
+
+impl ReplayPlayer {
+    /// Create a player positioned at the start of the replay
+    pub fn new(replay: ReplayFrames) -> Result<Self, JsValue> {
+        if replay.frames.is_empty() {
+            return Err(JsValue::from_str("Replay contains no frames"));
+        }
+
+        Ok(Self {
+            replay,
+            frame: 0,
+            playing: false,
+            speed: 1.0,
+        })
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.replay.frames.len()
+    }
+
+    pub fn current_state(&self) -> &GameState {
+        &self.replay.frames[self.frame]
+    }
+
+    pub fn players(&self) -> &[LobbyPlayer] {
+        &self.replay.players
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.frame + 1 >= self.frame_count()
+    }
+
+    /// Jump to a frame, clamped to the replay length
+    pub fn seek(&mut self, frame: usize) {
+        self.frame = frame.min(self.frame_count() - 1);
+    }
+
+    /// Move forwards or backwards by a number of frames
+    pub fn step(&mut self, delta: isize) {
+        self.seek(self.frame.saturating_add_signed(delta));
+    }
+
+    /// Start or stop playback, rewinding if started at the end
+    pub fn set_playing(&mut self, playing: bool) {
+        if playing && self.is_at_end() {
+            self.frame = 0;
+        }
+        self.playing = playing;
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        if speed > 0.0 {
+            self.speed = speed;
+        }
+    }
+
+    /// Time between frames at the current speed
+    pub fn frame_interval_ms(&self) -> u32 {
+        (constants::GAME_TICK_DURATION_MS as f64 / self.speed).round().max(1.0) as u32
+    }
+}
+
+/// Replay player together with the UI it drives
+struct ReplayView {
+    player: ReplayPlayer,
+    ui: UIManager,
+    renderer: GameRenderer,
+    timer: Option<Interval>,
+}
+
+type SharedView = Rc<RefCell<ReplayView>>;
+
+/// Fetch a replay from the server and show it in playback mode
+pub async fn load_replay(id: &str) -> Result<(), JsValue> {
+    let ui = UIManager::new()?;
+    ui.show_connecting()?;
+
+    let replay = match fetch_replay(id).await {
+        Ok(replay) => replay,
+        Err(e) => {
+            ui.show_error(&e.as_string().unwrap_or_else(|| "Failed to load replay".to_string()))?;
+            return Err(e);
+        }
+    };
+    let player = ReplayPlayer::new(replay)?;
+
+    ui.show_game()?;
+    let renderer = GameRenderer::new()?;
+    ui.get_canvas_container()?.append_child(renderer.get_canvas())?;
+    ui.show_replay_controls(player.frame_count())?;
+
+    let view = Rc::new(RefCell::new(ReplayView {
+        player,
+        ui,
+        renderer,
+        timer: None,
+    }));
+
+    render(&view.borrow())?;
+    bind_controls(&view)?;
+
+    Ok(())
+}
+
+/// Download the game state at every tick of a replay
+async fn fetch_replay(id: &str) -> Result<ReplayFrames, JsValue> {
+    let url = format!("/replays/{}/frames", id);
+    let response = Request::get(&url)
+        .send()
+        .await
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "Replay {} could not be loaded (HTTP {})",
+            id,
+            response.status()
+        )));
+    }
+
+    response
+        .json::<ReplayFrames>()
+        .await
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Draw the current frame and update the info panel and controls
+fn render(view: &ReplayView) -> Result<(), JsValue> {
+    let player = &view.player;
+    let state = player.current_state();
+
+    view.renderer.render(state, player.players())?;
+    view.ui.update_game_info(state, player.players())?;
+    view.ui
+        .update_replay_controls(player.frame(), player.frame_count(), player.is_playing())
+}
+
+/// Start or stop the playback timer
+fn set_playing(view: &SharedView, playing: bool) {
+    let mut guard = view.borrow_mut();
+    let v = &mut *guard;
+    v.player.set_playing(playing);
+    v.timer = None;
+
+    if playing {
+        let weak = Rc::downgrade(view);
+        v.timer = Some(Interval::new(v.player.frame_interval_ms(), move || {
+            advance(&weak);
+        }));
+    }
+
+    if let Err(e) = render(v) {
+        web_sys::console::error_1(&e);
+    }
+}
+
+/// Show the next frame, pausing once the end of the replay is reached
+fn advance(view: &Weak<RefCell<ReplayView>>) {
+    let Some(view) = view.upgrade() else {
+        return;
+    };
+
+    let at_end = {
+        let mut v = view.borrow_mut();
+        v.player.step(1);
+        if let Err(e) = render(&v) {
+            web_sys::console::error_1(&e);
+        }
+        v.player.is_at_end()
+    };
+
+    if at_end {
+        // Stop outside of the interval callback, which is owned by the timer
+        wasm_bindgen_futures::spawn_local(async move {
+            set_playing(&view, false);
+        });
+    }
+}
+
+/// Pause playback and move to another frame
+fn seek(view: &SharedView, frame: impl FnOnce(&ReplayPlayer) -> usize) {
+    set_playing(view, false);
+
+    let mut v = view.borrow_mut();
+    let target = frame(&v.player);
+    v.player.seek(target);
+    if let Err(e) = render(&v) {
+        web_sys::console::error_1(&e);
+    }
+}
+
+/// Toggle between playing and paused
+fn toggle_playing(view: &SharedView) {
+    let playing = view.borrow().player.is_playing();
+    set_playing(view, !playing);
+}
+
+/// Attach event handlers to the playback controls
+fn bind_controls(view: &SharedView) -> Result<(), JsValue> {
+    let document = window().unwrap().document().unwrap();
+
+    let on_click = |id: &str, action: Box<dyn Fn(&SharedView)>| -> Result<(), JsValue> {
+        if let Some(button) = document.get_element_by_id(id) {
+            let view = view.clone();
+            let callback = Closure::wrap(Box::new(move |_event: MouseEvent| {
+                action(&view);
+            }) as Box<dyn FnMut(_)>);
+            button.add_event_listener_with_callback("click", callback.as_ref().unchecked_ref())?;
+            callback.forget();
+        }
+        Ok(())
+    };
+
+    on_click("replay-play", Box::new(toggle_playing))?;
+    on_click("replay-back", Box::new(|view| seek(view, |p| p.frame().saturating_sub(1))))?;
+    on_click("replay-forward", Box::new(|view| seek(view, |p| p.frame() + 1)))?;
+
+    if let Some(scrubber) = document.get_element_by_id("replay-scrubber") {
+        let scrubber = scrubber.dyn_into::<HtmlInputElement>()?;
+        let input = scrubber.clone();
+        let view = view.clone();
+        let callback = Closure::wrap(Box::new(move |_event: Event| {
+            let frame = input.value().parse().unwrap_or(0);
+            seek(&view, |_| frame);
+        }) as Box<dyn FnMut(_)>);
+        scrubber.add_event_listener_with_callback("input", callback.as_ref().unchecked_ref())?;
+        callback.forget();
+    }
+
+    if let Some(speed) = document.get_element_by_id("replay-speed") {
+        let speed = speed.dyn_into::<HtmlSelectElement>()?;
+        let select = speed.clone();
+        let view = view.clone();
+        let callback = Closure::wrap(Box::new(move |_event: Event| {
+            let multiplier = select.value().parse().unwrap_or(1.0);
+            let playing = {
+                let mut v = view.borrow_mut();
+                v.player.set_speed(multiplier);
+                v.player.is_playing()
+            };
+            // Restart the timer so the new speed takes effect immediately
+            if playing {
+                set_playing(&view, true);
+            }
+        }) as Box<dyn FnMut(_)>);
+        speed.add_event_listener_with_callback("change", callback.as_ref().unchecked_ref())?;
+        callback.forget();
+    }
+
+    // Keyboard shortcuts: space plays/pauses, arrow keys step
+    let view = view.clone();
+    let callback = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        match event.key().as_str() {
+            " " => toggle_playing(&view),
+            "ArrowLeft" => seek(&view, |p| p.frame().saturating_sub(1)),
+            "ArrowRight" => seek(&view, |p| p.frame() + 1),
+            _ => return,
+        }
+        event.prevent_default();
+    }) as Box<dyn FnMut(_)>);
+    document.add_event_listener_with_callback("keydown", callback.as_ref().unchecked_ref())?;
+    callback.forget();
+
+    Ok(())
+}
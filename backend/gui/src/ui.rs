@@ -6,8 +6,8 @@
 use crate::types::*;
 use wasm_bindgen::prelude::*;
 use web_sys::{
-    window, Document, Element, HtmlElement, HtmlButtonElement, 
-    MouseEvent
+    window, Document, Element, HtmlElement, HtmlButtonElement, HtmlInputElement,
+    HtmlOptionElement, HtmlSelectElement, MouseEvent
 };
 
 /// UI Manager that handles all DOM interactions
@@ -251,6 +251,28 @@ impl UIManager {
                 text-align: center;
                 margin-bottom: 30px;
             }
+            
+            .replay-controls {
+                display: flex;
+                align-items: center;
+                gap: 10px;
+                margin-top: 15px;
+                width: 100%;
+            }
+            
+            .replay-controls .button {
+                padding: 8px 14px;
+            }
+            
+            .replay-scrubber {
+                flex: 1;
+            }
+            
+            .replay-position {
+                color: #cccccc;
+                min-width: 110px;
+                text-align: right;
+            }
         "#;
         
         style.set_text_content(Some(css));
@@ -409,6 +431,78 @@ impl UIManager {
         Ok(())
     }
 
+    /// Show the playback controls below the game board
+    pub fn show_replay_controls(&self, frame_count: usize) -> Result<(), JsValue> {
+        let controls = self.create_element("div", Some("replay-controls"))?;
+
+        for (id, label) in [
+            ("replay-back", "⏮"),
+            ("replay-play", "▶"),
+            ("replay-forward", "⏭"),
+        ] {
+            let button = self.create_element("button", Some("button"))?;
+            button.set_id(id);
+            button.set_text_content(Some(label));
+            controls.append_child(&button)?;
+        }
+
+        let scrubber = self
+            .create_element("input", Some("replay-scrubber"))?
+            .dyn_into::<HtmlInputElement>()?;
+        scrubber.set_id("replay-scrubber");
+        scrubber.set_type("range");
+        scrubber.set_min("0");
+        scrubber.set_max(&frame_count.saturating_sub(1).to_string());
+        scrubber.set_value("0");
+        controls.append_child(&scrubber)?;
+
+        let speed = self
+            .create_element("select", None)?
+            .dyn_into::<HtmlSelectElement>()?;
+        speed.set_id("replay-speed");
+        for multiplier in constants::PLAYBACK_SPEEDS {
+            let option = self
+                .create_element("option", None)?
+                .dyn_into::<HtmlOptionElement>()?;
+            option.set_value(&multiplier.to_string());
+            option.set_text_content(Some(&format!("{}x", multiplier)));
+            option.set_selected(multiplier == 1.0);
+            speed.append_child(&option)?;
+        }
+        controls.append_child(&speed)?;
+
+        let position = self.create_element("span", Some("replay-position"))?;
+        position.set_id("replay-position");
+        controls.append_child(&position)?;
+
+        self.get_canvas_container()?.append_child(&controls)?;
+
+        Ok(())
+    }
+
+    /// Reflect the current playback position in the controls
+    pub fn update_replay_controls(&self, frame: usize, frame_count: usize, playing: bool) -> Result<(), JsValue> {
+        if let Some(button) = self.document.get_element_by_id("replay-play") {
+            button.set_text_content(Some(if playing { "⏸" } else { "▶" }));
+        }
+
+        if let Some(scrubber) = self.document.get_element_by_id("replay-scrubber") {
+            scrubber
+                .dyn_into::<HtmlInputElement>()?
+                .set_value(&frame.to_string());
+        }
+
+        if let Some(position) = self.document.get_element_by_id("replay-position") {
+            position.set_text_content(Some(&format!(
+                "Tick {} / {}",
+                frame,
+                frame_count.saturating_sub(1)
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Show game ended state
     pub fn show_game_ended(&self, winner: Option<LobbyPlayer>) -> Result<(), JsValue> {
         // Add winner announcement
@@ -431,6 +525,49 @@ impl UIManager {
         Ok(())
     }
 
+    /// Show the rating leaderboard
+    pub fn show_leaderboard(&self, entries: &[LeaderboardEntry]) -> Result<(), JsValue> {
+        self.clear_content()?;
+        
+        let container = self.create_element("div", Some("container lobby-container"))?;
+        
+        let title = self.create_element("h1", None)?;
+        title.set_text_content(Some("🏆 Leaderboard"));
+        
+        let subtitle = self.create_element("div", Some("subtitle"))?;
+        subtitle.set_text_content(Some("Rating (wins / games) over all finished matches"));
+        
+        let list = self.create_element("ul", Some("players-list"))?;
+        
+        if entries.is_empty() {
+            let item = self.create_element("li", Some("player-item"))?;
+            item.set_text_content(Some("No rated matches yet"));
+            list.append_child(&item)?;
+        }
+        
+        for entry in entries {
+            let item = self.create_element("li", Some("player-item"))?;
+            
+            let name_element = self.create_element("span", Some("player-name"))?;
+            name_element.set_text_content(Some(&format!("{}. {}", entry.rank, entry.name)));
+            
+            let rating_element = self.create_element("span", Some("player-length"))?;
+            rating_element.set_text_content(Some(&format!("{} ({}/{})", entry.rating, entry.wins, entry.games)));
+            
+            item.append_child(&name_element)?;
+            item.append_child(&rating_element)?;
+            list.append_child(&item)?;
+        }
+        
+        container.append_child(&title)?;
+        container.append_child(&subtitle)?;
+        container.append_child(&list)?;
+        
+        self.root_element.append_child(&container)?;
+        
+        Ok(())
+    }
+
     /// Show error state
     pub fn show_error(&self, message: &str) -> Result<(), JsValue> {
         self.clear_content()?;
@@ -481,6 +618,9 @@ impl UIManager {
         if let Some(snake) = snake {
             if !snake.is_alive {
                 class_list.push_str(" player-dead");
+                if let (Some(cause), Some(tick)) = (&snake.death_cause, snake.death_tick) {
+                    name_element.set_attribute("title", &format!("Died at tick {}: {}", tick, cause.label()))?;
+                }
             }
         }
         
@@ -0,0 +1,37 @@
+//! Responses of the server's HTTP API that the GUI reads
+//!
+//! Replays and the leaderboard are fetched over HTTP rather than the
+//! WebSocket, but are shared all the same so the GUI reads what the server
+//! writes.
+
+use crate::{GameState, LobbyPlayer};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A replay expanded into the game state at every tick, for playback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReplayFrames {
+    /// Replay identifier (the game id)
+    pub id: Uuid,
+    /// Players in join order
+    pub players: Vec<LobbyPlayer>,
+    /// Winner of the game, if any
+    pub winner: Option<Uuid>,
+    /// Game state before the first tick followed by the state after each tick
+    pub frames: Vec<GameState>,
+}
+
+/// A player's place on the leaderboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LeaderboardEntry {
+    /// Position on the leaderboard, starting at 1
+    pub rank: usize,
+    pub player_id: Uuid,
+    pub name: String,
+    /// Rating rounded to a whole number
+    pub rating: i64,
+    pub games: u32,
+    pub wins: u32,
+}
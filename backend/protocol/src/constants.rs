@@ -0,0 +1,47 @@
+//! Defaults and styling the server and its clients agree on
+//!
+//! Game rules here are only the server's defaults; the rules of a game in
+//! progress are in its `GameState` and the room's configuration.
+
+/// Grid dimensions
+pub const GRID_WIDTH: usize = 50;
+pub const GRID_HEIGHT: usize = 50;
+
+/// Snake game rules
+pub const WINNING_SNAKE_LENGTH: usize = 50;
+pub const INITIAL_SNAKE_LENGTH: usize = 1;
+
+/// Fruit spawning rules
+pub const FRUIT_SPAWN_DELAY_TICKS: u32 = 5;
+
+/// Game timing
+pub const GAME_TICK_DURATION_MS: u64 = 200;
+pub const MOVE_TIMEOUT_MS: u64 = 5000; // 5 seconds to make a move
+pub const RECONNECT_GRACE_MS: u64 = 10000; // 10 seconds to resume a dropped session
+
+/// Game state limits
+pub const MAX_PLAYERS: usize = 8;
+pub const MIN_PLAYERS: usize = 2;
+
+/// Colors for snake visualization (HTML color codes)
+pub const SNAKE_COLORS: [&str; 8] = [
+    "#FF6B6B", // Red
+    "#4ECDC4", // Teal
+    "#45B7D1", // Blue
+    "#96CEB4", // Green
+    "#FFEAA7", // Yellow
+    "#DDA0DD", // Plum
+    "#FFB347", // Orange
+    "#87CEEB", // Sky Blue
+];
+
+/// Dead snake color modifier (makes colors more gray/transparent)
+pub const DEAD_SNAKE_ALPHA: f32 = 0.5;
+
+/// Fruit color
+pub const FRUIT_COLOR: &str = "#FF1493"; // Deep Pink
+
+/// Grid styling
+pub const GRID_BACKGROUND_COLOR: &str = "#2C3E50";
+pub const GRID_LINE_COLOR: &str = "#34495E";
+pub const CELL_SIZE_PX: u32 = 12;
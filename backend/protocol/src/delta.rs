@@ -0,0 +1,238 @@
+//! Changes between two game states, for sending updates as deltas
+//!
+//! A delta lists what moved from one state to the next: the cells each
+//! snake gained at its head and lost at its tail, deaths, and fruits that
+//! were eaten or spawned. Snakes that did not change are left out, so a
+//! delta stays small however long the snakes grow.
+
+use crate::game::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Everything that changed from the state at `base_tick` to the state at `tick`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StateDelta {
+    /// Tick of the state the delta applies to
+    pub base_tick: u64,
+    /// Tick of the state after applying the delta
+    pub tick: u64,
+    /// Snakes that moved, grew or missed moves
+    pub snakes: Vec<SnakeDelta>,
+    /// Snakes that died
+    pub deaths: Vec<Death>,
+    /// Fruits that were spawned
+    pub fruits_added: Vec<Fruit>,
+    /// Positions of the fruits that were eaten
+    pub fruits_removed: Vec<Position>,
+    pub is_running: bool,
+    pub winner: Option<Uuid>,
+}
+
+/// How a snake changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SnakeDelta {
+    pub id: Uuid,
+    /// Cells added in front of the head, newest first
+    pub new_heads: Vec<Position>,
+    /// Number of cells dropped from the end of the tail
+    pub removed_tail: usize,
+    pub length: usize,
+    pub last_direction: Option<Direction>,
+    pub missed_moves: u32,
+}
+
+impl GameState {
+    /// Changes leading from this state to `next`
+    ///
+    /// Returns `None` if `next` is not a later state of the same game, e.g.
+    /// when snakes were added or the board was cleared, so it has to be
+    /// sent in full.
+    pub fn delta_to(&self, next: &GameState) -> Option<StateDelta> {
+        let same_game = self.seed == next.seed
+            && self.grid_width == next.grid_width
+            && self.grid_height == next.grid_height
+            && self.tick <= next.tick
+            && self.snakes.len() == next.snakes.len();
+        if !same_game {
+            return None;
+        }
+
+        let mut snakes = Vec::new();
+        let mut deaths = Vec::new();
+        for (id, before) in &self.snakes {
+            let after = next.snakes.get(id)?;
+            if before.is_alive != after.is_alive {
+                // Snakes only ever die
+                if after.is_alive {
+                    return None;
+                }
+                deaths.push(Death {
+                    snake_id: *id,
+                    cause: after.death_cause.clone()?,
+                    tick: after.death_tick?,
+                });
+            }
+
+            let delta = SnakeDelta::between(before, after);
+            let unchanged = delta.new_heads.is_empty()
+                && delta.removed_tail == 0
+                && delta.length == before.length
+                && delta.last_direction == before.last_direction
+                && delta.missed_moves == before.missed_moves;
+            if !unchanged {
+                snakes.push(delta);
+            }
+        }
+
+        Some(StateDelta {
+            base_tick: self.tick,
+            tick: next.tick,
+            snakes,
+            deaths,
+            fruits_added: next
+                .fruits
+                .iter()
+                .filter(|fruit| !self.fruits.contains(fruit))
+                .cloned()
+                .collect(),
+            fruits_removed: self
+                .fruits
+                .iter()
+                .filter(|fruit| !next.fruits.contains(fruit))
+                .map(|fruit| fruit.position)
+                .collect(),
+            is_running: next.is_running,
+            winner: next.winner,
+        })
+    }
+
+    /// Apply a delta to this state, returning whether it applied
+    ///
+    /// A delta for another base tick or for unknown snakes is rejected and
+    /// leaves the state as it was; the client then needs a full state.
+    pub fn apply_delta(&mut self, delta: &StateDelta) -> bool {
+        if delta.base_tick != self.tick {
+            return false;
+        }
+
+        let mut next = self.clone();
+        for change in &delta.snakes {
+            let Some(snake) = next.snakes.get_mut(&change.id) else {
+                return false;
+            };
+            if change.removed_tail > snake.body.len() {
+                return false;
+            }
+            snake.body.truncate(snake.body.len() - change.removed_tail);
+            for head in change.new_heads.iter().rev() {
+                snake.body.push_front(*head);
+            }
+            snake.length = change.length;
+            snake.last_direction = change.last_direction;
+            snake.missed_moves = change.missed_moves;
+        }
+        for death in &delta.deaths {
+            let Some(snake) = next.snakes.get_mut(&death.snake_id) else {
+                return false;
+            };
+            snake.kill(death.cause.clone(), death.tick);
+        }
+
+        next.fruits
+            .retain(|fruit| !delta.fruits_removed.contains(&fruit.position));
+        next.fruits.extend(delta.fruits_added.iter().cloned());
+        next.tick = delta.tick;
+        next.is_running = delta.is_running;
+        next.winner = delta.winner;
+
+        *self = next;
+        true
+    }
+}
+
+impl SnakeDelta {
+    /// Changes of a snake from one state to the next
+    fn between(before: &Snake, after: &Snake) -> Self {
+        // The body after a move is the new heads followed by what is left
+        // of the old body; with no overlap it was replaced as a whole
+        let new_heads = (0..=after.body.len())
+            .find(|&heads| {
+                let kept = after.body.len() - heads;
+                kept <= before.body.len()
+                    && after
+                        .body
+                        .iter()
+                        .skip(heads)
+                        .eq(before.body.iter().take(kept))
+            })
+            .unwrap_or(after.body.len());
+
+        Self {
+            id: after.id,
+            new_heads: after.body.iter().take(new_heads).copied().collect(),
+            removed_tail: before.body.len() - (after.body.len() - new_heads),
+            length: after.length,
+            last_direction: after.last_direction,
+            missed_moves: after.missed_moves,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snake(x: i32) -> Snake {
+        Snake::new(
+            Uuid::from_u128(x as u128),
+            format!("snake {}", x),
+            Position::new(x, 5),
+            0,
+            1,
+        )
+    }
+
+    #[test]
+    fn test_delta_rebuilds_the_next_state() {
+        let mut state = GameState::new(10, 10);
+        let (alice, bob) = (snake(1), snake(5));
+        let (alice_id, bob_id) = (alice.id, bob.id);
+        state.snakes.insert(alice.id, alice);
+        state.snakes.insert(bob.id, bob);
+        state.fruits.push(Fruit::new(Position::new(1, 4), 0));
+        state.is_running = true;
+
+        let mut next = state.clone();
+        next.tick = 2;
+        // Alice eats the fruit and moves on, bob hits itself
+        let alice = next.snakes.get_mut(&alice_id).unwrap();
+        alice.move_snake(Direction::Up, 10, 10, true);
+        alice.move_snake(Direction::Up, 10, 10, false);
+        next.snakes
+            .get_mut(&bob_id)
+            .unwrap()
+            .kill(DeathCause::SelfCollision, 1);
+        next.fruits = vec![Fruit::new(Position::new(7, 7), 1)];
+
+        let delta = state.delta_to(&next).unwrap();
+        assert_eq!(delta.snakes.len(), 1);
+        assert_eq!(delta.snakes[0].new_heads.len(), 2);
+        assert_eq!(delta.deaths.len(), 1);
+        assert_eq!(delta.fruits_removed, vec![Position::new(1, 4)]);
+
+        let mut rebuilt = state.clone();
+        assert!(rebuilt.apply_delta(&delta));
+        assert_eq!(rebuilt, next);
+
+        // The same delta does not apply twice
+        assert!(!rebuilt.apply_delta(&delta));
+        assert_eq!(rebuilt, next);
+
+        // A new game on the board can't be described as a delta
+        let mut restarted = next.clone();
+        restarted.snakes.remove(&bob_id);
+        assert!(next.delta_to(&restarted).is_none());
+    }
+}
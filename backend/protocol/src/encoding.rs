@@ -0,0 +1,163 @@
+//! Encodings messages are exchanged in
+//!
+//! Messages are JSON text frames unless a client asks for MessagePack or
+//! CBOR, either with the `?encoding=` query parameter or by offering the
+//! matching WebSocket subprotocol. Binary encodings keep the JSON layout:
+//! every message is a map with a `type` key naming the variant.
+
+#[cfg(feature = "codec")]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Wire format of a connection's messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// JSON in text frames
+    #[default]
+    Json,
+    /// MessagePack in binary frames
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    /// CBOR in binary frames
+    Cbor,
+}
+
+impl Encoding {
+    /// Every encoding, as offered to clients
+    pub const ALL: [Encoding; 3] = [Encoding::Json, Encoding::MessagePack, Encoding::Cbor];
+
+    /// WebSocket subprotocols for the encodings, most compact first
+    pub const SUBPROTOCOLS: [&'static str; 3] = ["snake.msgpack", "snake.cbor", "snake.json"];
+
+    /// WebSocket subprotocol selecting this encoding
+    pub fn subprotocol(self) -> &'static str {
+        match self {
+            Encoding::Json => "snake.json",
+            Encoding::MessagePack => "snake.msgpack",
+            Encoding::Cbor => "snake.cbor",
+        }
+    }
+
+    /// Encoding selected by a WebSocket subprotocol
+    pub fn from_subprotocol(subprotocol: &str) -> Option<Self> {
+        Encoding::ALL
+            .into_iter()
+            .find(|encoding| encoding.subprotocol() == subprotocol)
+    }
+
+    /// Whether messages are sent as binary rather than text frames
+    pub fn is_binary(self) -> bool {
+        self != Encoding::Json
+    }
+}
+
+#[cfg(feature = "codec")]
+impl Encoding {
+    /// Encode a message
+    pub fn encode<T: Serialize>(self, message: &T) -> Result<Vec<u8>, CodecError> {
+        match self {
+            Encoding::Json => serde_json::to_vec(message).map_err(CodecError::new),
+            // Named fields, since the `type` tag needs a map to live in
+            Encoding::MessagePack => rmp_serde::to_vec_named(message).map_err(CodecError::new),
+            Encoding::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(message, &mut bytes).map_err(CodecError::new)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Decode a message
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, CodecError> {
+        match self {
+            Encoding::Json => serde_json::from_slice(bytes).map_err(CodecError::new),
+            Encoding::MessagePack => rmp_serde::from_slice(bytes).map_err(CodecError::new),
+            Encoding::Cbor => ciborium::from_reader(bytes).map_err(CodecError::new),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::Json => "json",
+            Encoding::MessagePack => "msgpack",
+            Encoding::Cbor => "cbor",
+        })
+    }
+}
+
+/// A message that could not be encoded or decoded
+#[cfg(feature = "codec")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodecError(String);
+
+#[cfg(feature = "codec")]
+impl CodecError {
+    fn new(error: impl fmt::Display) -> Self {
+        Self(error.to_string())
+    }
+}
+
+#[cfg(feature = "codec")]
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "codec")]
+impl std::error::Error for CodecError {}
+
+#[cfg(all(test, feature = "codec"))]
+mod tests {
+    use super::*;
+    use crate::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_messages_survive_every_encoding() {
+        let mut state = GameState::new(10, 10);
+        let snake = Snake::new(
+            Uuid::from_u128(1),
+            "alice".into(),
+            Position::new(2, 3),
+            0,
+            1,
+        );
+        state.snakes.insert(snake.id, snake);
+        state.fruits.push(Fruit::new(Position::new(5, 5), 0));
+        let update = ServerMessage::game_update(&state, RoomPhase::Running);
+
+        for encoding in Encoding::ALL {
+            let bytes = encoding.encode(&update).unwrap();
+            match encoding.decode(&bytes).unwrap() {
+                ServerMessage::GameUpdate { game_state, .. } => assert_eq!(game_state, state),
+                message => panic!("{} decoded {:?}", encoding, message),
+            }
+
+            let bytes = encoding
+                .encode(&ClientMessage::SubmitMove {
+                    direction: Direction::Left,
+                    tick: 4,
+                })
+                .unwrap();
+            assert!(matches!(
+                encoding.decode(&bytes).unwrap(),
+                ClientMessage::SubmitMove {
+                    direction: Direction::Left,
+                    tick: 4
+                }
+            ));
+            assert_eq!(
+                Encoding::from_subprotocol(encoding.subprotocol()),
+                Some(encoding)
+            );
+        }
+
+        assert!(Encoding::Cbor.decode::<ClientMessage>(b"{}").is_err());
+    }
+}
@@ -0,0 +1,429 @@
+//! Game state shared by the server and its clients
+//!
+//! The grid, the snakes and fruits on it and how the game ended, together
+//! with the rules of movement that both sides need to agree on.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use uuid::Uuid;
+
+/// Represents a position on the game grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// Move position in the given direction, wrapping around grid boundaries
+    pub fn move_in_direction(
+        &self,
+        direction: Direction,
+        grid_width: i32,
+        grid_height: i32,
+    ) -> Position {
+        let mut new_x = self.x;
+        let mut new_y = self.y;
+
+        match direction {
+            Direction::Up => new_y -= 1,
+            Direction::Down => new_y += 1,
+            Direction::Left => new_x -= 1,
+            Direction::Right => new_x += 1,
+        }
+
+        // Handle wrapping around boundaries
+        if new_x < 0 {
+            new_x = grid_width - 1;
+        } else if new_x >= grid_width {
+            new_x = 0;
+        }
+
+        if new_y < 0 {
+            new_y = grid_height - 1;
+        } else if new_y >= grid_height {
+            new_y = 0;
+        }
+
+        Position::new(new_x, new_y)
+    }
+}
+
+/// Movement directions for snakes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// Get the opposite direction
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// Get all possible directions
+    pub fn all() -> [Direction; 4] {
+        [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+    }
+}
+
+/// Represents a player's snake
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Snake {
+    /// Unique identifier for the snake
+    pub id: Uuid,
+    /// Player name
+    pub player_name: String,
+    /// Snake body positions (head is first, tail is last)
+    pub body: VecDeque<Position>,
+    /// Current length of the snake
+    pub length: usize,
+    /// Whether the snake is alive
+    pub is_alive: bool,
+    /// Color index for this snake (maps to SNAKE_COLORS)
+    pub color_index: usize,
+    /// Last direction moved (used to prevent moving backwards)
+    pub last_direction: Option<Direction>,
+    /// Why the snake died, if it is dead
+    #[serde(default)]
+    pub death_cause: Option<DeathCause>,
+    /// Tick during which the snake died, if it is dead
+    #[serde(default)]
+    pub death_tick: Option<u64>,
+    /// Ticks in a row the player has not submitted a move for
+    #[serde(default)]
+    pub missed_moves: u32,
+}
+
+impl Snake {
+    pub fn new(
+        id: Uuid,
+        player_name: String,
+        initial_position: Position,
+        color_index: usize,
+        initial_length: usize,
+    ) -> Self {
+        let mut body = VecDeque::new();
+        body.push_back(initial_position);
+
+        Self {
+            id,
+            player_name,
+            body,
+            length: initial_length,
+            is_alive: true,
+            color_index,
+            last_direction: None,
+            death_cause: None,
+            death_tick: None,
+            missed_moves: 0,
+        }
+    }
+
+    /// Get the head position of the snake
+    pub fn head(&self) -> Option<Position> {
+        self.body.front().copied()
+    }
+
+    /// Get the tail position of the snake (excluding head)
+    pub fn tail(&self) -> Vec<Position> {
+        self.body.iter().skip(1).copied().collect()
+    }
+
+    /// Check if the snake contains a specific position
+    pub fn contains_position(&self, pos: Position) -> bool {
+        self.body.contains(&pos)
+    }
+
+    /// Get valid directions (cannot move backwards into tail)
+    pub fn valid_directions(&self) -> Vec<Direction> {
+        let mut valid = Direction::all().to_vec();
+
+        // If snake has a tail and we know the last direction, remove opposite
+        if self.body.len() > 1 {
+            if let Some(last_dir) = self.last_direction {
+                valid.retain(|&dir| dir != last_dir.opposite());
+            }
+        }
+
+        valid
+    }
+
+    /// Move the snake in the given direction
+    pub fn move_snake(
+        &mut self,
+        direction: Direction,
+        grid_width: i32,
+        grid_height: i32,
+        grow: bool,
+    ) {
+        if let Some(head) = self.head() {
+            let new_head = head.move_in_direction(direction, grid_width, grid_height);
+            self.body.push_front(new_head);
+            self.last_direction = Some(direction);
+
+            if grow {
+                self.length += 1;
+            } else {
+                // Remove tail if not growing
+                if self.body.len() > self.length {
+                    self.body.pop_back();
+                }
+            }
+        }
+    }
+
+    /// Kill the snake, recording why and during which tick it died
+    pub fn kill(&mut self, cause: DeathCause, tick: u64) {
+        self.is_alive = false;
+        self.death_cause = Some(cause);
+        self.death_tick = Some(tick);
+    }
+}
+
+/// Why a snake died
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type")]
+pub enum DeathCause {
+    /// Moved into the same cell as the heads of the listed snakes
+    HeadOnCollision { with: Vec<Uuid> },
+    /// Moved into the body of another snake (alive or dead)
+    BodyCollision { with: Uuid },
+    /// Moved into its own body
+    SelfCollision,
+    /// Submitted a direction that was not allowed
+    InvalidMove,
+    /// Did not submit a move before the deadline
+    MoveTimeout,
+    /// Disconnected from the server during the game
+    Disconnected,
+    /// Removed from the game by an admin
+    Kicked,
+}
+
+impl DeathCause {
+    /// Short human readable description
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeathCause::HeadOnCollision { .. } => "head-on collision",
+            DeathCause::BodyCollision { .. } => "hit another snake",
+            DeathCause::SelfCollision => "hit itself",
+            DeathCause::InvalidMove => "invalid move",
+            DeathCause::MoveTimeout => "move timeout",
+            DeathCause::Disconnected => "disconnected",
+            DeathCause::Kicked => "kicked",
+        }
+    }
+}
+
+/// A snake death, together with its cause
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Death {
+    pub snake_id: Uuid,
+    pub cause: DeathCause,
+    /// Tick during which the snake died
+    #[serde(default)]
+    pub tick: u64,
+}
+
+/// A player's final standing in a game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Placement {
+    pub player_id: Uuid,
+    /// Placement starting at 1; players who went out together with equal
+    /// length share a placement
+    pub placement: usize,
+    /// Length of the snake when the game ended
+    pub length: usize,
+    /// Tick the snake died, or `null` if it survived
+    pub death_tick: Option<u64>,
+}
+
+/// Represents a piece of fruit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Fruit {
+    pub position: Position,
+    pub spawn_tick: u64,
+}
+
+impl Fruit {
+    pub fn new(position: Position, spawn_tick: u64) -> Self {
+        Self {
+            position,
+            spawn_tick,
+        }
+    }
+}
+
+/// Current state of the game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct GameState {
+    /// All snakes in the game, ordered by id for deterministic iteration
+    pub snakes: BTreeMap<Uuid, Snake>,
+    /// All fruits on the board
+    pub fruits: Vec<Fruit>,
+    /// Current game tick
+    pub tick: u64,
+    /// Whether the game is running
+    pub is_running: bool,
+    /// Winner of the game (if any)
+    pub winner: Option<Uuid>,
+    /// Grid dimensions
+    pub grid_width: i32,
+    pub grid_height: i32,
+    /// Seed the game's randomness was drawn from, for reproducing it
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl GameState {
+    /// Create an empty board of the given size, before a game is started
+    pub fn new(grid_width: usize, grid_height: usize) -> Self {
+        Self {
+            snakes: BTreeMap::new(),
+            fruits: Vec::new(),
+            tick: 0,
+            is_running: false,
+            winner: None,
+            grid_width: grid_width as i32,
+            grid_height: grid_height as i32,
+            seed: None,
+        }
+    }
+
+    /// Get all occupied positions on the grid
+    pub fn occupied_positions(&self) -> Vec<Position> {
+        let mut positions = Vec::new();
+
+        // Add all snake body positions
+        for snake in self.snakes.values() {
+            positions.extend(snake.body.iter().copied());
+        }
+
+        // Add all fruit positions
+        for fruit in &self.fruits {
+            positions.push(fruit.position);
+        }
+
+        positions
+    }
+
+    /// Get empty positions on the grid
+    pub fn empty_positions(&self) -> Vec<Position> {
+        let occupied = self.occupied_positions();
+        let mut empty = Vec::new();
+
+        for x in 0..self.grid_width {
+            for y in 0..self.grid_height {
+                let pos = Position::new(x, y);
+                if !occupied.contains(&pos) {
+                    empty.push(pos);
+                }
+            }
+        }
+
+        empty
+    }
+
+    /// Deaths that happened during the given tick
+    pub fn deaths_at(&self, tick: u64) -> Vec<Death> {
+        self.snakes
+            .values()
+            .filter(|snake| snake.death_tick == Some(tick))
+            .filter_map(|snake| {
+                snake.death_cause.clone().map(|cause| Death {
+                    snake_id: snake.id,
+                    cause,
+                    tick,
+                })
+            })
+            .collect()
+    }
+
+    /// Check if the game is over
+    pub fn is_game_over(&self, winning_length: usize) -> bool {
+        let alive_snakes: Vec<_> = self.snakes.values().filter(|s| s.is_alive).collect();
+
+        // Game over if only one snake alive or someone reached winning length
+        alive_snakes.len() <= 1 || alive_snakes.iter().any(|s| s.length >= winning_length)
+    }
+
+    /// Get the winner of the game
+    pub fn get_winner(&self, winning_length: usize) -> Option<Uuid> {
+        let alive_snakes: Vec<_> = self.snakes.values().filter(|s| s.is_alive).collect();
+
+        // Check for length winner first
+        for snake in &alive_snakes {
+            if snake.length >= winning_length {
+                return Some(snake.id);
+            }
+        }
+
+        // Check for last snake standing
+        if alive_snakes.len() == 1 {
+            return Some(alive_snakes[0].id);
+        }
+
+        None
+    }
+
+    /// Rank every player, best first
+    ///
+    /// The winner comes first, then the other survivors, then the dead in
+    /// reverse order of death, so snakes that died later place better.
+    /// Snakes going out on the same tick are separated by length, and
+    /// share a placement if that ties too.
+    pub fn ranking(&self) -> Vec<Placement> {
+        let standing = |snake: &Snake| {
+            (
+                self.winner == Some(snake.id),
+                snake.is_alive,
+                snake.death_tick.unwrap_or(u64::MAX),
+                snake.length,
+            )
+        };
+
+        let mut snakes: Vec<&Snake> = self.snakes.values().collect();
+        snakes.sort_by_key(|snake| std::cmp::Reverse(standing(snake)));
+
+        let mut ranking: Vec<Placement> = Vec::with_capacity(snakes.len());
+        for (index, snake) in snakes.iter().enumerate() {
+            let placement = match index.checked_sub(1).map(|prev| snakes[prev]) {
+                Some(prev) if standing(prev) == standing(snake) => ranking[index - 1].placement,
+                _ => index + 1,
+            };
+            ranking.push(Placement {
+                player_id: snake.id,
+                placement,
+                length: snake.length,
+                death_tick: snake.death_tick,
+            });
+        }
+        ranking
+    }
+}
@@ -0,0 +1,67 @@
+//! Wire protocol of the multiplayer snake game
+//!
+//! The game state, WebSocket messages and HTTP responses shared by the
+//! server and the WASM GUI, so that a change to the protocol breaks the build of both instead
+//! of one side silently misreading the other. The crate builds for native
+//! and `wasm32` targets; the `openapi` feature adds `utoipa` schemas for
+//! the server's API documentation and the `codec` feature encodes
+//! messages as JSON, MessagePack or CBOR.
+
+mod api;
+pub mod constants;
+mod delta;
+mod encoding;
+mod game;
+mod messages;
+mod rules;
+
+pub use api::*;
+pub use delta::*;
+pub use encoding::*;
+pub use game::*;
+pub use messages::*;
+pub use rules::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_keep_their_wire_format() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type":"SubmitMove","direction":"Left","tick":3}"#).unwrap();
+        assert!(matches!(
+            message,
+            ClientMessage::SubmitMove {
+                direction: Direction::Left,
+                tick: 3
+            }
+        ));
+
+        // A move without its tick could be applied to a later one
+        assert!(serde_json::from_str::<ClientMessage>(
+            r#"{"type":"SubmitMove","direction":"Left"}"#
+        )
+        .is_err());
+
+        // Fields added later default for messages from older servers
+        let message: ServerMessage =
+            serde_json::from_str(r#"{"type":"LobbyState","players":[]}"#).unwrap();
+        assert!(matches!(
+            message,
+            ServerMessage::LobbyState {
+                phase: RoomPhase::Lobby,
+                ..
+            }
+        ));
+
+        let json = serde_json::to_value(ServerMessage::PhaseChanged {
+            phase: RoomPhase::Ended,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "PhaseChanged", "phase": "Ended"})
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Messages exchanged over the WebSocket connections
+//!
+//! Every message is a JSON object whose `type` field names the variant.
+//! The server opens every connection with `Hello`; clients answering with
+//! their own `Hello` agree on a protocol version and features.
+
+use crate::delta::StateDelta;
+use crate::encoding::Encoding;
+use crate::game::*;
+use crate::rules::GameConfig;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Version of the protocol described by this crate, raised whenever a
+/// message changes in a way the other side could misread
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest protocol version that is still spoken
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Optional parts of the protocol that are only used when both sides agree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// Game updates as `GameDelta`s between keyframes
+    Deltas,
+    /// A feature from a newer version of the protocol, which is never agreed on
+    #[serde(other)]
+    Unknown,
+}
+
+/// Messages sent from client to server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type")]
+pub enum ClientMessage {
+    /// Protocol versions and features the client speaks, in answer to the
+    /// server's `Hello`; clients that never send it keep the defaults of
+    /// their connection
+    Hello {
+        /// Newest protocol version the client speaks
+        protocol_version: u32,
+        /// Oldest version the client can fall back to; without it the
+        /// client only speaks `protocol_version`
+        #[serde(default)]
+        min_protocol_version: Option<u32>,
+        /// Features the client would like to use
+        #[serde(default)]
+        features: Vec<Feature>,
+    },
+    /// Join the game lobby, renaming the seat taken on connecting if the name is free
+    JoinLobby { player_name: String },
+    /// Submit a move for the current tick
+    SubmitMove {
+        direction: Direction,
+        /// Tick from the `MoveRequest` being answered; moves for any other
+        /// tick are rejected
+        tick: u64,
+    },
+    /// Ready to start the game (from GUI), optionally with a fixed seed
+    StartGame {
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    /// Ask for the full game state after a gap in the `seq` of delta
+    /// updates; it arrives as a `GameUpdate` keyframe
+    Resync,
+    /// Ping to keep connection alive
+    Ping,
+}
+
+/// Messages sent from server to client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type")]
+pub enum ServerMessage {
+    /// First message on every connection, describing what the server speaks
+    Hello {
+        /// Newest protocol version the server speaks
+        protocol_version: u32,
+        /// Oldest protocol version the server speaks
+        min_protocol_version: u32,
+        /// Encodings a connection can ask for
+        encodings: Vec<Encoding>,
+        /// Optional features the server supports
+        features: Vec<Feature>,
+        /// Rules of the room's games
+        rules: GameConfig,
+    },
+    /// Answer to the client's `Hello`: the protocol version the connection
+    /// uses from now on and the features that are turned on
+    Welcome {
+        protocol_version: u32,
+        features: Vec<Feature>,
+    },
+    /// Confirmation of joining lobby
+    LobbyJoined {
+        player_id: Uuid,
+        player_name: String,
+        /// Room the player was seated in
+        room: String,
+        /// Token for reconnecting to the same seat with `?resume_token=`
+        resume_token: Uuid,
+    },
+    /// Current lobby state
+    LobbyState {
+        players: Vec<LobbyPlayer>,
+        /// Where the room is in its game lifecycle
+        #[serde(default)]
+        phase: RoomPhase,
+    },
+    /// Game has started
+    GameStarted {
+        game_state: GameState,
+        your_snake_id: Uuid,
+    },
+    /// Game state update
+    GameUpdate {
+        game_state: GameState,
+        /// Snakes that died during the tick that was just processed
+        #[serde(default)]
+        deaths: Vec<Death>,
+        /// Where the room is in its game lifecycle
+        #[serde(default)]
+        phase: RoomPhase,
+        /// Position in the stream of updates, only on connections that
+        /// asked for deltas; such a full update is a keyframe
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        seq: Option<u64>,
+    },
+    /// Changes since the previous update, on connections that asked for
+    /// deltas
+    GameDelta {
+        /// Position in the stream of updates, one more than the previous
+        /// `GameUpdate` or `GameDelta`; on a gap ask for a `Resync`
+        seq: u64,
+        delta: StateDelta,
+        /// Where the room is in its game lifecycle
+        phase: RoomPhase,
+    },
+    /// Request for next move
+    MoveRequest {
+        /// Tick the move is for, to be echoed in `SubmitMove`
+        tick: u64,
+        valid_directions: Vec<Direction>,
+        /// Time left until the deadline for this tick
+        time_limit_ms: u64,
+    },
+    /// No move arrived in time, so one was made according to the
+    /// game's `missed_move_policy`
+    MoveFallback {
+        /// Tick the move was missed in
+        tick: u64,
+        /// Direction the snake was moved in
+        direction: Direction,
+        /// Ticks in a row without a move from the player
+        missed_moves: u32,
+    },
+    /// Enough players are in the lobby and a game starts on its own soon
+    GameStarting {
+        /// Time until the game starts in milliseconds
+        starts_in_ms: u64,
+    },
+    /// Players left during the countdown and the game no longer starts
+    CountdownCancelled,
+    /// The room moved on in its game lifecycle; `Lobby` after a game means
+    /// the results are over and the next game can be started
+    PhaseChanged { phase: RoomPhase },
+    /// The game was paused by an admin; no moves are processed until it resumes
+    GamePaused,
+    /// The game was resumed and moves for the open tick are accepted again
+    GameResumed,
+    /// Game ended
+    GameEnded {
+        winner: Option<LobbyPlayer>,
+        /// Every player's final placement, best first
+        ranking: Vec<Placement>,
+        final_state: GameState,
+    },
+    /// Error message
+    Error { message: String },
+    /// Pong response to ping
+    Pong,
+}
+
+impl ServerMessage {
+    /// Build a `GameUpdate` for the state after a processed tick
+    pub fn game_update(game_state: &GameState, phase: RoomPhase) -> Self {
+        ServerMessage::GameUpdate {
+            deaths: game_state.deaths_at(game_state.tick.saturating_sub(1)),
+            game_state: game_state.clone(),
+            phase,
+            seq: None,
+        }
+    }
+
+    /// Build a `MoveFallback` if a snake was moved for its player in the
+    /// last processed tick
+    pub fn move_fallback(game_state: &GameState, snake_id: &Uuid) -> Option<Self> {
+        let tick = game_state.tick.checked_sub(1)?;
+        let snake = game_state.snakes.get(snake_id)?;
+
+        let moved_for_player = snake.missed_moves > 0
+            && snake.death_cause != Some(DeathCause::MoveTimeout)
+            && snake.death_tick.is_none_or(|death_tick| death_tick == tick);
+
+        moved_for_player.then_some(ServerMessage::MoveFallback {
+            tick,
+            direction: snake.last_direction?,
+            missed_moves: snake.missed_moves,
+        })
+    }
+}
+
+/// Player information in lobby
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LobbyPlayer {
+    pub id: Uuid,
+    pub name: String,
+    pub color_index: usize,
+    pub is_ready: bool,
+}
+
+/// Stage of a room's game lifecycle
+///
+/// A room waits in `Lobby` for players, may count down before a game starts
+/// on its own, runs the game, and shows the results in `Ended` before it
+/// opens the lobby again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum RoomPhase {
+    /// Waiting for players and for the next game to be started
+    #[default]
+    Lobby,
+    /// Enough players are in and the game starts when the countdown ends
+    Countdown,
+    /// A game is being played
+    Running,
+    /// The game is over and its results are shown
+    Ended,
+}
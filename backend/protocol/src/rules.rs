@@ -0,0 +1,144 @@
+//! Rules of a room's games, as configured on the server and announced to clients
+
+use crate::constants;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// What happens to a snake whose player does not submit a move in time
+///
+/// In a config file this is `"kill"`, `"continue"`, `"random"` or
+/// `{ allow_misses = 3 }`; on the command line `allow_misses:3`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MissedMovePolicy {
+    /// The snake dies
+    #[default]
+    Kill,
+    /// The snake keeps moving in its last direction
+    Continue,
+    /// The snake makes a random valid move
+    Random,
+    /// The snake keeps moving in its last direction for up to this many
+    /// missed ticks in a row and dies on the next one
+    AllowMisses(u32),
+}
+
+impl fmt::Display for MissedMovePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MissedMovePolicy::Kill => write!(f, "kill"),
+            MissedMovePolicy::Continue => write!(f, "continue"),
+            MissedMovePolicy::Random => write!(f, "random"),
+            MissedMovePolicy::AllowMisses(misses) => write!(f, "allow_misses:{}", misses),
+        }
+    }
+}
+
+impl FromStr for MissedMovePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kill" => Ok(MissedMovePolicy::Kill),
+            "continue" => Ok(MissedMovePolicy::Continue),
+            "random" => Ok(MissedMovePolicy::Random),
+            _ => s
+                .strip_prefix("allow_misses:")
+                .and_then(|misses| misses.parse().ok())
+                .map(MissedMovePolicy::AllowMisses)
+                .ok_or_else(|| {
+                    format!(
+                        "invalid missed move policy '{}' (expected kill, continue, random or allow_misses:<n>)",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+/// Game rules that are in force for a match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct GameConfig {
+    /// Grid width in cells
+    pub grid_width: usize,
+    /// Grid height in cells
+    pub grid_height: usize,
+    /// Length at which a snake wins the game outright
+    pub winning_snake_length: usize,
+    /// Length of every snake when the game starts
+    pub initial_snake_length: usize,
+    /// Ticks between a fruit being eaten and a new one spawning
+    pub fruit_spawn_delay_ticks: u32,
+    /// Minimum duration of a game tick in milliseconds
+    pub game_tick_duration_ms: u64,
+    /// Time players have to submit a move each tick in milliseconds
+    pub move_timeout_ms: u64,
+    /// What happens to snakes whose player misses the move deadline
+    pub missed_move_policy: MissedMovePolicy,
+    /// Time a player who dropped out of a running game has to reconnect
+    /// before their snake dies, in milliseconds
+    pub reconnect_grace_ms: u64,
+    /// Maximum number of players in a lobby
+    pub max_players: usize,
+    /// Minimum number of players required to start a game
+    pub min_players: usize,
+    /// Start a game on its own this many milliseconds after enough players
+    /// are in the lobby, and again after every game; off if unset
+    pub auto_start_countdown_ms: Option<u64>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            grid_width: constants::GRID_WIDTH,
+            grid_height: constants::GRID_HEIGHT,
+            winning_snake_length: constants::WINNING_SNAKE_LENGTH,
+            initial_snake_length: constants::INITIAL_SNAKE_LENGTH,
+            fruit_spawn_delay_ticks: constants::FRUIT_SPAWN_DELAY_TICKS,
+            game_tick_duration_ms: constants::GAME_TICK_DURATION_MS,
+            move_timeout_ms: constants::MOVE_TIMEOUT_MS,
+            missed_move_policy: MissedMovePolicy::default(),
+            reconnect_grace_ms: constants::RECONNECT_GRACE_MS,
+            max_players: constants::MAX_PLAYERS,
+            min_players: constants::MIN_PLAYERS,
+            auto_start_countdown_ms: None,
+        }
+    }
+}
+
+impl GameConfig {
+    /// Check that the rules describe a playable game
+    pub fn validate(&self) -> Result<(), String> {
+        if self.grid_width == 0 || self.grid_height == 0 {
+            return Err("grid dimensions must be positive".to_string());
+        }
+        if self.grid_width > i32::MAX as usize || self.grid_height > i32::MAX as usize {
+            return Err("grid dimensions are too large".to_string());
+        }
+        if self.initial_snake_length == 0 {
+            return Err("initial_snake_length must be positive".to_string());
+        }
+        if self.winning_snake_length <= self.initial_snake_length {
+            return Err(
+                "winning_snake_length must be greater than initial_snake_length".to_string(),
+            );
+        }
+        if self.game_tick_duration_ms == 0 {
+            return Err("game_tick_duration_ms must be positive".to_string());
+        }
+        if self.move_timeout_ms == 0 {
+            return Err("move_timeout_ms must be positive".to_string());
+        }
+        if self.min_players == 0 || self.min_players > self.max_players {
+            return Err("min_players must be between 1 and max_players".to_string());
+        }
+        if self.max_players > self.grid_width * self.grid_height {
+            return Err("max_players does not fit on the grid".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+//! Registered player identities authenticated with API keys
+//!
+//! A registered player connects with their API key instead of just picking
+//! a name, and keeps the same player id across connections and games. The
+//! registry is kept in a JSON file so identities survive restarts. Only a
+//! SHA-256 hash of each key is stored; the key itself is shown once, when
+//! the player is registered.
+
+use crate::types::GameError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A registered player, as shown to admins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RegisteredPlayer {
+    /// Player id used in every game the player joins
+    pub id: Uuid,
+    /// Name shown in the lobby
+    pub name: String,
+}
+
+/// A registered player together with their API key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PlayerAccount {
+    pub id: Uuid,
+    pub name: String,
+    /// Key to connect with, as `X-API-Key` header or `api_key` query parameter
+    pub api_key: String,
+}
+
+/// A registered player as kept in the players file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredAccount {
+    id: Uuid,
+    name: String,
+    /// Hash of the player's API key, see `hash_secret`
+    api_key_hash: String,
+}
+
+impl StoredAccount {
+    fn player(&self) -> RegisteredPlayer {
+        RegisteredPlayer {
+            id: self.id,
+            name: self.name.clone(),
+        }
+    }
+}
+
+/// Hex encoded SHA-256 hash of a secret, the form API keys and the admin
+/// token are kept in
+pub fn hash_secret(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+/// Check a secret against a hash from `hash_secret`, in constant time
+pub fn verify_secret(secret: &str, hash: &str) -> bool {
+    let presented = hash_secret(secret);
+    presented.len() == hash.len()
+        && presented
+            .bytes()
+            .zip(hash.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Request body for registering a player
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RegisterPlayerRequest {
+    /// Name the player will be shown with
+    pub name: String,
+}
+
+/// Errors raised while registering players
+#[derive(Debug, thiserror::Error)]
+pub enum AccountError {
+    #[error("A player named {0} is already registered")]
+    NameTaken(String),
+
+    #[error("Player names must not be empty")]
+    EmptyName,
+
+    #[error("Registered player not found: {0}")]
+    NotFound(Uuid),
+
+    #[error("Failed to write player registry: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl From<AccountError> for GameError {
+    fn from(error: AccountError) -> Self {
+        match error {
+            AccountError::NameTaken(name) => GameError::NameTaken(name),
+            AccountError::EmptyName => GameError::InvalidRequest(error.to_string()),
+            AccountError::NotFound(id) => GameError::PlayerNotFound(id),
+            AccountError::Io(e) => GameError::Internal(e.to_string()),
+        }
+    }
+}
+
+/// Registered players, stored in a JSON file
+#[derive(Debug, Clone)]
+pub struct PlayerRegistry {
+    path: Arc<PathBuf>,
+    accounts: Arc<RwLock<Vec<StoredAccount>>>,
+}
+
+impl PlayerRegistry {
+    /// Load the registry, starting empty if the file does not exist yet
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let accounts: Vec<StoredAccount> = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            path: Arc::new(path),
+            accounts: Arc::new(RwLock::new(accounts)),
+        })
+    }
+
+    /// The player an API key belongs to
+    pub fn authenticate(&self, api_key: &str) -> Option<RegisteredPlayer> {
+        self.read()
+            .iter()
+            .find(|account| verify_secret(api_key, &account.api_key_hash))
+            .map(StoredAccount::player)
+    }
+
+    /// A registered player by id
+    pub fn get(&self, id: Uuid) -> Option<RegisteredPlayer> {
+        self.read()
+            .iter()
+            .find(|account| account.id == id)
+            .map(StoredAccount::player)
+    }
+
+    /// Whether a player id belongs to a registered player
+    pub fn is_registered(&self, id: Uuid) -> bool {
+        self.read().iter().any(|account| account.id == id)
+    }
+
+    /// Whether a name belongs to a registered player, ignoring surrounding
+    /// whitespace as `register` does
+    pub fn is_registered_name(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.read().iter().any(|account| account.name == name)
+    }
+
+    /// All registered players, ordered by name
+    pub fn list(&self) -> Vec<RegisteredPlayer> {
+        let mut players: Vec<RegisteredPlayer> =
+            self.read().iter().map(StoredAccount::player).collect();
+        players.sort_by(|a, b| a.name.cmp(&b.name));
+        players
+    }
+
+    /// Register a player under a new id and API key
+    pub fn register(&self, name: &str) -> Result<PlayerAccount, AccountError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AccountError::EmptyName);
+        }
+
+        let mut accounts = self.write();
+        if accounts.iter().any(|account| account.name == name) {
+            return Err(AccountError::NameTaken(name.to_string()));
+        }
+
+        let account = PlayerAccount {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            api_key: format!("sk_{}", Uuid::new_v4().simple()),
+        };
+        accounts.push(StoredAccount {
+            id: account.id,
+            name: account.name.clone(),
+            api_key_hash: hash_secret(&account.api_key),
+        });
+
+        if let Err(e) = self.save(&accounts) {
+            accounts.pop();
+            return Err(e.into());
+        }
+        Ok(account)
+    }
+
+    /// Revoke a player's API key
+    pub fn remove(&self, id: Uuid) -> Result<(), AccountError> {
+        let mut accounts = self.write();
+        let index = accounts
+            .iter()
+            .position(|account| account.id == id)
+            .ok_or(AccountError::NotFound(id))?;
+
+        let account = accounts.remove(index);
+        if let Err(e) = self.save(&accounts) {
+            accounts.insert(index, account);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn save(&self, accounts: &[StoredAccount]) -> io::Result<()> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(self.path.as_path(), serde_json::to_string_pretty(accounts)?)
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Vec<StoredAccount>> {
+        self.accounts.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Vec<StoredAccount>> {
+        self.accounts.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_registered_players_survive_reload() {
+        let dir = TempDir::new("players");
+        let path = dir.join("players.json");
+        let registry = PlayerRegistry::load(&path).unwrap();
+
+        let alice = registry.register("alice").unwrap();
+        assert!(matches!(
+            registry.register("alice"),
+            Err(AccountError::NameTaken(_))
+        ));
+        assert!(registry.register("  ").is_err());
+
+        // Only a hash of the key is written to the file
+        assert!(!std::fs::read_to_string(&path)
+            .unwrap()
+            .contains(&alice.api_key));
+
+        let reloaded = PlayerRegistry::load(&path).unwrap();
+        let player = reloaded.authenticate(&alice.api_key).unwrap();
+        assert_eq!(player.id, alice.id);
+        assert!(reloaded.is_registered_name("alice"));
+        assert!(reloaded.is_registered_name(" alice "));
+        assert!(reloaded.authenticate("sk_wrong").is_none());
+
+        reloaded.remove(alice.id).unwrap();
+        assert!(PlayerRegistry::load(&path)
+            .unwrap()
+            .authenticate(&alice.api_key)
+            .is_none());
+    }
+}
@@ -0,0 +1,147 @@
+//! Offline bot matches for the multiplayer snake game
+//!
+//! Plays a batch of games between built-in bots as fast as possible and
+//! prints how often each bot won:
+//!
+//! ```text
+//! snake-sim --bots random,random,random --games 1000 --seed 42
+//! ```
+
+use backend::bots::{create_bot, Bot, BOT_NAMES};
+use backend::config::{Config, GameConfig};
+use backend::sim::{run_games, SimOptions, SimReport};
+use clap::Parser;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Command line flags of the simulator
+#[derive(Debug, Parser)]
+#[command(
+    name = "snake-sim",
+    about = "Run offline snake matches between local bots"
+)]
+struct SimArgs {
+    /// Comma separated bots, one per snake (e.g. `random,random`)
+    #[arg(long, value_delimiter = ',', required = true)]
+    bots: Vec<String>,
+
+    /// Number of games to play
+    #[arg(long, default_value_t = 100)]
+    games: usize,
+
+    /// Seed of the first game; random if omitted
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Ticks after which an unfinished game counts as a draw
+    #[arg(long, default_value_t = 10_000)]
+    max_ticks: u64,
+
+    /// Number of worker threads; defaults to the number of CPUs
+    #[arg(long)]
+    jobs: Option<usize>,
+
+    /// TOML or JSON config file whose `[game]` rules are used
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+fn main() {
+    let args = SimArgs::parse();
+
+    if let Err(message) = run(args) {
+        eprintln!("error: {}", message);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: SimArgs) -> Result<(), String> {
+    if args.bots.len() < 2 {
+        return Err("at least two bots are needed for a match".to_string());
+    }
+    if let Some(unknown) = args.bots.iter().find(|name| create_bot(name, 0).is_none()) {
+        return Err(format!(
+            "unknown bot '{}' (available: {})",
+            unknown,
+            BOT_NAMES.join(", ")
+        ));
+    }
+
+    let config = match &args.config {
+        Some(path) => Config::from_file(path).map_err(|e| e.to_string())?.game,
+        None => GameConfig::default(),
+    };
+    config.validate().map_err(|e| e.to_string())?;
+
+    let options = SimOptions {
+        config,
+        games: args.games,
+        seed: args.seed.unwrap_or_else(rand::random),
+        max_ticks: args.max_ticks,
+        jobs: args
+            .jobs
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get())),
+    };
+
+    let names = &args.bots;
+    let make_bots = |seed: u64| -> Vec<Box<dyn Bot>> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(seat, name)| {
+                let bot_seed = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ seat as u64;
+                create_bot(name, bot_seed).expect("bot names are checked above")
+            })
+            .collect()
+    };
+
+    let started = Instant::now();
+    let report = run_games(&options, make_bots).map_err(|e| e.to_string())?;
+    let elapsed = started.elapsed().as_secs_f64();
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        println!("{}", json);
+    } else {
+        print_report(&report, options.seed, elapsed);
+    }
+
+    Ok(())
+}
+
+fn print_report(report: &SimReport, seed: u64, elapsed: f64) {
+    println!(
+        "Played {} games (seed {}) in {:.2}s, {:.0} games/s, {:.1} ticks per game",
+        report.games,
+        seed,
+        elapsed,
+        report.games as f64 / elapsed.max(f64::EPSILON),
+        report.average_ticks()
+    );
+    println!();
+    println!(
+        "{:<4} {:<16} {:>8} {:>9}",
+        "Seat", "Bot", "Wins", "Win rate"
+    );
+
+    for (seat, name) in report.bots.iter().enumerate() {
+        println!(
+            "{:<4} {:<16} {:>8} {:>8.1}%",
+            seat,
+            name,
+            report.wins[seat],
+            report.win_rate(seat) * 100.0
+        );
+    }
+    println!(
+        "{:<4} {:<16} {:>8} {:>8.1}%",
+        "",
+        "(draw)",
+        report.draws,
+        report.draw_rate() * 100.0
+    );
+}
@@ -0,0 +1,383 @@
+//! Bots that play the game locally, without a WebSocket connection
+//!
+//! A bot is given the full game state and the id of its own snake and
+//! answers with a direction, just like a remote client answering a
+//! `MoveRequest`. The built-in strategies are used by the `snake-sim`
+//! binary and can join a room's lobby as house players.
+
+use crate::types::*;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+/// A move-choosing strategy
+pub trait Bot: Send {
+    /// Name shown in the lobby and in simulation reports
+    fn name(&self) -> &str;
+
+    /// Choose the next move for `snake_id` in the given state
+    fn next_move(&mut self, state: &GameState, snake_id: Uuid) -> Direction;
+}
+
+/// Names of the built-in bots, as accepted by [`create_bot`]
+pub const BOT_NAMES: &[&str] = &["random", "greedy", "flood-fill", "tail-chaser"];
+
+/// Create a built-in bot by name, seeding any randomness it uses
+pub fn create_bot(name: &str, seed: u64) -> Option<Box<dyn Bot>> {
+    match name {
+        "random" => Some(Box::new(RandomBot::new(seed))),
+        "greedy" => Some(Box::new(GreedyBot)),
+        "flood-fill" => Some(Box::new(FloodFillBot)),
+        "tail-chaser" => Some(Box::new(TailChaserBot)),
+        _ => None,
+    }
+}
+
+/// Picks a random move among those that do not run into a snake
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Bot for RandomBot {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn next_move(&mut self, state: &GameState, snake_id: Uuid) -> Direction {
+        let Some(snake) = state.snakes.get(&snake_id) else {
+            return Direction::Up;
+        };
+
+        let moves = Board::new(state).safe_moves(snake);
+        match moves.choose(&mut self.rng) {
+            Some(&(direction, _)) => direction,
+            None => fallback_move(snake),
+        }
+    }
+}
+
+/// Heads for the nearest fruit, avoiding moves that run into a snake
+pub struct GreedyBot;
+
+impl Bot for GreedyBot {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn next_move(&mut self, state: &GameState, snake_id: Uuid) -> Direction {
+        let Some(snake) = state.snakes.get(&snake_id) else {
+            return Direction::Up;
+        };
+
+        let board = Board::new(state);
+        board
+            .safe_moves(snake)
+            .into_iter()
+            .min_by_key(|&(_, target)| board.fruit_distance(target))
+            .map_or_else(|| fallback_move(snake), |(direction, _)| direction)
+    }
+}
+
+/// Moves into the largest open area, going for fruit when areas are equal
+pub struct FloodFillBot;
+
+impl Bot for FloodFillBot {
+    fn name(&self) -> &str {
+        "flood-fill"
+    }
+
+    fn next_move(&mut self, state: &GameState, snake_id: Uuid) -> Direction {
+        let Some(snake) = state.snakes.get(&snake_id) else {
+            return Direction::Up;
+        };
+
+        let board = Board::new(state);
+        most_space(&board, snake).unwrap_or_else(|| fallback_move(snake))
+    }
+}
+
+/// Goes for fruit only along moves that keep a path back to its own tail
+///
+/// Following its tail keeps the snake out of dead ends; when no move keeps
+/// the tail reachable it falls back to the largest open area.
+pub struct TailChaserBot;
+
+impl Bot for TailChaserBot {
+    fn name(&self) -> &str {
+        "tail-chaser"
+    }
+
+    fn next_move(&mut self, state: &GameState, snake_id: Uuid) -> Direction {
+        let Some(snake) = state.snakes.get(&snake_id) else {
+            return Direction::Up;
+        };
+
+        let board = Board::new(state);
+        let tail = snake.body.back().copied();
+
+        board
+            .safe_moves(snake)
+            .into_iter()
+            .filter(|&(_, target)| match tail {
+                Some(tail) if snake.body.len() > 1 => board.can_reach(target, tail),
+                _ => true,
+            })
+            .min_by_key(|&(_, target)| board.fruit_distance(target))
+            .map(|(direction, _)| direction)
+            .or_else(|| most_space(&board, snake))
+            .unwrap_or_else(|| fallback_move(snake))
+    }
+}
+
+/// Safe move leading into the largest open area, ties broken by fruit distance
+///
+/// Areas are only compared up to a few times the snake's length: beyond that
+/// there is room enough and going for fruit matters more.
+fn most_space(board: &Board, snake: &Snake) -> Option<Direction> {
+    let enough = snake.length * 2 + 8;
+
+    board
+        .safe_moves(snake)
+        .into_iter()
+        .max_by_key(|&(_, target)| {
+            (
+                board.open_area(target, enough),
+                std::cmp::Reverse(board.fruit_distance(target)),
+            )
+        })
+        .map(|(direction, _)| direction)
+}
+
+/// Move to make when every move is fatal
+fn fallback_move(snake: &Snake) -> Direction {
+    snake
+        .last_direction
+        .or_else(|| snake.valid_directions().first().copied())
+        .unwrap_or(Direction::Up)
+}
+
+/// The grid as seen by a bot about to move
+struct Board<'a> {
+    state: &'a GameState,
+    width: i32,
+    height: i32,
+    /// Cells, row by row, that will still hold a snake segment after this tick
+    blocked: Vec<bool>,
+}
+
+impl<'a> Board<'a> {
+    fn new(state: &'a GameState) -> Self {
+        let width = state.grid_width;
+        let height = state.grid_height;
+        let mut board = Self {
+            state,
+            width,
+            height,
+            blocked: vec![false; (width * height).max(0) as usize],
+        };
+
+        for snake in state.snakes.values() {
+            // A tail moves out of the way unless the snake is still growing
+            let tail_moves = snake.is_alive && snake.body.len() >= snake.length;
+            let keep = if tail_moves {
+                snake.body.len().saturating_sub(1)
+            } else {
+                snake.body.len()
+            };
+            for &position in snake.body.iter().take(keep) {
+                let index = board.index(position);
+                board.blocked[index] = true;
+            }
+        }
+
+        board
+    }
+
+    fn index(&self, position: Position) -> usize {
+        (position.y * self.width + position.x) as usize
+    }
+
+    fn is_blocked(&self, position: Position) -> bool {
+        self.blocked[self.index(position)]
+    }
+
+    fn step(&self, position: Position, direction: Direction) -> Position {
+        position.move_in_direction(direction, self.width, self.height)
+    }
+
+    /// Moves that do not run into a snake, preferring cells no other head can reach
+    fn safe_moves(&self, snake: &Snake) -> Vec<(Direction, Position)> {
+        let Some(head) = snake.head() else {
+            return Vec::new();
+        };
+
+        let free: Vec<(Direction, Position)> = snake
+            .valid_directions()
+            .into_iter()
+            .map(|direction| (direction, self.step(head, direction)))
+            .filter(|&(_, target)| !self.is_blocked(target))
+            .collect();
+
+        // Entering a cell another head can also reach risks a head-on collision
+        let uncontested: Vec<(Direction, Position)> = free
+            .iter()
+            .copied()
+            .filter(|&(_, target)| !self.reachable_by_other_head(snake, target))
+            .collect();
+
+        if uncontested.is_empty() {
+            free
+        } else {
+            uncontested
+        }
+    }
+
+    fn reachable_by_other_head(&self, snake: &Snake, target: Position) -> bool {
+        self.state.snakes.values().any(|other| {
+            other.id != snake.id
+                && other.is_alive
+                && other.head().is_some_and(|head| {
+                    Direction::all()
+                        .into_iter()
+                        .any(|d| self.step(head, d) == target)
+                })
+        })
+    }
+
+    /// Number of free cells reachable from a position, counting up to `limit`
+    fn open_area(&self, from: Position, limit: usize) -> usize {
+        self.flood(from, limit, |_| false)
+    }
+
+    /// Whether `to` can be reached from `from` through free cells
+    fn can_reach(&self, from: Position, to: Position) -> bool {
+        from == to || self.flood(from, usize::MAX, |cell| cell == to) == usize::MAX
+    }
+
+    /// Breadth-first fill of free cells, returning `usize::MAX` as soon as
+    /// `stop` matches a neighbouring cell and the filled size otherwise
+    fn flood(&self, from: Position, limit: usize, stop: impl Fn(Position) -> bool) -> usize {
+        let mut seen = vec![false; self.blocked.len()];
+        let mut queue = VecDeque::from([from]);
+        let mut filled = 1;
+        seen[self.index(from)] = true;
+
+        while let Some(cell) = queue.pop_front() {
+            for direction in Direction::all() {
+                let next = self.step(cell, direction);
+                if stop(next) {
+                    return usize::MAX;
+                }
+
+                let index = self.index(next);
+                if !self.blocked[index] && !seen[index] {
+                    seen[index] = true;
+                    filled += 1;
+                    if filled >= limit {
+                        return filled;
+                    }
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        filled
+    }
+
+    /// Distance to the nearest fruit on the wrapping grid
+    fn fruit_distance(&self, from: Position) -> i32 {
+        self.state
+            .fruits
+            .iter()
+            .map(|fruit| self.distance(from, fruit.position))
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn distance(&self, a: Position, b: Position) -> i32 {
+        let dx = (a.x - b.x).abs();
+        let dy = (a.y - b.y).abs();
+        dx.min(self.width - dx) + dy.min(self.height - dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+
+    /// A 10x10 board with one snake heading right
+    fn single_snake_state(body: &[(i32, i32)]) -> (GameState, Uuid) {
+        let config = GameConfig {
+            grid_width: 10,
+            grid_height: 10,
+            ..Default::default()
+        };
+        let mut state = GameState::new(config.grid_width, config.grid_height);
+        let id = Uuid::from_u128(1);
+        let mut snake = Snake::new(
+            id,
+            "bot".to_string(),
+            Position::new(body[0].0, body[0].1),
+            0,
+            body.len(),
+        );
+        snake.body = body.iter().map(|&(x, y)| Position::new(x, y)).collect();
+        snake.last_direction = Some(Direction::Right);
+        state.snakes.insert(id, snake);
+        (state, id)
+    }
+
+    #[test]
+    fn test_greedy_moves_towards_fruit() {
+        let (mut state, id) = single_snake_state(&[(2, 5), (1, 5)]);
+        state.fruits.push(Fruit::new(Position::new(2, 1), 0));
+
+        assert_eq!(GreedyBot.next_move(&state, id), Direction::Up);
+    }
+
+    #[test]
+    fn test_bots_avoid_other_snakes() {
+        let (mut state, id) = single_snake_state(&[(2, 5), (1, 5)]);
+        let wall_id = Uuid::from_u128(2);
+        let mut wall = Snake::new(wall_id, "wall".to_string(), Position::new(3, 4), 1, 3);
+        wall.body = [(3, 4), (3, 5), (3, 6)]
+            .iter()
+            .map(|&(x, y)| Position::new(x, y))
+            .collect();
+        wall.kill(DeathCause::SelfCollision, 0);
+        state.snakes.insert(wall_id, wall);
+        state.fruits.push(Fruit::new(Position::new(5, 5), 0));
+
+        for name in BOT_NAMES {
+            let mut bot = create_bot(name, 7).unwrap();
+            let direction = bot.next_move(&state, id);
+            assert_ne!(direction, Direction::Right, "{} ran into a snake", name);
+        }
+    }
+
+    #[test]
+    fn test_flood_fill_avoids_dead_end() {
+        // Moving up from (1, 1) enters a single enclosed cell at (1, 0)
+        let (mut state, id) = single_snake_state(&[(1, 1), (1, 2)]);
+        state.snakes.get_mut(&id).unwrap().last_direction = Some(Direction::Up);
+        let wall_id = Uuid::from_u128(2);
+        let mut wall = Snake::new(wall_id, "wall".to_string(), Position::new(0, 0), 1, 4);
+        wall.body = [(0, 0), (2, 0), (1, 9), (0, 1)]
+            .iter()
+            .map(|&(x, y)| Position::new(x, y))
+            .collect();
+        wall.kill(DeathCause::SelfCollision, 0);
+        state.snakes.insert(wall_id, wall);
+
+        assert_eq!(FloodFillBot.next_move(&state, id), Direction::Right);
+    }
+}
@@ -0,0 +1,343 @@
+//! Runtime configuration for the multiplayer snake game
+//!
+//! This module resolves the game rules and server settings at startup instead
+//! of baking them in at compile time. Values are layered, with later sources
+//! overriding earlier ones:
+//!
+//! 1. Built-in defaults (see `constants.rs`)
+//! 2. An optional TOML or JSON config file (`--config` / `SNAKE_CONFIG`)
+//! 3. Environment variables (e.g. `SERVER_PORT`, `MAX_PLAYERS`)
+//! 4. Command line flags (e.g. `--port`, `--max-players`)
+
+use crate::constants;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// The game rules are part of the protocol, as `Hello` announces them to clients
+pub use snake_protocol::{GameConfig, MissedMovePolicy};
+
+/// Network settings for the HTTP/WebSocket server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// Directory finished game replays are written to
+    pub replay_dir: PathBuf,
+    /// File the history of finished matches is kept in
+    pub match_history_file: PathBuf,
+    /// Token required for game control; one is generated at startup if not set
+    pub admin_token: Option<String>,
+    /// File registered players and hashes of their API keys are kept in
+    pub players_file: PathBuf,
+    /// Only let registered players with an API key join
+    pub require_api_key: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: constants::SERVER_HOST.to_string(),
+            port: constants::SERVER_PORT,
+            replay_dir: PathBuf::from(constants::REPLAY_DIR),
+            match_history_file: PathBuf::from(constants::MATCH_HISTORY_FILE),
+            admin_token: None,
+            players_file: PathBuf::from(constants::PLAYERS_FILE),
+            require_api_key: false,
+        }
+    }
+}
+
+/// Complete runtime configuration
+///
+/// In a config file the two sections map to `[server]` and `[game]` tables
+/// (or the equivalent JSON objects). Missing keys fall back to defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub game: GameConfig,
+}
+
+/// Command line flags, each of which can also be set through the environment
+#[derive(Debug, Default, Parser)]
+#[command(name = "snake-game", about = "Multiplayer snake game server")]
+pub struct CliArgs {
+    /// Path to a TOML or JSON config file
+    #[arg(long, env = "SNAKE_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    #[arg(long, env = "SERVER_HOST")]
+    pub host: Option<String>,
+
+    #[arg(long, env = "SERVER_PORT")]
+    pub port: Option<u16>,
+
+    #[arg(long, env = "REPLAY_DIR")]
+    pub replay_dir: Option<PathBuf>,
+
+    #[arg(long, env = "MATCH_HISTORY_FILE")]
+    pub match_history_file: Option<PathBuf>,
+
+    /// Token for starting games and other admin actions; generated if unset
+    #[arg(long, env = "ADMIN_TOKEN")]
+    pub admin_token: Option<String>,
+
+    #[arg(long, env = "PLAYERS_FILE")]
+    pub players_file: Option<PathBuf>,
+
+    /// Reject players connecting without a registered API key
+    #[arg(long, env = "REQUIRE_API_KEY")]
+    pub require_api_key: Option<bool>,
+
+    #[arg(long, env = "GRID_WIDTH")]
+    pub grid_width: Option<usize>,
+
+    #[arg(long, env = "GRID_HEIGHT")]
+    pub grid_height: Option<usize>,
+
+    #[arg(long, env = "WINNING_SNAKE_LENGTH")]
+    pub winning_snake_length: Option<usize>,
+
+    #[arg(long, env = "INITIAL_SNAKE_LENGTH")]
+    pub initial_snake_length: Option<usize>,
+
+    #[arg(long, env = "FRUIT_SPAWN_DELAY_TICKS")]
+    pub fruit_spawn_delay_ticks: Option<u32>,
+
+    #[arg(long, env = "GAME_TICK_DURATION_MS")]
+    pub game_tick_duration_ms: Option<u64>,
+
+    #[arg(long, env = "MOVE_TIMEOUT_MS")]
+    pub move_timeout_ms: Option<u64>,
+
+    /// kill, continue, random or allow_misses:<n>
+    #[arg(long, env = "MISSED_MOVE_POLICY")]
+    pub missed_move_policy: Option<MissedMovePolicy>,
+
+    #[arg(long, env = "RECONNECT_GRACE_MS")]
+    pub reconnect_grace_ms: Option<u64>,
+
+    #[arg(long, env = "MAX_PLAYERS")]
+    pub max_players: Option<usize>,
+
+    #[arg(long, env = "MIN_PLAYERS")]
+    pub min_players: Option<usize>,
+
+    #[arg(long, env = "AUTO_START_COUNTDOWN_MS")]
+    pub auto_start_countdown_ms: Option<u64>,
+}
+
+/// Errors raised while loading configuration
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse config file {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+
+    #[error("Invalid configuration: {0}")]
+    Invalid(String),
+}
+
+impl Config {
+    /// Load configuration from the process arguments and environment
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_args(CliArgs::parse())
+    }
+
+    /// Resolve configuration from already parsed arguments
+    pub fn from_args(args: CliArgs) -> Result<Self, ConfigError> {
+        let mut config = match &args.config {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        config.apply_args(args);
+        config.game.validate().map_err(ConfigError::Invalid)?;
+        Ok(config)
+    }
+
+    /// Read a config file, choosing the format from its extension
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+        let parsed = if is_json {
+            serde_json::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&content).map_err(|e| e.to_string())
+        };
+
+        parsed.map_err(|message| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message,
+        })
+    }
+
+    /// Override values with any flags or environment variables that were set
+    fn apply_args(&mut self, args: CliArgs) {
+        let server = &mut self.server;
+        let game = &mut self.game;
+
+        if let Some(host) = args.host {
+            server.host = host;
+        }
+        if let Some(port) = args.port {
+            server.port = port;
+        }
+        if let Some(replay_dir) = args.replay_dir {
+            server.replay_dir = replay_dir;
+        }
+        if let Some(match_history_file) = args.match_history_file {
+            server.match_history_file = match_history_file;
+        }
+        if let Some(admin_token) = args.admin_token {
+            server.admin_token = Some(admin_token);
+        }
+        if let Some(players_file) = args.players_file {
+            server.players_file = players_file;
+        }
+        if let Some(require_api_key) = args.require_api_key {
+            server.require_api_key = require_api_key;
+        }
+        if let Some(value) = args.grid_width {
+            game.grid_width = value;
+        }
+        if let Some(value) = args.grid_height {
+            game.grid_height = value;
+        }
+        if let Some(value) = args.winning_snake_length {
+            game.winning_snake_length = value;
+        }
+        if let Some(value) = args.initial_snake_length {
+            game.initial_snake_length = value;
+        }
+        if let Some(value) = args.fruit_spawn_delay_ticks {
+            game.fruit_spawn_delay_ticks = value;
+        }
+        if let Some(value) = args.game_tick_duration_ms {
+            game.game_tick_duration_ms = value;
+        }
+        if let Some(value) = args.move_timeout_ms {
+            game.move_timeout_ms = value;
+        }
+        if let Some(value) = args.missed_move_policy {
+            game.missed_move_policy = value;
+        }
+        if let Some(value) = args.reconnect_grace_ms {
+            game.reconnect_grace_ms = value;
+        }
+        if let Some(value) = args.max_players {
+            game.max_players = value;
+        }
+        if let Some(value) = args.min_players {
+            game.min_players = value;
+        }
+        if let Some(value) = args.auto_start_countdown_ms {
+            game.auto_start_countdown_ms = Some(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_match_constants() {
+        let config = Config::default();
+        assert_eq!(config.server.port, constants::SERVER_PORT);
+        assert_eq!(config.game.grid_width, constants::GRID_WIDTH);
+        assert_eq!(config.game.max_players, constants::MAX_PLAYERS);
+        assert!(config.game.validate().is_ok());
+    }
+
+    #[test]
+    fn test_partial_toml_uses_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+            [game]
+            grid_width = 20
+            winning_snake_length = 10
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.game.grid_width, 20);
+        assert_eq!(config.game.winning_snake_length, 10);
+        assert_eq!(config.game.grid_height, constants::GRID_HEIGHT);
+        assert_eq!(config.server, ServerConfig::default());
+    }
+
+    #[test]
+    fn test_args_override_config() {
+        let mut config = Config::default();
+        config.apply_args(CliArgs {
+            port: Some(8080),
+            max_players: Some(4),
+            ..Default::default()
+        });
+
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.game.max_players, 4);
+        assert_eq!(config.game.min_players, constants::MIN_PLAYERS);
+    }
+
+    #[test]
+    fn test_missed_move_policy_formats() {
+        let config: Config = toml::from_str(
+            r#"
+            [game]
+            missed_move_policy = { allow_misses = 3 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.game.missed_move_policy,
+            MissedMovePolicy::AllowMisses(3)
+        );
+
+        for policy in [
+            MissedMovePolicy::Kill,
+            MissedMovePolicy::Continue,
+            MissedMovePolicy::AllowMisses(2),
+        ] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+        assert!("sometimes".parse::<MissedMovePolicy>().is_err());
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        let config = GameConfig {
+            min_players: 5,
+            max_players: 4,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        // Snakes could never move in time, and rooms would tick without pause
+        let no_wait = GameConfig {
+            move_timeout_ms: 0,
+            ..Default::default()
+        };
+        assert!(no_wait.validate().is_err());
+
+        let no_tick = GameConfig {
+            game_tick_duration_ms: 0,
+            ..Default::default()
+        };
+        assert!(no_tick.validate().is_err());
+    }
+}
@@ -2,63 +2,50 @@
 //!
 //! This module contains all configurable constants for the game,
 //! making it easy to modify game parameters without changing core logic.
+//! Game rules and server settings here are only defaults; the values in
+//! force are resolved at startup into a `GameConfig` (see `config.rs`).
 
-/// Grid dimensions
-pub const GRID_WIDTH: usize = 50;
-pub const GRID_HEIGHT: usize = 50;
-
-/// Snake game rules
-pub const WINNING_SNAKE_LENGTH: usize = 50;
-pub const INITIAL_SNAKE_LENGTH: usize = 1;
-
-/// Fruit spawning rules
-pub const FRUIT_SPAWN_DELAY_TICKS: u32 = 5;
-
-/// Game timing
-pub const GAME_TICK_DURATION_MS: u64 = 200;
+/// Game defaults and styling shared with the GUI
+pub use snake_protocol::constants::{
+    CELL_SIZE_PX, DEAD_SNAKE_ALPHA, FRUIT_COLOR, FRUIT_SPAWN_DELAY_TICKS, GAME_TICK_DURATION_MS,
+    GRID_BACKGROUND_COLOR, GRID_HEIGHT, GRID_LINE_COLOR, GRID_WIDTH, INITIAL_SNAKE_LENGTH,
+    MAX_PLAYERS, MIN_PLAYERS, MOVE_TIMEOUT_MS, RECONNECT_GRACE_MS, SNAKE_COLORS,
+    WINNING_SNAKE_LENGTH,
+};
 
 /// Server configuration
 pub const SERVER_HOST: &str = "0.0.0.0";
 pub const SERVER_PORT: u16 = 3000;
 
-/// WebSocket endpoints
-pub const LOBBY_ENDPOINT: &str = "/lobby";
-pub const GUI_ENDPOINT: &str = "/gui";
+/// Directory game replays are written to
+pub const REPLAY_DIR: &str = "replays";
 
-/// Game state limits
-pub const MAX_PLAYERS: usize = 8;
-pub const MIN_PLAYERS: usize = 2;
+/// File registered players and their API keys are kept in
+pub const PLAYERS_FILE: &str = "players.json";
 
-/// Colors for snake visualization (HTML color codes)
-pub const SNAKE_COLORS: [&str; 8] = [
-    "#FF6B6B", // Red
-    "#4ECDC4", // Teal
-    "#45B7D1", // Blue
-    "#96CEB4", // Green
-    "#FFEAA7", // Yellow
-    "#DDA0DD", // Plum
-    "#FFB347", // Orange
-    "#87CEEB", // Sky Blue
-];
+/// File the history of finished matches is appended to
+pub const MATCH_HISTORY_FILE: &str = "matches.jsonl";
 
-/// Dead snake color modifier (makes colors more gray/transparent)
-pub const DEAD_SNAKE_ALPHA: f32 = 0.5;
-
-/// Fruit color
-pub const FRUIT_COLOR: &str = "#FF1493"; // Deep Pink
+/// WebSocket endpoints
+pub const LOBBY_ENDPOINT: &str = "/lobby";
+pub const GUI_ENDPOINT: &str = "/gui";
+pub const SPECTATE_ENDPOINT: &str = "/spectate";
 
-/// Grid styling
-pub const GRID_BACKGROUND_COLOR: &str = "#2C3E50";
-pub const GRID_LINE_COLOR: &str = "#34495E";
-pub const CELL_SIZE_PX: u32 = 12;
+/// Room used when a client does not ask for a specific one
+pub const DEFAULT_ROOM_ID: &str = "default";
 
 /// WebSocket message size limits
 pub const MAX_MESSAGE_SIZE: usize = 1024 * 16; // 16KB
 pub const MAX_FRAME_SIZE: usize = 1024 * 16; // 16KB
 
 /// Game timing constraints
-pub const MOVE_TIMEOUT_MS: u64 = 5000; // 5 seconds to make a move
 pub const LOBBY_TIMEOUT_MS: u64 = 300000; // 5 minutes lobby timeout
+pub const QUEUE_COUNTDOWN_MS: u64 = 5000; // 5 seconds before a matchmaking room starts
+pub const RESULTS_DISPLAY_MS: u64 = 5000; // 5 seconds of results before the lobby opens again
+pub const ENTRANT_WAIT_MS: u64 = 30000; // 30 seconds for tournament entrants to connect for a match
+
+/// Delta updates between two full keyframes, for connections asking for deltas
+pub const DELTA_KEYFRAME_INTERVAL: u64 = 50;
 
 /// Debug settings
 pub const ENABLE_DEBUG_LOGGING: bool = cfg!(debug_assertions);
@@ -3,8 +3,21 @@
 //! This module provides comprehensive API documentation using utoipa,
 //! making it easy for developers to implement clients in any language.
 
+use crate::accounts::{PlayerAccount, RegisterPlayerRequest, RegisteredPlayer};
+use crate::config::{GameConfig, MissedMovePolicy};
+use crate::matches::{MatchPage, MatchPlayer, MatchRecord};
+use crate::replay::ReplayInfo;
+use crate::rooms::{
+    AddBotRequest, CreateRoomRequest, RoomSummary, StartGameRequest, StartGameResponse,
+};
+use crate::server::ErrorResponse;
+use crate::tournaments::{
+    CreateTournamentRequest, Entrant, EntrantRequest, Standing, Tournament, TournamentFormat,
+    TournamentMatch, TournamentRound, TournamentStatus, TournamentSummary,
+};
 use crate::types::*;
-use utoipa::OpenApi;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 
 /// OpenAPI specification for the Multiplayer Snake Game API
 #[derive(OpenApi)]
@@ -12,6 +25,33 @@ use utoipa::OpenApi;
     paths(
         crate::server::health_check,
         crate::server::game_stats,
+        crate::server::game_config,
+        crate::server::list_rooms,
+        crate::server::create_room,
+        crate::server::delete_room,
+        crate::server::start_room_game,
+        crate::server::abort_room_game,
+        crate::server::pause_room_game,
+        crate::server::resume_room_game,
+        crate::server::reset_room_lobby,
+        crate::server::kick_player,
+        crate::server::update_room_config,
+        crate::server::list_bots,
+        crate::server::list_players,
+        crate::server::register_player,
+        crate::server::remove_player,
+        crate::server::add_house_bot,
+        crate::server::remove_house_bot,
+        crate::server::list_matches,
+        crate::server::get_match,
+        crate::server::leaderboard,
+        crate::server::list_tournaments,
+        crate::server::create_tournament,
+        crate::server::get_tournament,
+        crate::server::cancel_tournament,
+        crate::server::list_replays,
+        crate::server::download_replay,
+        crate::server::replay_frames,
         crate::server::serve_openapi_spec,
         crate::server::serve_index,
         crate::server::serve_api_docs,
@@ -27,16 +67,55 @@ use utoipa::OpenApi;
             Fruit,
             GameState,
             LobbyPlayer,
+            RoomPhase,
+            Placement,
+            StateDelta,
+            SnakeDelta,
+            Feature,
+            Encoding,
             ClientMessage,
             ServerMessage,
             GameError,
             GameStats,
+            GameConfig,
+            MissedMovePolicy,
+            RoomSummary,
+            CreateRoomRequest,
+            StartGameRequest,
+            AddBotRequest,
+            StartGameResponse,
+            RegisteredPlayer,
+            PlayerAccount,
+            RegisterPlayerRequest,
+            ErrorResponse,
+            MatchRecord,
+            MatchPlayer,
+            MatchPage,
+            LeaderboardEntry,
+            CreateTournamentRequest,
+            EntrantRequest,
+            TournamentFormat,
+            TournamentStatus,
+            Entrant,
+            TournamentMatch,
+            TournamentRound,
+            Standing,
+            Tournament,
+            TournamentSummary,
+            ReplayInfo,
+            ReplayFrames,
         )
     ),
     tags(
         (name = "websocket", description = "WebSocket endpoints for real-time game communication"),
         (name = "health", description = "Health check and monitoring endpoints"),
-        (name = "game", description = "Game state and statistics endpoints")
+        (name = "game", description = "Game state and statistics endpoints"),
+        (name = "rooms", description = "Game room management endpoints"),
+        (name = "admin", description = "Game and lobby control, requiring the admin token"),
+        (name = "players", description = "Registered players and their API keys"),
+        (name = "matches", description = "History of finished matches and player ratings"),
+        (name = "tournaments", description = "Tournaments of bots and registered players"),
+        (name = "replays", description = "Recorded game replays")
     ),
     info(
         title = "Multiplayer Snake Game API",
@@ -54,10 +133,25 @@ use utoipa::OpenApi;
     servers(
         (url = "http://localhost:3000", description = "Local development server"),
         (url = "https://api.snakegame.com", description = "Production server")
-    )
+    ),
+    modifiers(&AdminTokenScheme)
 )]
 pub struct ApiDoc;
 
+/// Registers the bearer token scheme used by admin endpoints
+struct AdminTokenScheme;
+
+impl Modify for AdminTokenScheme {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "admin_token",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
+
 /// Generate OpenAPI JSON specification
 pub fn generate_openapi_spec() -> String {
     ApiDoc::openapi().to_pretty_json().unwrap()
@@ -74,14 +168,19 @@ The Multiplayer Snake Game API provides real-time multiplayer snake gameplay thr
 ## Game Rules
 
 ### Setup
-- Grid size: 50x50 cells
-- Initial snake length: 1 cell
-- Maximum players: 8
-- Minimum players to start: 2
-- Winning condition: Be the last snake alive OR reach length 300
+The rules are a `GameConfig`, set for the server at startup (see
+Configuration below) and per room with `PUT /rooms/{id}/config`; `GET /config`
+returns the server's. The defaults are:
+- Grid size: 50x50 cells (`grid_width` / `grid_height`)
+- Initial snake length: 1 cell (`initial_snake_length`)
+- Maximum players: 8 (`max_players`)
+- Minimum players to start: 2 (`min_players`)
+- Winning condition: Be the last snake alive OR reach length 50
+  (`winning_snake_length`)
 
 ### Gameplay
-- Each game tick (200ms interval), players submit their next move
+- Each game tick (at least 200ms by default, `game_tick_duration_ms`), players
+  submit their next move
 - Valid directions: UP, DOWN, LEFT, RIGHT
 - Grid wraps around (no boundaries)
 - Snakes cannot move backward into their own tail
@@ -94,7 +193,8 @@ The Multiplayer Snake Game API provides real-time multiplayer snake gameplay thr
 
 ### Fruit System
 - Number of fruits = Number of players - 1
-- Fruits spawn every 5 ticks in random empty cells
+- Fruits spawn every 5 ticks by default (`fruit_spawn_delay_ticks`) in random
+  empty cells
 - Eating fruit increases snake length by 1
 
 ## WebSocket Endpoints
@@ -105,23 +205,300 @@ Connect as a player to join the game lobby and participate in matches.
 **Connection Parameters:**
 - `player_name` (optional): Your display name (auto-generated if not provided)
 
+- `room` (optional): Room to join (defaults to `default`)
+
+- `resume_token` (optional): Token from `LobbyJoined` to reconnect to your seat
+
+- `api_key` (optional): API key of a registered player, for clients that
+  cannot send the `X-API-Key` header
+
+- `delta` (optional): `true` to receive game updates as deltas (see below)
+
+- `encoding` (optional): `json` (default), `msgpack` or `cbor` (see below)
+
 **Example Connection:**
 ```javascript
-const ws = new WebSocket('ws://localhost:3000/lobby?player_name=YourName');
+const ws = new WebSocket('ws://localhost:3000/lobby?player_name=YourName&room=default');
+```
+
+**Reconnecting:** If your connection drops while your snake is alive in a
+running game, you keep your seat for the reconnect grace period (10 seconds
+by default). Until then your snake is treated like any player who misses a
+move. Connect again with `?resume_token=<token>&room=<room>` to get
+`LobbyJoined` and `GameStarted` with the current state and continue with the
+same snake. Outside of a running game, or once the grace period is over,
+the seat is given up and the token stops working.
+
+**Room lifecycle:** A room goes through the phases `Lobby` → `Countdown` →
+`Running` → `Ended` → `Lobby`. Every change is sent to all connections as
+`PhaseChanged`, and `LobbyState` and `GameUpdate` carry the current `phase`.
+After a game the results are shown for 5 seconds in `Ended`; then the board
+is cleared and the lobby opens again with the players who are still
+connected, who need not rejoin. An admin can start the next game without
+waiting for the results to end.
+
+**Auto-start:** Rooms configured with `auto_start_countdown_ms` start a game
+on their own once enough players are in the lobby. `GameStarting` announces
+the countdown; if players leave before it runs out, `CountdownCancelled`
+follows and the room waits for players again. Rooms without auto-start skip
+the `Countdown` phase and wait for an admin to start the game.
+
+**Delta updates:** By default every tick sends the whole board in
+`GameUpdate`. With `?delta=true` (on `/lobby`, `/queue`, `/gui` and
+`/spectate`) the first update of a game is a full `GameUpdate` keyframe and
+the following ticks send a `GameDelta` with only what changed: new heads,
+dropped tail cells, deaths and fruits. A fresh keyframe follows every 50
+updates. Keyframes and deltas carry a `seq` that goes up by one with every
+update; if you see a gap, or a delta doesn't apply to your state, send
+`Resync` to get a keyframe right away.
+
+**Binary encodings:** Messages are JSON text frames by default. Bots that
+would rather not parse JSON can ask for MessagePack or CBOR with
+`?encoding=msgpack` or `?encoding=cbor` (on any WebSocket endpoint), or by
+offering the WebSocket subprotocol `snake.msgpack`, `snake.cbor` or
+`snake.json`; the query parameter wins if both are given. Server messages
+then arrive as binary frames, and binary frames you send are read in the
+same encoding. Messages keep the JSON layout: a map with a `type` key and
+the same field names. UUIDs are 16-byte binary strings. Text frames are
+always read as JSON.
+
+**Handshake:** Every connection opens with a `Hello` from the server listing
+the protocol versions it speaks (`min_protocol_version` to
+`protocol_version`), the encodings and optional features it supports and the
+rules of the room. Bots should answer with their own `Hello` giving the
+newest version they speak, the oldest they can fall back to and the features
+they want (currently `deltas`). The server answers with `Welcome`: the
+version the connection uses from now on, the newest both sides speak, and
+the features that are on. A client with no version in common gets an
+`Error` and is disconnected instead of misreading messages. Clients that
+never send `Hello` keep working as before, on version 1 with the features
+chosen by their connection parameters.
+
+### Matchmaking Queue: `/queue`
+Join without picking a room. You are seated in the fullest queue room still
+waiting for players, or in a new one, and games start on their own after a
+countdown (5 seconds unless `AUTO_START_COUNTDOWN_MS` is set). You keep your
+seat between games, so a bot can stay connected and play game after game.
+A queue room closes once its last player has left.
+The messages are the same as on `/lobby`; `LobbyJoined` tells you the room.
+
+**Connection Parameters:**
+- `player_name` (optional): Your display name (auto-generated if not provided)
+
+- `api_key` (optional): API key of a registered player
+
+- `delta`, `encoding` (optional): As on `/lobby`
+
+To reconnect after a dropped connection, use `/lobby` with the `room` from
+`LobbyJoined` and your `resume_token`.
+
+**Example Connection:**
+```javascript
+const ws = new WebSocket('ws://localhost:3000/queue?player_name=YourName');
 ```
 
 ### GUI Connection: `/gui`
-Connect as a spectator/controller to view the game and manage lobby state.
+Admin channel to view the game and control it with `StartGame`. The
+connection is refused with 401 without the admin token.
+
+**Connection Parameters:**
+- `token` (required): The admin token
+- `room` (optional): Room to watch (defaults to `default`)
+- `delta` (optional): `true` to receive game updates as deltas
+- `encoding` (optional): `json` (default), `msgpack` or `cbor`
+
+**Example Connection:**
+```javascript
+const ws = new WebSocket('ws://localhost:3000/gui?room=default&token=ADMIN_TOKEN');
+```
+
+### Spectator Connection: `/spectate`
+Read-only view of a room for displays and viewers. Spectators receive
+`LobbyState`, `GameUpdate` and `GameEnded`; anything they send other than
+`Hello` and `Resync` is ignored.
+
+**Connection Parameters:**
+- `room` (optional): Room to watch (defaults to `default`)
+- `delta` (optional): `true` to receive game updates as deltas
+- `encoding` (optional): `json` (default), `msgpack` or `cbor`
 
 **Example Connection:**
 ```javascript
-const ws = new WebSocket('ws://localhost:3000/gui');
+const ws = new WebSocket('ws://localhost:3000/spectate?room=default');
 ```
 
+## Player Authentication
+
+Players can be registered to get a persistent identity: connecting with
+their API key (`X-API-Key` header or `?api_key=`) they join under the same
+player id and registered name every time, and nobody else can take that
+name. A registered player who connects again while they still have a seat
+gets it back, like with a resume token.
+
+With `REQUIRE_API_KEY=true` (or `--require-api-key true`) connections
+without a valid key are refused with 401, reconnections with a resume token
+included; otherwise anyone can still join under an unregistered name. An
+invalid key is always refused. A resume token of a registered player only
+works together with that player's key, and a key only resumes its own
+player's seat.
+
+Registering needs the admin token. Players are kept in `PLAYERS_FILE`
+(`players.json` by default), which holds only a hash of each key.
+
+- `GET /players`: List registered players
+- `POST /players`: Register a player, e.g. `{"name": "alice"}`; the response
+  holds the API key, which is not shown again
+- `DELETE /players/{id}`: Remove a player, revoking their key
+
+## Admin Token
+
+Game control needs the admin token, set with `ADMIN_TOKEN` or
+`--admin-token`. If none is set, the server generates one at startup and
+logs an admin GUI link containing it. HTTP admin endpoints take the token as
+`Authorization: Bearer <token>` and answer 401 without it. The web page at
+`/` spectates by default and can start games when opened as `/?token=<token>`.
+
+## Game Rooms
+
+A server can run several matches at once, each in its own room with its own
+lobby, game loop and player limit. Events from one room never reach clients
+connected to another. A room called `default` always exists.
+
+- `GET /rooms`: List rooms with player counts and game status
+- `POST /rooms`: Create a room, e.g. `{"id": "experiment-1"}`; an optional
+  `config` object overrides the server's game rules for that room (admin
+  token required)
+- `DELETE /rooms/{id}`: Delete a room and disconnect everyone in it (admin
+  token required)
+- `POST /rooms/{id}/start`: Start a game, optionally with `{"seed": 42}`
+  (admin token required)
+
+### Admin Endpoints
+
+These control a room's lobby and game and all need the admin token:
+
+- `POST /rooms/{id}/abort`: End the running game without a winner; players
+  get `GameEnded` with a `null` winner and no replay is saved
+- `POST /rooms/{id}/pause`: Hold the game before its next tick; players get
+  `GamePaused` and moves they submit are kept for when it resumes
+- `POST /rooms/{id}/resume`: Continue a paused game; players get
+  `GameResumed` and a new `MoveRequest` with the full move timeout
+- `DELETE /rooms/{id}/players/{player_id}`: Kick a player or house player;
+  a snake in a running game dies with `Kicked`
+- `POST /rooms/{id}/reset`: Remove everyone from the lobby between games
+- `PUT /rooms/{id}/config`: Replace the room's rules (a full `GameConfig`, as
+  returned by `GET /config`) between games; `max_players` cannot be lower
+  than the number of players in the lobby
+
+`GET /stats` and `GET /config` accept the same `?room=<id>` parameter.
+
+### House Players
+
+Built-in bots can join a lobby as house players, so a single developer can
+test a client against opponents. They answer every `MoveRequest` instantly.
+Adding and removing them needs the admin token.
+
+- `GET /bots`: Available bots: `random` (random move that avoids snakes),
+  `greedy` (nearest fruit), `flood-fill` (largest open area) and
+  `tail-chaser` (goes for fruit only while it can still reach its own tail)
+- `POST /rooms/{id}/bots`: Add a house player, e.g. `{"bot": "greedy"}`;
+  an optional `name` overrides the default `greedy-bot-1`
+- `DELETE /rooms/{id}/bots/{player_id}`: Remove a house player; in a running
+  game its snake dies as if it was kicked
+
+## Match History
+
+Every finished game is summarized in the match history, which is kept in
+`MATCH_HISTORY_FILE` (`matches.jsonl` by default) and survives restarts.
+A match records the room, start and end time, seed, rules, number of ticks,
+winner, and for each player their placement, whether they were rated, final
+length and whether and how their snake died. Aborted games are not recorded.
+
+Placements are the `ranking` players receive in `GameEnded`.
+
+- `GET /matches`: Matches, newest first, as `{"total", "offset", "limit",
+  "matches"}`. Filter with `room`, `player` (id or name), `winner` (id) and
+  `since`/`until` (Unix timestamps of the start); page with `offset` and
+  `limit` (default 50, at most 500)
+- `GET /matches/{id}`: A single match; its id is also the id of its replay
+- `GET /leaderboard`: Players ordered by rating, as `rank`, `player_id`,
+  `name`, `rating`, `games` and `wins`. Filter with `min_games`; `limit`
+  defaults to 100
+
+Ratings are a multiplayer Elo over placements: every pair of players in a
+match counts as a game won by the better placed one. Everyone starts at
+1500, and a match moves a rating by at most 32 points. Ratings are rebuilt
+from the match history at startup. Only players registered at the time of
+the match are rated, and only those still registered are listed; anonymous
+players and house bots are left out of the update, as if they had not
+played.
+
+## Tournaments
+
+A tournament plays matches back to back, without anyone starting them, in
+a room of its own (`tournament-<id>`, watchable with `/spectate`). Entrants
+are built-in bots (`{"bot": "greedy"}`), seated as house players, or
+registered players (`{"player": "<id>"}`), who connect to the room with
+`/lobby?room=tournament-<id>` and their API key and wait in its lobby
+between their matches. An entrant who has not connected within 30 seconds
+of their match forfeits it and places last. Entrants are seated in groups
+of up to `group_size` players, and every match earns a player one point for
+each opponent they placed ahead of.
+
+- `round_robin`: Rounds are played until every pair of entrants has met
+- `swiss`: `rounds` rounds, each grouping players with similar points
+- `single_elimination`: The better half of every group (at least one
+  player) advances until one is left; ties at the cut go to the better seed
+
+An entrant left over when the groups are formed sits the round out (and
+advances, in single elimination). Standings are ordered by the round a
+player was knocked out in, then points, then wins. All matches are recorded
+in the match history, and count towards the leaderboard for registered
+players. The room is closed once the tournament is over; its results stay
+available from `GET /tournaments/{id}`.
+
+- `POST /tournaments` (admin): Create and start a tournament, e.g.
+  `{"format": "swiss", "entrants": [{"bot": "greedy"}, {"player": "<id>"}],
+  "group_size": 2, "rounds": 3}`; optional `config` and `seed` as for rooms
+- `GET /tournaments`: All tournaments with their status and leader
+- `GET /tournaments/{id}`: Entrants, rounds with each match's ranking and
+  history id, standings and, once finished, the champion
+- `DELETE /tournaments/{id}` (admin): Cancel a running tournament
+
+## Replays
+
+Every finished game is saved as a replay file in JSON Lines format, one
+record per line:
+
+- `Header`: game id, room, start time, rules, seed, players and initial state
+- `Tick`: moves submitted, snakes that timed out, deaths with their cause
+  (`HeadOnCollision`, `BodyCollision`, `SelfCollision`, `InvalidMove`,
+  `MoveTimeout`, `Disconnected`, `Kicked`) and fruit spawned during the tick
+- `End`: number of ticks played, winner and end time
+
+- `GET /replays`: List stored replays, newest first
+- `GET /replays/{id}`: Download a replay file
+- `GET /replays/{id}/frames`: The game state at every tick, rebuilt from the
+  seed and recorded moves; used by the GUI's playback mode
+
 ## Message Protocol
 
 ### Client Messages (Player → Server)
 
+#### Hello
+```json
+{
+  "type": "Hello",
+  "protocol_version": 1,
+  "min_protocol_version": 1,
+  "features": ["deltas"]
+}
+```
+Answer to the server's `Hello` with the newest protocol version you speak,
+optionally the oldest you can fall back to (defaults to `protocol_version`),
+and the optional features you want. Features the server doesn't know are
+ignored.
+
 #### JoinLobby
 ```json
 {
@@ -129,24 +506,34 @@ const ws = new WebSocket('ws://localhost:3000/gui');
   "player_name": "string"
 }
 ```
-Join the game lobby with a specified name.
+Join the game lobby with a specified name. You are seated when you connect;
+while you wait in the lobby this renames you if the name is free. An empty or
+taken name, or a rename outside the lobby, gets an `Error` and leaves your
+name as it is. Registered players keep their registered name and get an
+`Error` when asking for another.
 
 #### SubmitMove
 ```json
 {
   "type": "SubmitMove",
-  "direction": "Up" | "Down" | "Left" | "Right"
+  "direction": "Up" | "Down" | "Left" | "Right",
+  "tick": 12
 }
 ```
-Submit your next move direction for the current game tick.
+Submit your next move direction for the current game tick. Echo the `tick`
+of the `MoveRequest` you are answering: a move for any other tick, or one
+sent after the deadline has passed, is rejected with an `Error` instead of
+being applied to a later tick. `tick` is required.
 
 #### StartGame
 ```json
 {
-  "type": "StartGame"
+  "type": "StartGame",
+  "seed": 42
 }
 ```
-Start the game (GUI only). All players must be connected and ready.
+Start the game (admin GUI only). All players must be connected and ready.
+`seed` is optional; the same seed, players and moves always replay the same game.
 
 #### Ping
 ```json
@@ -156,17 +543,67 @@ Start the game (GUI only). All players must be connected and ready.
 ```
 Keep connection alive.
 
+#### Resync
+```json
+{
+  "type": "Resync"
+}
+```
+Ask for a full `GameUpdate` keyframe after missing a delta update. Only
+answered while a game is running.
+
 ### Server Messages (Server → Client)
 
+#### Hello
+```json
+{
+  "type": "Hello",
+  "protocol_version": 1,
+  "min_protocol_version": 1,
+  "encodings": ["json", "msgpack", "cbor"],
+  "features": ["deltas"],
+  "rules": {
+    "grid_width": 50,
+    "grid_height": 50,
+    "winning_snake_length": 50,
+    "initial_snake_length": 1,
+    "fruit_spawn_delay_ticks": 5,
+    "game_tick_duration_ms": 200,
+    "move_timeout_ms": 5000,
+    "missed_move_policy": "kill",
+    "reconnect_grace_ms": 10000,
+    "max_players": 8,
+    "min_players": 2,
+    "auto_start_countdown_ms": null
+  }
+}
+```
+First message on every connection: the protocol versions, encodings and
+optional features the server supports and the rules of the room.
+
+#### Welcome
+```json
+{
+  "type": "Welcome",
+  "protocol_version": 1,
+  "features": ["deltas"]
+}
+```
+Answer to your `Hello`: the protocol version the connection uses from now
+on and the features that are turned on.
+
 #### LobbyJoined
 ```json
 {
   "type": "LobbyJoined",
   "player_id": "uuid",
-  "player_name": "string"
+  "player_name": "string",
+  "room": "string",
+  "resume_token": "uuid"
 }
 ```
-Confirmation that you've joined the lobby.
+Confirmation that you've joined the lobby of `room`. Keep `resume_token` to
+reconnect after a dropped connection.
 
 #### LobbyState
 ```json
@@ -179,10 +616,11 @@ Confirmation that you've joined the lobby.
       "color_index": 0,
       "is_ready": true
     }
-  ]
+  ],
+  "phase": "Lobby"
 }
 ```
-Current lobby state with all connected players.
+Current lobby state with all connected players and the room's phase.
 
 #### GameStarted
 ```json
@@ -198,30 +636,133 @@ Game has started with initial state.
 ```json
 {
   "type": "GameUpdate",
-  "game_state": "GameState"
+  "game_state": "GameState",
+  "deaths": [
+    {"snake_id": "uuid", "cause": {"type": "BodyCollision", "with": "uuid"}, "tick": 41}
+  ],
+  "phase": "Running"
+}
+```
+Game state update after each tick. `deaths` lists the snakes that died
+during the tick that was just played, with the cause of each death:
+`HeadOnCollision` (with the ids of the other snakes), `BodyCollision` (with
+the id of the snake that was hit), `SelfCollision`, `InvalidMove`,
+`MoveTimeout`, `Disconnected` or `Kicked`. On connections with delta
+updates a `GameUpdate` is a keyframe and carries its `seq`.
+
+#### GameDelta
+```json
+{
+  "type": "GameDelta",
+  "seq": 18,
+  "delta": {
+    "base_tick": 16,
+    "tick": 17,
+    "snakes": [
+      {"id": "uuid", "new_heads": [{"x": 4, "y": 9}], "removed_tail": 1,
+       "length": 6, "last_direction": "Up", "missed_moves": 0}
+    ],
+    "deaths": [],
+    "fruits_added": [{"position": {"x": 20, "y": 3}, "spawn_tick": 16}],
+    "fruits_removed": [{"x": 4, "y": 9}],
+    "is_running": true,
+    "winner": null
+  },
+  "phase": "Running"
 }
 ```
-Game state update after each tick.
+Changes since the previous update, sent instead of `GameUpdate` on
+connections with `?delta=true`. Apply it to the state at `base_tick`: push
+`new_heads` onto the front of each listed snake (newest first), drop
+`removed_tail` cells from its end and take its `length`, `last_direction`
+and `missed_moves`; mark the snakes in `deaths` dead; remove the fruits at
+`fruits_removed` and add `fruits_added`. Snakes that did not change are not
+listed.
 
 #### MoveRequest
 ```json
 {
   "type": "MoveRequest",
+  "tick": 12,
   "valid_directions": ["Up", "Down", "Left", "Right"],
-  "time_limit_ms": 5000
+  "time_limit_ms": 4990
+}
+```
+Request for your next move with valid options. `time_limit_ms` is the time
+left until the deadline for `tick`, which is `move_timeout_ms` from
+`GET /config` after the tick opened. The tick is played as soon as every
+living snake has moved, or at the deadline otherwise.
+
+#### MoveFallback
+```json
+{
+  "type": "MoveFallback",
+  "tick": 12,
+  "direction": "Left",
+  "missed_moves": 1
 }
 ```
-Request for your next move with valid options.
+Your move for `tick` did not arrive in time and your snake was moved in
+`direction` instead, as set by the game's `missed_move_policy`:
+
+- `"kill"` (default): the snake dies with `MoveTimeout`; no `MoveFallback`
+- `"continue"`: the snake keeps going in its last direction
+- `"random"`: the snake makes a random valid move
+- `{"allow_misses": n}`: the snake keeps going for up to `n` missed ticks
+  in a row and dies on the next one
+
+`missed_moves` counts the ticks in a row you have missed.
+
+#### GameStarting
+```json
+{"type": "GameStarting", "starts_in_ms": 5000}
+```
+The room will start a game on its own in `starts_in_ms` milliseconds.
+
+#### CountdownCancelled
+```json
+{"type": "CountdownCancelled"}
+```
+Players left during the countdown and the room is waiting for players again.
+
+#### PhaseChanged
+```json
+{"type": "PhaseChanged", "phase": "Lobby"}
+```
+The room moved to another phase: `Lobby`, `Countdown`, `Running` or `Ended`.
+`Ended` comes before the game's `GameEnded`, and `Lobby` after `Ended` means
+the results are over and the next game can start.
+
+#### GamePaused
+```json
+{"type": "GamePaused"}
+```
+An admin paused the game. The current tick is not played until the game is
+resumed; a move you submit in the meantime still counts.
+
+#### GameResumed
+```json
+{"type": "GameResumed"}
+```
+The game continues. A fresh `MoveRequest` follows with the time left for
+the open tick.
 
 #### GameEnded
 ```json
 {
   "type": "GameEnded",
   "winner": "LobbyPlayer | null",
+  "ranking": [
+    {"player_id": "uuid", "placement": 1, "length": 12, "death_tick": null}
+  ],
   "final_state": "GameState"
 }
 ```
-Game has ended with winner information.
+Game has ended with winner information and every player's placement, best
+first. The winner places first, then the other survivors, then the dead from
+last to first to die. Snakes that died on the same tick are ranked by length
+and share a placement if that ties too, so a game without a winner still has
+a full ranking.
 
 #### Error
 ```json
@@ -252,10 +793,14 @@ Represents a coordinate on the game grid.
   "length": 2,
   "is_alive": true,
   "color_index": 0,
-  "last_direction": "Up"
+  "last_direction": "Up",
+  "death_cause": null,
+  "death_tick": null,
+  "missed_moves": 0
 }
 ```
-Complete snake state including position, status, and metadata.
+Complete snake state including position, status, and metadata. Once a snake
+has died, `death_cause` and `death_tick` tell why and during which tick.
 
 ### Fruit
 ```json
@@ -275,7 +820,8 @@ Fruit position and spawn information.
   "is_running": true,
   "winner": "uuid | null",
   "grid_width": 50,
-  "grid_height": 50
+  "grid_height": 50,
+  "seed": 42
 }
 ```
 Complete game state including all snakes, fruits, and metadata.
@@ -334,7 +880,7 @@ class SnakeGameClient {
         break;
       case 'MoveRequest':
         const direction = this.chooseDirection(message.valid_directions);
-        this.submitMove(direction);
+        this.submitMove(direction, message.tick);
         break;
       case 'GameEnded':
         console.log('Game ended!', message.winner);
@@ -342,10 +888,11 @@ class SnakeGameClient {
     }
   }
   
-  submitMove(direction) {
+  submitMove(direction, tick) {
     this.ws.send(JSON.stringify({
       type: 'SubmitMove',
-      direction: direction
+      direction: direction,
+      tick: tick
     }));
   }
   
@@ -382,12 +929,13 @@ class SnakeGameClient:
             self.game_state = message['game_state']
         elif message['type'] == 'MoveRequest':
             direction = self.choose_direction(message['valid_directions'])
-            await self.submit_move(websocket, direction)
+            await self.submit_move(websocket, direction, message['tick'])
     
-    async def submit_move(self, websocket, direction):
+    async def submit_move(self, websocket, direction, tick):
         move_message = {
             'type': 'SubmitMove',
-            'direction': direction
+            'direction': direction,
+            'tick': tick
         }
         await websocket.send(json.dumps(move_message))
     
@@ -404,7 +952,7 @@ asyncio.run(client.connect())
 
 ### For Client Developers
 1. **Always handle all message types** - The server may send unexpected messages
-2. **Implement reconnection logic** - WebSocket connections can drop
+2. **Implement reconnection logic** - WebSocket connections can drop; reconnect with your `resume_token` to keep your snake
 3. **Validate server messages** - Don't assume perfect JSON structure
 4. **Use timeouts for move requests** - Submit moves within the time limit
 5. **Handle game state efficiently** - Game updates come frequently (5 FPS)
@@ -432,7 +980,7 @@ websocat ws://localhost:3000/lobby?player_name=TestPlayer
 echo '{"type":"JoinLobby","player_name":"TestBot"}' | websocat ws://localhost:3000/lobby
 
 # Submit a move
-echo '{"type":"SubmitMove","direction":"Up"}' | websocat ws://localhost:3000/lobby
+echo '{"type":"SubmitMove","direction":"Up","tick":0}' | websocat ws://localhost:3000/lobby
 ```
 
 ## Deployment
@@ -451,10 +999,41 @@ EXPOSE 3000
 CMD ["snake-game"]
 ```
 
+### Configuration
+Settings are resolved from defaults, then an optional config file, then
+environment variables, then command line flags. The rules in force can be
+read from `GET /config`.
+
+Config file (`--config game.toml` or `SNAKE_CONFIG=game.toml`, JSON also accepted):
+```toml
+[server]
+port = 3000
+
+[game]
+grid_width = 30
+grid_height = 30
+winning_snake_length = 20
+missed_move_policy = { allow_misses = 3 }
+```
+
 ### Environment Variables
 - `RUST_LOG`: Set logging level (debug, info, warn, error)
-- `SERVER_PORT`: Override default port (3000)
-- `MAX_PLAYERS`: Override maximum players per game (8)
+- `SNAKE_CONFIG`: Path to a TOML or JSON config file
+- `SERVER_HOST` / `SERVER_PORT`: Override bind address (0.0.0.0:3000)
+- `REPLAY_DIR`: Directory replay files are written to (replays)
+- `ADMIN_TOKEN`: Token for game control (generated at startup if unset)
+- `GRID_WIDTH` / `GRID_HEIGHT`: Override grid size (50x50)
+- `WINNING_SNAKE_LENGTH`: Override winning length (50)
+- `INITIAL_SNAKE_LENGTH`: Override starting length (1)
+- `FRUIT_SPAWN_DELAY_TICKS`: Override fruit respawn delay (5)
+- `GAME_TICK_DURATION_MS`: Override minimum tick duration (200)
+- `MOVE_TIMEOUT_MS`: Override move timeout (5000)
+- `MISSED_MOVE_POLICY`: `kill`, `continue`, `random` or `allow_misses:<n>` (kill)
+- `RECONNECT_GRACE_MS`: Time to reconnect after a dropped connection (10000)
+- `AUTO_START_COUNTDOWN_MS`: Start games on their own this long after enough players joined (off)
+- `MAX_PLAYERS` / `MIN_PLAYERS`: Override player limits (8 / 2)
+
+Every variable has a matching command line flag, e.g. `--port 8080 --max-players 4`.
 
 For more information, check the source code documentation and examples in the repository.
 "#;
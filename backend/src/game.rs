@@ -3,49 +3,75 @@
 //! This module implements the main game mechanics including snake movement,
 //! collision detection, fruit spawning, and game state management.
 
-use crate::constants::*;
+use crate::config::{GameConfig, MissedMovePolicy};
 use crate::types::*;
 use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use uuid::Uuid;
 
 /// Game engine that manages all game logic
 pub struct GameEngine {
     /// Current game state
     pub state: GameState,
+    /// Rules for the current game
+    config: GameConfig,
     /// Random number generator
     rng: StdRng,
-    /// Tracks when fruits should spawn
-    fruit_spawn_timer: HashMap<usize, u32>,
+    /// Tracks when fruits should spawn, one timer per fruit slot
+    fruit_spawn_timer: Vec<u32>,
+    /// Deaths between ticks (e.g. disconnects), reported with the next tick
+    pending_deaths: Vec<Death>,
 }
 
 impl GameEngine {
     /// Create a new game engine
-    pub fn new() -> Self {
+    pub fn new(config: GameConfig) -> Self {
         Self {
-            state: GameState::new(),
+            state: GameState::new(config.grid_width, config.grid_height),
+            config,
             rng: StdRng::from_entropy(),
-            fruit_spawn_timer: HashMap::new(),
+            fruit_spawn_timer: Vec::new(),
+            pending_deaths: Vec::new(),
         }
     }
 
     /// Initialize the game with players
-    pub fn initialize_game(&mut self, players: &HashMap<Uuid, LobbyPlayer>) -> GameResult<()> {
-        self.state.snakes.clear();
-        self.state.fruits.clear();
-        self.state.tick = 0;
+    ///
+    /// All randomness in the game is drawn from an RNG seeded with `seed`
+    /// (or a freshly generated seed if none is given), so the same seed, players
+    /// and move sequence always produce the same game. Returns the seed used.
+    pub fn initialize_game(
+        &mut self,
+        players: &HashMap<Uuid, LobbyPlayer>,
+        seed: Option<u64>,
+    ) -> GameResult<u64> {
+        let seed = seed.unwrap_or_else(rand::random);
+        self.rng = StdRng::seed_from_u64(seed);
+
+        self.state = GameState::new(self.config.grid_width, self.config.grid_height);
         self.state.is_running = true;
-        self.state.winner = None;
+        self.pending_deaths.clear();
+        self.state.seed = Some(seed);
 
-        // Place snakes at random positions
+        // Place snakes at random positions, in join order so that spawn
+        // placement does not depend on hash map iteration order
         let mut occupied_positions = HashSet::new();
+        let mut ordered_players: Vec<&LobbyPlayer> = players.values().collect();
+        ordered_players.sort_by_key(|p| (p.color_index, p.id));
 
-        for player in players.values() {
+        for player in ordered_players {
             let position = self.find_random_empty_position(&occupied_positions)?;
             occupied_positions.insert(position);
 
-            let snake = Snake::new(player.id, player.name.clone(), position, player.color_index);
+            let snake = Snake::new(
+                player.id,
+                player.name.clone(),
+                position,
+                player.color_index,
+                self.config.initial_snake_length,
+            );
 
             self.state.snakes.insert(player.id, snake);
         }
@@ -53,23 +79,16 @@ impl GameEngine {
         // Initialize fruit spawning
         self.initialize_fruit_spawning();
 
-        Ok(())
+        Ok(seed)
     }
 
     /// Initialize fruit spawning timers
     fn initialize_fruit_spawning(&mut self) {
         let player_count = self.state.snakes.len();
-        let fruit_count = if player_count > 1 {
-            player_count - 1
-        } else {
-            0
-        };
+        let fruit_count = player_count.saturating_sub(1);
 
-        self.fruit_spawn_timer.clear();
-        for i in 0..fruit_count {
-            // Stagger initial fruit spawning
-            self.fruit_spawn_timer.insert(i, i as u32);
-        }
+        // Stagger initial fruit spawning
+        self.fruit_spawn_timer = (0..fruit_count as u32).collect();
     }
 
     /// Find a random empty position on the grid
@@ -81,7 +100,7 @@ impl GameEngine {
         occupied.extend(additional_occupied.iter().copied());
 
         let mut attempts = 0;
-        let max_attempts = GRID_WIDTH * GRID_HEIGHT;
+        let max_attempts = self.config.grid_width * self.config.grid_height;
 
         loop {
             if attempts >= max_attempts {
@@ -90,8 +109,8 @@ impl GameEngine {
                 ));
             }
 
-            let x = self.rng.gen_range(0..GRID_WIDTH as i32);
-            let y = self.rng.gen_range(0..GRID_HEIGHT as i32);
+            let x = self.rng.gen_range(0..self.state.grid_width);
+            let y = self.rng.gen_range(0..self.state.grid_height);
             let position = Position::new(x, y);
 
             if !occupied.contains(&position) {
@@ -103,22 +122,31 @@ impl GameEngine {
     }
 
     /// Process a game tick with player moves
-    pub fn process_tick(&mut self, moves: HashMap<Uuid, Direction>) -> GameResult<()> {
+    ///
+    /// Returns a summary of what happened during the tick (missed moves,
+    /// deaths and fruit spawns) for recording.
+    pub fn process_tick(&mut self, moves: HashMap<Uuid, Direction>) -> GameResult<TickSummary> {
         if !self.state.is_running {
             return Err(GameError::GameNotRunning);
         }
 
+        let mut summary = TickSummary {
+            tick: self.state.tick,
+            deaths: std::mem::take(&mut self.pending_deaths),
+            ..Default::default()
+        };
+
         // Move all snakes
-        self.move_snakes(moves)?;
+        self.move_snakes(moves, &mut summary)?;
 
         // Handle collisions and deaths
-        self.handle_collisions()?;
+        self.handle_collisions(&mut summary)?;
 
         // Handle fruit consumption
         self.handle_fruit_consumption()?;
 
         // Spawn new fruits
-        self.spawn_fruits()?;
+        self.spawn_fruits(&mut summary)?;
 
         // Check for game end conditions
         self.check_game_end()?;
@@ -126,11 +154,15 @@ impl GameEngine {
         // Increment tick counter
         self.state.tick += 1;
 
-        Ok(())
+        Ok(summary)
     }
 
     /// Move all snakes based on player input
-    fn move_snakes(&mut self, moves: HashMap<Uuid, Direction>) -> GameResult<()> {
+    fn move_snakes(
+        &mut self,
+        moves: HashMap<Uuid, Direction>,
+        summary: &mut TickSummary,
+    ) -> GameResult<()> {
         let mut snakes_to_update = Vec::new();
 
         // Collect moves for alive snakes
@@ -141,22 +173,37 @@ impl GameEngine {
                     let valid_directions = snake.valid_directions();
                     if !valid_directions.contains(&direction) {
                         // Invalid move - snake dies
-                        snakes_to_update.push((*snake_id, None));
+                        snakes_to_update.push((*snake_id, Err(DeathCause::InvalidMove)));
                         continue;
                     }
-                    snakes_to_update.push((*snake_id, Some(direction)));
+                    snakes_to_update.push((*snake_id, Ok(direction)));
                 } else {
-                    // No move submitted - snake dies
-                    snakes_to_update.push((*snake_id, None));
+                    // No move submitted - the missed move policy decides
+                    summary.timeouts.push(*snake_id);
+                    match fallback_move(self.config.missed_move_policy, snake, &mut self.rng) {
+                        Some(direction) => {
+                            summary.fallback_moves.insert(*snake_id, direction);
+                            snakes_to_update.push((*snake_id, Ok(direction)));
+                        }
+                        None => {
+                            snakes_to_update.push((*snake_id, Err(DeathCause::MoveTimeout)));
+                        }
+                    }
                 }
             }
         }
 
         // Apply moves
-        for (snake_id, direction_opt) in snakes_to_update {
+        for (snake_id, outcome) in snakes_to_update {
             if let Some(snake) = self.state.snakes.get_mut(&snake_id) {
-                match direction_opt {
-                    Some(direction) => {
+                if moves.contains_key(&snake_id) {
+                    snake.missed_moves = 0;
+                } else {
+                    snake.missed_moves += 1;
+                }
+
+                match outcome {
+                    Ok(direction) => {
                         // Move the snake (will check for fruit consumption later)
                         snake.move_snake(
                             direction,
@@ -165,9 +212,14 @@ impl GameEngine {
                             false, // We'll handle growth separately
                         );
                     }
-                    None => {
+                    Err(cause) => {
                         // Kill snake for invalid/missing move
-                        snake.kill();
+                        snake.kill(cause.clone(), summary.tick);
+                        summary.deaths.push(Death {
+                            snake_id,
+                            cause,
+                            tick: summary.tick,
+                        });
                     }
                 }
             }
@@ -177,47 +229,67 @@ impl GameEngine {
     }
 
     /// Handle all collision detection and deaths
-    fn handle_collisions(&mut self) -> GameResult<()> {
-        let mut snakes_to_kill = Vec::new();
+    fn handle_collisions(&mut self, summary: &mut TickSummary) -> GameResult<()> {
+        let tick = summary.tick;
+        let mut snakes_to_kill: Vec<Death> = Vec::new();
 
         // Collect all head positions for collision detection
         let mut head_positions: HashMap<Position, Vec<Uuid>> = HashMap::new();
         for (snake_id, snake) in &self.state.snakes {
             if snake.is_alive {
                 if let Some(head_pos) = snake.head() {
-                    head_positions
-                        .entry(head_pos)
-                        .or_insert_with(Vec::new)
-                        .push(*snake_id);
+                    head_positions.entry(head_pos).or_default().push(*snake_id);
                 }
             }
         }
 
         // Check for head-to-head collisions
-        for (_pos, snake_ids) in &head_positions {
+        for snake_ids in head_positions.values() {
             if snake_ids.len() > 1 {
                 // Multiple snakes moved to same position - all die
-                snakes_to_kill.extend(snake_ids.iter().copied());
+                for snake_id in snake_ids {
+                    let mut with: Vec<Uuid> = snake_ids
+                        .iter()
+                        .copied()
+                        .filter(|id| id != snake_id)
+                        .collect();
+                    with.sort();
+                    snakes_to_kill.push(Death {
+                        snake_id: *snake_id,
+                        cause: DeathCause::HeadOnCollision { with },
+                        tick,
+                    });
+                }
             }
         }
 
         // Check for head-to-body collisions
         for (snake_id, snake) in &self.state.snakes {
-            if snake.is_alive && !snakes_to_kill.contains(&snake_id) {
+            if snake.is_alive && !snakes_to_kill.iter().any(|d| d.snake_id == *snake_id) {
                 if let Some(head_pos) = snake.head() {
                     // Check collision with own tail
                     let tail_positions = snake.tail();
                     if tail_positions.contains(&head_pos) {
-                        snakes_to_kill.push(*snake_id);
+                        snakes_to_kill.push(Death {
+                            snake_id: *snake_id,
+                            cause: DeathCause::SelfCollision,
+                            tick,
+                        });
                         continue;
                     }
 
                     // Check collision with other snakes' bodies
                     for (other_id, other_snake) in &self.state.snakes {
                         if *other_id != *snake_id {
+                            let body_collision = DeathCause::BodyCollision { with: *other_id };
+
                             // Check collision with other snake's tail
                             if other_snake.tail().contains(&head_pos) {
-                                snakes_to_kill.push(*snake_id);
+                                snakes_to_kill.push(Death {
+                                    snake_id: *snake_id,
+                                    cause: body_collision,
+                                    tick,
+                                });
                                 break;
                             }
                             // Also check collision with other snake's head if they didn't move to same spot
@@ -225,7 +297,11 @@ impl GameEngine {
                                 if other_head == head_pos
                                     && !head_positions.get(&head_pos).unwrap().contains(other_id)
                                 {
-                                    snakes_to_kill.push(*snake_id);
+                                    snakes_to_kill.push(Death {
+                                        snake_id: *snake_id,
+                                        cause: body_collision,
+                                        tick,
+                                    });
                                     break;
                                 }
                             }
@@ -236,9 +312,10 @@ impl GameEngine {
         }
 
         // Kill all snakes that collided
-        for snake_id in snakes_to_kill {
-            if let Some(snake) = self.state.snakes.get_mut(&snake_id) {
-                snake.kill();
+        for death in snakes_to_kill {
+            if let Some(snake) = self.state.snakes.get_mut(&death.snake_id) {
+                snake.kill(death.cause.clone(), death.tick);
+                summary.deaths.push(death);
             }
         }
 
@@ -288,29 +365,22 @@ impl GameEngine {
     }
 
     /// Spawn new fruits according to game rules
-    fn spawn_fruits(&mut self) -> GameResult<()> {
+    fn spawn_fruits(&mut self, summary: &mut TickSummary) -> GameResult<()> {
         let player_count = self.state.snakes.len();
-        let max_fruits = if player_count > 1 {
-            player_count - 1
-        } else {
-            0
-        };
+        let max_fruits = player_count.saturating_sub(1);
 
         // Update fruit spawn timers
-        for timer in self.fruit_spawn_timer.values_mut() {
+        for timer in self.fruit_spawn_timer.iter_mut() {
             *timer += 1;
         }
 
         // Spawn fruits that are ready
         let mut new_fruits = Vec::new();
-        let timer_snapshot: Vec<(usize, u32)> = self
-            .fruit_spawn_timer
-            .iter()
-            .map(|(&k, &v)| (k, v))
-            .collect();
+        let timer_snapshot = self.fruit_spawn_timer.clone();
 
-        for (fruit_id, timer) in timer_snapshot {
-            if timer >= FRUIT_SPAWN_DELAY_TICKS && self.state.fruits.len() < max_fruits {
+        for (fruit_id, timer) in timer_snapshot.into_iter().enumerate() {
+            if timer >= self.config.fruit_spawn_delay_ticks && self.state.fruits.len() < max_fruits
+            {
                 if let Ok(position) = self.find_random_empty_position(&HashSet::new()) {
                     new_fruits.push((fruit_id, position));
                 }
@@ -322,13 +392,13 @@ impl GameEngine {
             self.state
                 .fruits
                 .push(Fruit::new(position, self.state.tick));
-            self.fruit_spawn_timer.insert(fruit_id, 0);
+            self.fruit_spawn_timer[fruit_id] = 0;
+            summary.fruit_spawns.push(position);
         }
 
         // Ensure we maintain the right number of fruit spawn timers
-        while self.fruit_spawn_timer.len() < max_fruits {
-            let new_id = self.fruit_spawn_timer.len();
-            self.fruit_spawn_timer.insert(new_id, 0);
+        if self.fruit_spawn_timer.len() < max_fruits {
+            self.fruit_spawn_timer.resize(max_fruits, 0);
         }
 
         Ok(())
@@ -336,13 +406,70 @@ impl GameEngine {
 
     /// Check if the game should end and set winner
     fn check_game_end(&mut self) -> GameResult<()> {
-        if self.state.is_game_over() {
-            self.state.winner = self.state.get_winner();
+        let winning_length = self.config.winning_snake_length;
+        if self.state.is_game_over(winning_length) {
+            self.state.winner = self.state.get_winner(winning_length);
             self.state.is_running = false;
         }
         Ok(())
     }
 
+    /// Kill a snake outside of tick processing, e.g. when its player leaves
+    ///
+    /// The death is attributed to the upcoming tick and reported in its
+    /// summary. Returns `None` if the snake is not alive in a running game.
+    pub fn kill_snake(&mut self, snake_id: &Uuid, cause: DeathCause) -> Option<Death> {
+        if !self.state.is_running {
+            return None;
+        }
+
+        let tick = self.state.tick;
+        let snake = self.state.snakes.get_mut(snake_id).filter(|s| s.is_alive)?;
+        snake.kill(cause.clone(), tick);
+
+        let death = Death {
+            snake_id: *snake_id,
+            cause,
+            tick,
+        };
+        self.pending_deaths.push(death.clone());
+        Some(death)
+    }
+
+    /// End the running game without a winner
+    pub fn abort(&mut self) -> GameResult<()> {
+        if !self.state.is_running {
+            return Err(GameError::GameNotRunning);
+        }
+
+        self.state.is_running = false;
+        self.state.winner = None;
+        self.pending_deaths.clear();
+        Ok(())
+    }
+
+    /// Clear the board of the last game, keeping the rules
+    pub fn clear_board(&mut self) -> GameResult<()> {
+        if self.state.is_running {
+            return Err(GameError::GameAlreadyRunning);
+        }
+
+        self.state = GameState::new(self.config.grid_width, self.config.grid_height);
+        self.pending_deaths.clear();
+        Ok(())
+    }
+
+    /// Change the rules for the next game, clearing the board of the last one
+    pub fn set_config(&mut self, config: GameConfig) -> GameResult<()> {
+        if self.state.is_running {
+            return Err(GameError::GameAlreadyRunning);
+        }
+
+        self.state = GameState::new(config.grid_width, config.grid_height);
+        self.config = config;
+        Ok(())
+    }
+
     /// Get valid moves for a specific snake
     pub fn get_valid_moves(&self, snake_id: &Uuid) -> Vec<Direction> {
         if let Some(snake) = self.state.snakes.get(snake_id) {
@@ -362,6 +489,11 @@ impl GameEngine {
             .unwrap_or(false)
     }
 
+    /// Get the rules the engine is running with
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
     /// Get current game state snapshot
     pub fn get_game_state(&self) -> &GameState {
         &self.state
@@ -391,6 +523,43 @@ impl GameEngine {
     }
 }
 
+/// Everything that happened during a single processed tick
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TickSummary {
+    /// The tick that was processed
+    pub tick: u64,
+    /// Alive snakes that did not submit a move in time
+    pub timeouts: Vec<Uuid>,
+    /// Moves made for timed out snakes under the missed move policy
+    pub fallback_moves: BTreeMap<Uuid, Direction>,
+    /// Snakes that died this tick and why
+    pub deaths: Vec<Death>,
+    /// Positions of fruits spawned this tick
+    pub fruit_spawns: Vec<Position>,
+}
+
+/// Move made for a snake whose player missed the deadline, if it survives
+///
+/// `Continue` and `AllowMisses` keep the last direction (or the first valid
+/// one before the snake has moved); `AllowMisses(n)` only does so for `n`
+/// missed ticks in a row. Random moves are drawn from the game's seeded RNG
+/// so replays stay reproducible.
+fn fallback_move(policy: MissedMovePolicy, snake: &Snake, rng: &mut StdRng) -> Option<Direction> {
+    let keep_going = || {
+        snake
+            .last_direction
+            .or_else(|| snake.valid_directions().first().copied())
+    };
+
+    match policy {
+        MissedMovePolicy::Kill => None,
+        MissedMovePolicy::Continue => keep_going(),
+        MissedMovePolicy::Random => snake.valid_directions().choose(rng).copied(),
+        MissedMovePolicy::AllowMisses(allowed) if snake.missed_moves < allowed => keep_going(),
+        MissedMovePolicy::AllowMisses(_) => None,
+    }
+}
+
 /// Game statistics for monitoring
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GameStats {
@@ -405,7 +574,7 @@ pub struct GameStats {
 
 impl Default for GameEngine {
     fn default() -> Self {
-        Self::new()
+        Self::new(GameConfig::default())
     }
 }
 
@@ -415,7 +584,7 @@ mod tests {
 
     #[test]
     fn test_game_initialization() {
-        let mut engine = GameEngine::new();
+        let mut engine = GameEngine::default();
         let mut players = HashMap::new();
 
         players.insert(
@@ -428,12 +597,210 @@ mod tests {
             },
         );
 
-        let result = engine.initialize_game(&players);
+        let result = engine.initialize_game(&players, None);
         assert!(result.is_ok());
         assert_eq!(engine.state.snakes.len(), 1);
         assert!(engine.state.is_running);
     }
 
+    fn seeded_players() -> HashMap<Uuid, LobbyPlayer> {
+        (0..4)
+            .map(|i| {
+                let id = Uuid::from_u128(i as u128 + 1);
+                let player = LobbyPlayer {
+                    id,
+                    name: format!("Player{}", i),
+                    color_index: i,
+                    is_ready: true,
+                };
+                (id, player)
+            })
+            .collect()
+    }
+
+    fn play_seeded_game(seed: u64) -> String {
+        let mut engine = GameEngine::default();
+        let seed_used = engine
+            .initialize_game(&seeded_players(), Some(seed))
+            .unwrap();
+        assert_eq!(seed_used, seed);
+
+        for tick in 0..100 {
+            if !engine.state.is_running {
+                break;
+            }
+            let moves = engine
+                .state
+                .snakes
+                .values()
+                .filter(|s| s.is_alive)
+                .map(|s| {
+                    let valid = s.valid_directions();
+                    (s.id, valid[(tick + s.color_index) % valid.len()])
+                })
+                .collect();
+            engine.process_tick(moves).unwrap();
+        }
+
+        serde_json::to_string(&engine.state).unwrap()
+    }
+
+    #[test]
+    fn test_seeded_games_are_reproducible() {
+        assert_eq!(play_seeded_game(42), play_seeded_game(42));
+        assert_ne!(play_seeded_game(42), play_seeded_game(7));
+    }
+
+    #[test]
+    fn test_tick_summary_reports_death_causes() {
+        let mut engine = GameEngine::default();
+        let players = seeded_players();
+        engine.initialize_game(&players, Some(1)).unwrap();
+
+        // Only the first snake moves; everyone else times out
+        let mover = Uuid::from_u128(1);
+        let moves = HashMap::from([(mover, Direction::Up)]);
+        let summary = engine.process_tick(moves).unwrap();
+
+        assert_eq!(summary.tick, 0);
+        assert_eq!(summary.timeouts.len(), players.len() - 1);
+        assert!(summary
+            .deaths
+            .iter()
+            .all(|d| d.snake_id != mover && d.cause == DeathCause::MoveTimeout));
+        assert!(!engine.state.is_running);
+        assert_eq!(engine.state.winner, Some(mover));
+
+        let dead = &engine.state.snakes[&Uuid::from_u128(2)];
+        assert_eq!(dead.death_cause, Some(DeathCause::MoveTimeout));
+        assert_eq!(dead.death_tick, Some(0));
+        assert_eq!(engine.state.deaths_at(0), summary.deaths);
+    }
+
+    #[test]
+    fn test_disconnect_is_reported_with_next_tick() {
+        let mut engine = GameEngine::default();
+        let players = seeded_players();
+        engine.initialize_game(&players, Some(1)).unwrap();
+
+        let leaver = Uuid::from_u128(3);
+        let death = engine
+            .kill_snake(&leaver, DeathCause::Disconnected)
+            .unwrap();
+        assert_eq!(death.tick, 0);
+        assert!(engine
+            .kill_snake(&leaver, DeathCause::Disconnected)
+            .is_none());
+
+        let moves = engine
+            .state
+            .snakes
+            .values()
+            .filter(|s| s.is_alive)
+            .map(|s| (s.id, s.valid_directions()[0]))
+            .collect();
+        let summary = engine.process_tick(moves).unwrap();
+        assert_eq!(summary.deaths.first(), Some(&death));
+        assert!(summary.timeouts.is_empty());
+    }
+
+    #[test]
+    fn test_allowed_misses_keep_snake_moving() {
+        let mut engine = GameEngine::new(GameConfig {
+            missed_move_policy: MissedMovePolicy::AllowMisses(2),
+            ..Default::default()
+        });
+        engine.initialize_game(&seeded_players(), Some(1)).unwrap();
+        engine.state.fruits.clear();
+
+        // Snake 1 moves once, then its player goes quiet
+        let quiet = Uuid::from_u128(1);
+        let everyone_else = |engine: &GameEngine| -> HashMap<Uuid, Direction> {
+            engine
+                .state
+                .snakes
+                .values()
+                .filter(|s| s.is_alive && s.id != quiet)
+                .map(|s| (s.id, s.last_direction.unwrap_or(Direction::Up)))
+                .collect()
+        };
+        let mut moves = everyone_else(&engine);
+        moves.insert(quiet, Direction::Left);
+        engine.process_tick(moves).unwrap();
+
+        for missed in 1..=2 {
+            let summary = engine.process_tick(everyone_else(&engine)).unwrap();
+            assert_eq!(summary.fallback_moves.get(&quiet), Some(&Direction::Left));
+
+            let fallback = ServerMessage::move_fallback(&engine.state, &quiet);
+            assert!(matches!(
+                fallback,
+                Some(ServerMessage::MoveFallback { direction: Direction::Left, missed_moves, .. })
+                    if missed_moves == missed
+            ));
+        }
+
+        let summary = engine.process_tick(everyone_else(&engine)).unwrap();
+        assert!(summary.fallback_moves.is_empty());
+        assert_eq!(
+            engine.state.snakes[&quiet].death_cause,
+            Some(DeathCause::MoveTimeout)
+        );
+        assert!(ServerMessage::move_fallback(&engine.state, &quiet).is_none());
+    }
+
+    #[test]
+    fn test_head_on_collision_cause() {
+        let mut engine = GameEngine::default();
+        let players = seeded_players();
+        engine.initialize_game(&players, Some(1)).unwrap();
+
+        // Put two snakes next to each other and drive them into the same cell
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        engine.state.snakes.get_mut(&a).unwrap().body = [Position::new(10, 10)].into();
+        engine.state.snakes.get_mut(&b).unwrap().body = [Position::new(12, 10)].into();
+        engine.state.fruits.clear();
+        let mut moves: HashMap<Uuid, Direction> = engine
+            .state
+            .snakes
+            .keys()
+            .map(|id| (*id, Direction::Down))
+            .collect();
+        moves.insert(a, Direction::Right);
+        moves.insert(b, Direction::Left);
+
+        let summary = engine.process_tick(moves).unwrap();
+        let death_a = summary.deaths.iter().find(|d| d.snake_id == a).unwrap();
+        assert_eq!(death_a.cause, DeathCause::HeadOnCollision { with: vec![b] });
+    }
+
+    #[test]
+    fn test_ranking_orders_by_death_tick_then_length() {
+        let mut engine = GameEngine::default();
+        engine.initialize_game(&seeded_players(), Some(1)).unwrap();
+
+        // Player 1 survives and wins, 2 and 3 die together, 4 dies first
+        let [a, b, c, d] = [1, 2, 3, 4].map(Uuid::from_u128);
+        for (id, death_tick) in [(b, 7), (c, 7), (d, 3)] {
+            let snake = engine.state.snakes.get_mut(&id).unwrap();
+            snake.is_alive = false;
+            snake.death_tick = Some(death_tick);
+        }
+        engine.state.winner = Some(a);
+
+        let ranking = engine.state.ranking();
+        let placements: Vec<(Uuid, usize)> =
+            ranking.iter().map(|p| (p.player_id, p.placement)).collect();
+        assert_eq!(placements, vec![(a, 1), (b, 2), (c, 2), (d, 4)]);
+
+        // A longer snake places better among those that died together
+        engine.state.snakes.get_mut(&c).unwrap().length += 1;
+        let ranking = engine.state.ranking();
+        assert_eq!(ranking[1].player_id, c);
+        assert_eq!((ranking[1].placement, ranking[2].placement), (2, 3));
+    }
+
     #[test]
     fn test_position_wrapping() {
         let pos = Position::new(0, 0);
@@ -453,7 +820,13 @@ mod tests {
 
     #[test]
     fn test_snake_valid_directions() {
-        let snake = Snake::new(Uuid::new_v4(), "Test".to_string(), Position::new(5, 5), 0);
+        let snake = Snake::new(
+            Uuid::new_v4(),
+            "Test".to_string(),
+            Position::new(5, 5),
+            0,
+            1,
+        );
 
         // New snake should be able to move in any direction
         let valid_dirs = snake.valid_directions();
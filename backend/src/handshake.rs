@@ -0,0 +1,100 @@
+//! Protocol handshake with clients
+//!
+//! Every connection starts with the server's `Hello`, announcing the
+//! protocol versions, encodings and features it speaks and the rules of the
+//! room. A client answering with its own `Hello` gets a `Welcome` with the
+//! newest version both sides speak and the features both want, or an error
+//! and a closed connection if they have no version in common. Clients that
+//! never answer keep the defaults of their connection.
+
+use crate::config::GameConfig;
+use crate::types::*;
+use crate::updates::UpdateStream;
+
+/// Optional features this server supports
+pub const SUPPORTED_FEATURES: [Feature; 1] = [Feature::Deltas];
+
+/// Greeting a connection to a room with the given rules
+pub fn hello(config: &GameConfig) -> ServerMessage {
+    ServerMessage::Hello {
+        protocol_version: PROTOCOL_VERSION,
+        min_protocol_version: MIN_PROTOCOL_VERSION,
+        encodings: Encoding::ALL.to_vec(),
+        features: SUPPORTED_FEATURES.to_vec(),
+        rules: config.clone(),
+    }
+}
+
+/// Agree on a protocol version and features with a client's `Hello`,
+/// turning on the features agreed on
+pub fn welcome(
+    protocol_version: u32,
+    min_protocol_version: Option<u32>,
+    features: &[Feature],
+    updates: &mut UpdateStream,
+) -> GameResult<ServerMessage> {
+    let client_min = min_protocol_version.unwrap_or(protocol_version);
+    let agreed = protocol_version.min(PROTOCOL_VERSION);
+    if agreed < client_min.max(MIN_PROTOCOL_VERSION) {
+        return Err(GameError::IncompatibleProtocol {
+            client_min,
+            client_max: protocol_version,
+            server_min: MIN_PROTOCOL_VERSION,
+            server_max: PROTOCOL_VERSION,
+        });
+    }
+
+    // Deltas may already be on from `?delta=true`
+    if features.contains(&Feature::Deltas) {
+        updates.enable_deltas();
+    }
+
+    Ok(ServerMessage::Welcome {
+        protocol_version: agreed,
+        features: SUPPORTED_FEATURES
+            .into_iter()
+            .filter(|feature| match feature {
+                Feature::Deltas => updates.deltas(),
+                Feature::Unknown => false,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_agrees_on_version_and_features() {
+        let mut updates = UpdateStream::new(false);
+
+        // A newer client falls back to the server's version, and features
+        // the server doesn't know are dropped
+        let welcome = welcome(
+            PROTOCOL_VERSION + 1,
+            Some(MIN_PROTOCOL_VERSION),
+            &[Feature::Deltas, Feature::Unknown],
+            &mut updates,
+        )
+        .unwrap();
+        assert!(matches!(
+            welcome,
+            ServerMessage::Welcome { protocol_version: PROTOCOL_VERSION, features }
+                if features == vec![Feature::Deltas]
+        ));
+        assert!(updates.deltas());
+
+        // A client that can't go back to the server's version is turned away
+        let mut updates = UpdateStream::new(false);
+        let rejected = super::welcome(PROTOCOL_VERSION + 1, None, &[Feature::Deltas], &mut updates);
+        assert!(matches!(
+            rejected,
+            Err(GameError::IncompatibleProtocol { client_min, .. }) if client_min == PROTOCOL_VERSION + 1
+        ));
+        assert!(!updates.deltas());
+
+        // So is one older than any version the server still speaks
+        assert!(super::welcome(MIN_PROTOCOL_VERSION - 1, None, &[], &mut updates).is_err());
+    }
+}
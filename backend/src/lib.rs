@@ -0,0 +1,27 @@
+//! Multiplayer snake game library
+//!
+//! The game engine, rules and message types are usable without the server,
+//! e.g. to simulate matches between local bots (see the `sim` module and the
+//! `snake-sim` binary). The `server` module wires them into the Axum
+//! WebSocket server started by the `backend` binary.
+
+pub mod accounts;
+pub mod bots;
+pub mod config;
+pub mod constants;
+pub mod docs;
+pub mod game;
+pub mod handshake;
+pub mod matches;
+pub mod matchmaking;
+pub mod ratings;
+pub mod replay;
+pub mod rooms;
+pub mod server;
+pub mod sim;
+pub mod tournaments;
+pub mod types;
+pub mod updates;
+
+#[cfg(test)]
+mod test_support;
@@ -3,39 +3,73 @@
 //! This module sets up and starts the Axum web server with WebSocket support
 //! for the multiplayer snake game.
 
-mod constants;
-mod docs;
-mod game;
-mod server;
-mod types;
-
-use server::start_server;
+use backend::config::Config;
+use backend::constants;
+use backend::server::start_server;
 use tracing::{error, info};
 
 #[tokio::main]
 async fn main() {
+    // Initialize tracing
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "backend=debug,tower_http=debug".into()),
+        )
+        .init();
+
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Failed to load configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let host = &config.server.host;
+    let port = config.server.port;
+
     info!("🐍 Starting Multiplayer Snake Game Server");
-    info!(
-        "Server will be available at http://{}:{}",
-        constants::SERVER_HOST,
-        constants::SERVER_PORT
-    );
+    info!("Server will be available at http://{}:{}", host, port);
     info!("WebSocket endpoints:");
     info!(
         "  - Player lobby: ws://{}:{}{}",
-        constants::SERVER_HOST,
-        constants::SERVER_PORT,
+        host,
+        port,
         constants::LOBBY_ENDPOINT
     );
     info!(
         "  - GUI interface: ws://{}:{}{}",
-        constants::SERVER_HOST,
-        constants::SERVER_PORT,
+        host,
+        port,
         constants::GUI_ENDPOINT
     );
+    info!(
+        "  - Spectators: ws://{}:{}{}",
+        host,
+        port,
+        constants::SPECTATE_ENDPOINT
+    );
+    info!("Game rules: {:?}", config.game);
+
+    // Game control always needs a token; make one up if none was configured.
+    // Only a made up token is printed, as nobody could know it otherwise
+    match &mut config.server.admin_token {
+        Some(_) => info!(
+            "🔑 Admin GUI: http://{}:{}/?token=<configured admin token>",
+            host, port
+        ),
+        token @ None => {
+            let generated = token.insert(uuid::Uuid::new_v4().simple().to_string());
+            info!(
+                "🔑 Admin GUI: http://{}:{}/?token={}",
+                host, port, generated
+            );
+        }
+    }
 
     // Start the server
-    if let Err(e) = start_server().await {
+    if let Err(e) = start_server(config).await {
         error!("Failed to start server: {}", e);
         std::process::exit(1);
     }
@@ -0,0 +1,345 @@
+//! Match history of finished games
+//!
+//! Every finished game gets a summary record: who played, who won, how
+//! long each snake grew and how it died, along with the seed and rules. The
+//! records are appended to a JSON Lines file and kept in memory for
+//! querying, so the history survives restarts. The full game is in the
+//! replay with the same id.
+
+use crate::accounts::PlayerRegistry;
+use crate::config::GameConfig;
+use crate::ratings::RatingLadder;
+use crate::replay::ReplayRecord;
+use crate::rooms::RoomId;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::warn;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Number of matches returned when no limit is given
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Largest number of matches returned at once
+const MAX_PAGE_SIZE: usize = 500;
+
+/// Summary of a finished game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MatchRecord {
+    /// Match identifier, the same as the id of its replay
+    pub id: Uuid,
+    /// Room the game was played in
+    pub room: RoomId,
+    /// Unix timestamp (seconds) of the game start
+    pub started_at: u64,
+    /// Unix timestamp (seconds) of the game end
+    pub ended_at: u64,
+    /// Seed the game was played with
+    pub seed: u64,
+    /// Rules the game was played with
+    pub config: GameConfig,
+    /// Number of ticks played
+    pub ticks: u64,
+    pub winner: Option<Uuid>,
+    /// Players in join order
+    pub players: Vec<MatchPlayer>,
+}
+
+/// How a player did in a match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MatchPlayer {
+    pub id: Uuid,
+    pub name: String,
+    /// Final placement, see [`GameState::ranking`]
+    pub placement: usize,
+    /// Whether the player was registered when the match was played, and so
+    /// counts for the ratings
+    pub rated: bool,
+    /// Length of the snake when the game ended
+    pub final_length: usize,
+    /// Whether the snake was still alive at the end
+    pub survived: bool,
+    pub death_cause: Option<DeathCause>,
+    pub death_tick: Option<u64>,
+}
+
+impl MatchRecord {
+    /// Summarize a finished game from its replay and final state
+    ///
+    /// Players in `registry` are marked as rated.
+    pub fn from_replay(
+        records: &[ReplayRecord],
+        final_state: &GameState,
+        registry: &PlayerRegistry,
+    ) -> Option<Self> {
+        let header = match records.first()? {
+            ReplayRecord::Header(header) => header,
+            _ => return None,
+        };
+        let end = match records.last()? {
+            ReplayRecord::End(end) => end,
+            _ => return None,
+        };
+
+        let ranking = final_state.ranking();
+        let players = header
+            .players
+            .iter()
+            .map(|player| {
+                let snake = final_state.snakes.get(&player.id);
+                MatchPlayer {
+                    id: player.id,
+                    name: player.name.clone(),
+                    placement: ranking
+                        .iter()
+                        .find(|p| p.player_id == player.id)
+                        .map_or(ranking.len() + 1, |p| p.placement),
+                    rated: registry.is_registered(player.id),
+                    final_length: snake.map_or(0, |s| s.length),
+                    survived: snake.is_some_and(|s| s.is_alive),
+                    death_cause: snake.and_then(|s| s.death_cause.clone()),
+                    death_tick: snake.and_then(|s| s.death_tick),
+                }
+            })
+            .collect();
+
+        Some(Self {
+            id: header.game_id,
+            room: header.room.clone(),
+            started_at: header.started_at,
+            ended_at: end.ended_at,
+            seed: header.seed,
+            config: header.config.clone(),
+            ticks: end.ticks,
+            winner: end.winner,
+            players,
+        })
+    }
+}
+
+/// Filters and paging for listing matches
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MatchQuery {
+    /// Only matches played in this room
+    pub room: Option<String>,
+    /// Only matches this player took part in, by id or name
+    pub player: Option<String>,
+    /// Only matches won by this player
+    pub winner: Option<Uuid>,
+    /// Only matches started at or after this Unix timestamp
+    pub since: Option<u64>,
+    /// Only matches started before this Unix timestamp
+    pub until: Option<u64>,
+    /// Number of matches to skip
+    pub offset: Option<usize>,
+    /// Number of matches to return (default 50, at most 500)
+    pub limit: Option<usize>,
+}
+
+impl MatchQuery {
+    fn matches(&self, record: &MatchRecord) -> bool {
+        self.room.as_ref().is_none_or(|room| &record.room == room)
+            && self
+                .winner
+                .is_none_or(|winner| record.winner == Some(winner))
+            && self.since.is_none_or(|since| record.started_at >= since)
+            && self.until.is_none_or(|until| record.started_at < until)
+            && self.player.as_ref().is_none_or(|player| {
+                record
+                    .players
+                    .iter()
+                    .any(|p| &p.name == player || p.id.to_string() == *player)
+            })
+    }
+}
+
+/// One page of matches, newest first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MatchPage {
+    /// Number of matches passing the filters
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub matches: Vec<MatchRecord>,
+}
+
+/// Match history kept in a JSON Lines file
+#[derive(Debug, Clone)]
+pub struct MatchStore {
+    path: Arc<PathBuf>,
+    /// All matches in the order they finished
+    matches: Arc<RwLock<Vec<MatchRecord>>>,
+    /// Held while a match is added, so the file and memory keep the same
+    /// order without readers waiting for the disk
+    writer: Arc<Mutex<()>>,
+    /// Ratings registered players earned over these matches
+    ladder: RatingLadder,
+}
+
+impl MatchStore {
+    /// Load the history, starting empty if the file does not exist yet
+    ///
+    /// Lines that cannot be read (e.g. cut short by a crash) are skipped.
+    /// The matches are rated for the players marked as rated in them.
+    pub fn load(path: impl Into<PathBuf>, players: PlayerRegistry) -> io::Result<Self> {
+        let path = path.into();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+
+        let mut matches = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(record) => matches.push(record),
+                Err(e) => warn!("Skipping line {} of {}: {}", number + 1, path.display(), e),
+            }
+        }
+
+        Ok(Self {
+            path: Arc::new(path),
+            ladder: RatingLadder::from_matches(players, &matches),
+            matches: Arc::new(RwLock::new(matches)),
+            writer: Arc::default(),
+        })
+    }
+
+    /// Append a finished match to the history and rate its players
+    pub fn add(&self, record: MatchRecord) -> io::Result<()> {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path.as_path())?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+
+        let mut matches = self.matches.write().unwrap_or_else(|e| e.into_inner());
+        self.ladder.record(&record);
+        matches.push(record);
+        Ok(())
+    }
+
+    /// Ratings of the players in the history
+    pub fn ladder(&self) -> &RatingLadder {
+        &self.ladder
+    }
+
+    /// Look up a match by id
+    pub fn get(&self, id: Uuid) -> Option<MatchRecord> {
+        let matches = self.matches.read().unwrap_or_else(|e| e.into_inner());
+        matches.iter().find(|record| record.id == id).cloned()
+    }
+
+    /// Matches passing the query's filters, newest first
+    pub fn query(&self, query: &MatchQuery) -> MatchPage {
+        let offset = query.offset.unwrap_or(0);
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+
+        let matches = self.matches.read().unwrap_or_else(|e| e.into_inner());
+        let found: Vec<&MatchRecord> = matches
+            .iter()
+            .rev()
+            .filter(|record| query.matches(record))
+            .collect();
+
+        MatchPage {
+            total: found.len(),
+            offset,
+            limit,
+            matches: found
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn record(room: &str, started_at: u64, players: &[&str]) -> MatchRecord {
+        let players: Vec<MatchPlayer> = players
+            .iter()
+            .map(|name| MatchPlayer {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+                placement: 1,
+                rated: false,
+                final_length: 3,
+                survived: false,
+                death_cause: Some(DeathCause::SelfCollision),
+                death_tick: Some(10),
+            })
+            .collect();
+
+        MatchRecord {
+            id: Uuid::new_v4(),
+            room: room.to_string(),
+            started_at,
+            ended_at: started_at + 60,
+            seed: started_at,
+            config: GameConfig::default(),
+            ticks: 12,
+            winner: players.first().map(|p| p.id),
+            players,
+        }
+    }
+
+    #[test]
+    fn test_history_is_filtered_paged_and_reloaded() {
+        let dir = TempDir::new("matches");
+        let path = dir.join("matches.jsonl");
+        let players = PlayerRegistry::load(dir.join("players.json")).unwrap();
+        let store = MatchStore::load(&path, players.clone()).unwrap();
+
+        let first = record("default", 100, &["alice", "bob"]);
+        store.add(first.clone()).unwrap();
+        store
+            .add(record("default", 200, &["bob", "carol"]))
+            .unwrap();
+        store.add(record("cup", 300, &["alice", "carol"])).unwrap();
+
+        let reloaded = MatchStore::load(&path, players).unwrap();
+        assert_eq!(reloaded.get(first.id), Some(first.clone()));
+
+        let alice = reloaded.query(&MatchQuery {
+            player: Some("alice".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(alice.total, 2);
+        assert_eq!(alice.matches[0].room, "cup");
+
+        let page = reloaded.query(&MatchQuery {
+            room: Some("default".to_string()),
+            offset: Some(1),
+            limit: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(page.total, 2);
+        assert_eq!(page.matches, vec![first.clone()]);
+
+        let won = reloaded.query(&MatchQuery {
+            winner: first.winner,
+            since: Some(100),
+            until: Some(200),
+            ..Default::default()
+        });
+        assert_eq!(won.total, 1);
+    }
+}
@@ -0,0 +1,193 @@
+//! Matchmaking queue that seats players in auto-starting rooms
+//!
+//! Players connecting to `/queue` don't pick a room: they are seated in the
+//! fullest queue room still waiting for players, or in a new one if every
+//! queue room is full or playing. Queue rooms start games on their own after
+//! a countdown and keep their players between games, so bots can keep
+//! playing unattended. A queue room closes once its last player has left.
+
+use crate::config::GameConfig;
+use crate::constants::QUEUE_COUNTDOWN_MS;
+use crate::rooms::{CreateRoomRequest, PlayerSession, RoomRegistry, RoomState};
+use crate::types::*;
+use std::sync::Arc;
+use tokio::sync::{broadcast, broadcast::error::RecvError, Mutex};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Prefix of the ids of rooms opened by the queue
+pub const QUEUE_ROOM_PREFIX: &str = "queue-";
+
+/// Seats queued players in rooms
+#[derive(Clone)]
+pub struct Matchmaker {
+    rooms: RoomRegistry,
+    /// Rules of the rooms the queue opens
+    config: GameConfig,
+    /// Held while seating a player, so two players don't race for one seat
+    seating: Arc<Mutex<()>>,
+}
+
+impl Matchmaker {
+    /// Create a matchmaker opening rooms with the registry's default rules,
+    /// auto-starting after `QUEUE_COUNTDOWN_MS` unless they set a countdown
+    pub fn new(rooms: RoomRegistry) -> Self {
+        let mut config = rooms.default_config().clone();
+        config
+            .auto_start_countdown_ms
+            .get_or_insert(QUEUE_COUNTDOWN_MS);
+
+        Self {
+            rooms,
+            config,
+            seating: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Seat a player in the fullest queue room waiting for players, opening
+    /// a new room if there is none
+    pub async fn join(
+        &self,
+        player_id: Uuid,
+        player_name: String,
+    ) -> GameResult<(RoomState, PlayerSession)> {
+        let _seating = self.seating.lock().await;
+
+        let mut waiting: Vec<_> = self
+            .rooms
+            .list()
+            .await
+            .into_iter()
+            .filter(|room| {
+                room.id.starts_with(QUEUE_ROOM_PREFIX)
+                    && !room.is_running
+                    && room.players < room.max_players
+            })
+            .collect();
+        waiting.sort_by_key(|room| std::cmp::Reverse(room.players));
+
+        for summary in waiting {
+            let Ok(room) = self.rooms.get(&summary.id).await else {
+                continue;
+            };
+            // The name may be taken in this room, but not in the next one
+            if let Ok(session) = room.join_player(player_id, player_name.clone()).await {
+                return Ok((room, session));
+            }
+        }
+
+        let room = self
+            .rooms
+            .create(CreateRoomRequest {
+                id: Some(format!(
+                    "{}{}",
+                    QUEUE_ROOM_PREFIX,
+                    &Uuid::new_v4().simple().to_string()[..8]
+                )),
+                config: Some(self.config.clone()),
+            })
+            .await?;
+        info!("🎟️ Queue opened room {}", room.id);
+
+        let events = room.event_sender.subscribe();
+        match room.join_player(player_id, player_name).await {
+            Ok(session) => {
+                tokio::spawn(self.clone().close_when_empty(room.clone(), events));
+                Ok((room, session))
+            }
+            Err(e) => {
+                let _ = self.rooms.remove(&room.id).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Close a queue room once its last player has left
+    async fn close_when_empty(self, room: RoomState, mut events: broadcast::Receiver<GameEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(GameEvent::PlayerLeft(_)) | Err(RecvError::Lagged(_)) => {}
+                Ok(GameEvent::RoomClosed) | Err(RecvError::Closed) => return,
+                Ok(_) => continue,
+            }
+
+            // Nobody is seated in the room while it closes
+            let _seating = self.seating.lock().await;
+            if !room.game_room.read().await.players.is_empty() {
+                continue;
+            }
+            match self.rooms.remove(&room.id).await {
+                Ok(()) => info!("🎟️ Queue closed empty room {}", room.id),
+                Err(e) => warn!("Failed to close queue room {}: {}", room.id, e),
+            }
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::PlayerRegistry;
+    use crate::matches::MatchStore;
+    use crate::replay::ReplayStore;
+    use crate::test_support::TempDir;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_queue_fills_rooms_and_starts_games() {
+        let config = GameConfig {
+            max_players: 2,
+            auto_start_countdown_ms: Some(50),
+            ..Default::default()
+        };
+        let dir = TempDir::new("queue");
+        let rooms = RoomRegistry::new(
+            config,
+            ReplayStore::new(dir.join("replays")),
+            MatchStore::load(
+                dir.join("matches.jsonl"),
+                PlayerRegistry::load(dir.join("players.json")).unwrap(),
+            )
+            .unwrap(),
+        );
+        let queue = Matchmaker::new(rooms.clone());
+
+        let (first, _) = queue.join(Uuid::new_v4(), "alice".into()).await.unwrap();
+        let mut events = first.event_sender.subscribe();
+        let (second, _) = queue.join(Uuid::new_v4(), "bob".into()).await.unwrap();
+        assert_eq!(first.id, second.id);
+        assert!(first.id.starts_with(QUEUE_ROOM_PREFIX));
+
+        // The room is full, so the next player gets a room of their own
+        let carol = Uuid::new_v4();
+        let (third, _) = queue.join(carol, "carol".into()).await.unwrap();
+        assert_ne!(third.id, first.id);
+
+        let started = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                match events.recv().await {
+                    Ok(GameEvent::GameStarted) => return true,
+                    Ok(_) => continue,
+                    Err(_) => return false,
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert!(started);
+        assert!(first.game_engine.read().await.state.is_running);
+        assert!(!third.game_engine.read().await.state.is_running);
+
+        // Once carol leaves, her room is closed
+        third.disconnect_player(carol).await;
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while rooms.get(&third.id).await.is_ok() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(rooms.get(&first.id).await.is_ok());
+    }
+}
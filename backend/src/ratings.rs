@@ -0,0 +1,289 @@
+//! Rating ladder of players across matches
+//!
+//! Ratings are a multiplayer Elo: a match counts as a game between every
+//! pair of its players, won by the one with the better final placement
+//! (a draw if they share it). Each player's rating moves by the sum of their
+//! pairwise results against expectation, scaled so that a match moves a
+//! rating about as far as a single two-player game would.
+//!
+//! Only registered players are rated, since they keep their id between
+//! connections; anonymous players and house bots get a new id every time.
+//! They are left out of the pairwise update, as if they had not played.
+//! Ratings are rebuilt from the match history at startup and need no storage
+//! of their own. Whether a player was registered is recorded with each
+//! match, so revoking a registration later does not change past results.
+
+use crate::accounts::PlayerRegistry;
+use crate::matches::{MatchPlayer, MatchRecord};
+use crate::types::LeaderboardEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// Rating of a player who has not played yet
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// Largest change of a rating in one match
+const K_FACTOR: f64 = 32.0;
+
+/// Number of leaderboard entries returned when no limit is given
+const DEFAULT_LEADERBOARD_SIZE: usize = 100;
+
+/// Rating and record of a player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PlayerRating {
+    pub player_id: Uuid,
+    /// Name the player last played under
+    pub name: String,
+    pub rating: f64,
+    /// Number of rated matches played
+    pub games: u32,
+    pub wins: u32,
+}
+
+/// Filters for the leaderboard
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LeaderboardQuery {
+    /// Only players with at least this many rated matches
+    pub min_games: Option<u32>,
+    /// Number of entries to return (default 100)
+    pub limit: Option<usize>,
+}
+
+/// Ratings of the registered players who played a match, shared by all rooms
+#[derive(Debug, Clone)]
+pub struct RatingLadder {
+    /// Registered players, the only ones listed on the leaderboard
+    players: PlayerRegistry,
+    ratings: Arc<RwLock<HashMap<Uuid, PlayerRating>>>,
+}
+
+impl RatingLadder {
+    /// Rate every match in the order they were played
+    pub fn from_matches<'a>(
+        players: PlayerRegistry,
+        matches: impl IntoIterator<Item = &'a MatchRecord>,
+    ) -> Self {
+        let ladder = Self {
+            players,
+            ratings: Arc::default(),
+        };
+        for record in matches {
+            ladder.record(record);
+        }
+        ladder
+    }
+
+    /// Update the ratings of the rated players of a match
+    ///
+    /// Matches with fewer than two rated players are not rated.
+    pub fn record(&self, record: &MatchRecord) {
+        let rated: Vec<&MatchPlayer> = record
+            .players
+            .iter()
+            .filter(|player| player.rated)
+            .collect();
+        let mut ratings = self.ratings.write().unwrap_or_else(|e| e.into_inner());
+        update_ratings(&mut ratings, &rated, record.winner);
+    }
+
+    /// Registered players, who are rated in the matches they play
+    pub fn players(&self) -> &PlayerRegistry {
+        &self.players
+    }
+
+    /// Rating of a single player, if they played a rated match
+    pub fn get(&self, player_id: &Uuid) -> Option<PlayerRating> {
+        let ratings = self.ratings.read().unwrap_or_else(|e| e.into_inner());
+        ratings.get(player_id).cloned()
+    }
+
+    /// Players ordered by rating, best first
+    pub fn leaderboard(&self, query: &LeaderboardQuery) -> Vec<LeaderboardEntry> {
+        let ratings = self.ratings.read().unwrap_or_else(|e| e.into_inner());
+        // Players whose registration was revoked drop off the board
+        let mut players: Vec<&PlayerRating> = ratings
+            .values()
+            .filter(|player| player.games >= query.min_games.unwrap_or(0))
+            .filter(|player| self.players.is_registered(player.player_id))
+            .collect();
+        players.sort_by(|a, b| {
+            b.rating
+                .total_cmp(&a.rating)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        players
+            .into_iter()
+            .take(query.limit.unwrap_or(DEFAULT_LEADERBOARD_SIZE))
+            .enumerate()
+            .map(|(index, player)| LeaderboardEntry {
+                rank: index + 1,
+                player_id: player.player_id,
+                name: player.name.clone(),
+                rating: player.rating.round() as i64,
+                games: player.games,
+                wins: player.wins,
+            })
+            .collect()
+    }
+}
+
+/// Apply the pairwise Elo update between the rated players of one match
+fn update_ratings(
+    ratings: &mut HashMap<Uuid, PlayerRating>,
+    players: &[&MatchPlayer],
+    winner: Option<Uuid>,
+) {
+    if players.len() < 2 {
+        return;
+    }
+
+    let before: Vec<f64> = players
+        .iter()
+        .map(|p| ratings.get(&p.id).map_or(INITIAL_RATING, |r| r.rating))
+        .collect();
+    let scale = K_FACTOR / (players.len() - 1) as f64;
+
+    for (i, player) in players.iter().enumerate() {
+        let delta: f64 = players
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(j, other)| {
+                let expected = 1.0 / (1.0 + 10f64.powf((before[j] - before[i]) / 400.0));
+                let score = match player.placement.cmp(&other.placement) {
+                    std::cmp::Ordering::Less => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Greater => 0.0,
+                };
+                score - expected
+            })
+            .sum();
+
+        let rating = ratings.entry(player.id).or_insert_with(|| PlayerRating {
+            player_id: player.id,
+            name: player.name.clone(),
+            rating: INITIAL_RATING,
+            games: 0,
+            wins: 0,
+        });
+        rating.name = player.name.clone();
+        rating.rating = before[i] + scale * delta;
+        rating.games += 1;
+        if winner == Some(player.id) {
+            rating.wins += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::matches::MatchPlayer;
+    use crate::test_support::TempDir;
+
+    /// Match between the given players, rating those registered in `registry`
+    fn match_with_placements(registry: &PlayerRegistry, players: &[(Uuid, usize)]) -> MatchRecord {
+        MatchRecord {
+            id: Uuid::new_v4(),
+            room: "default".to_string(),
+            started_at: 0,
+            ended_at: 0,
+            seed: 0,
+            config: GameConfig::default(),
+            ticks: 10,
+            winner: players.iter().find(|(_, place)| *place == 1).map(|p| p.0),
+            players: players
+                .iter()
+                .map(|&(id, placement)| MatchPlayer {
+                    id,
+                    name: format!("bot-{}", id.as_u128()),
+                    placement,
+                    rated: registry.is_registered(id),
+                    final_length: 1,
+                    survived: placement == 1,
+                    death_cause: None,
+                    death_tick: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Registry of `count` freshly registered players in a temp dir
+    fn registered(count: usize) -> (PlayerRegistry, Vec<Uuid>, TempDir) {
+        let dir = TempDir::new("ratings");
+        let players = PlayerRegistry::load(dir.join("players.json")).unwrap();
+        let ids = (0..count)
+            .map(|i| players.register(&format!("player-{}", i)).unwrap().id)
+            .collect();
+        (players, ids, dir)
+    }
+
+    #[test]
+    fn test_ratings_follow_placements() {
+        let (players, ids, _dir) = registered(4);
+        let [a, b, c, d] = [ids[0], ids[1], ids[2], ids[3]];
+        let ladder = RatingLadder::from_matches(
+            players.clone(),
+            &[
+                match_with_placements(&players, &[(a, 1), (b, 2), (c, 3)]),
+                match_with_placements(&players, &[(a, 1), (b, 2), (c, 2)]),
+            ],
+        );
+
+        let board = ladder.leaderboard(&LeaderboardQuery::default());
+        let order: Vec<Uuid> = board.iter().map(|entry| entry.player_id).collect();
+        assert_eq!(order, vec![a, b, c]);
+        assert_eq!(board[0].wins, 2);
+        assert_eq!(board[2].games, 2);
+
+        // Elo is zero-sum between the players of a match
+        let total: f64 = [a, b, c]
+            .iter()
+            .map(|id| ladder.get(id).unwrap().rating)
+            .sum();
+        assert!((total - 3.0 * INITIAL_RATING).abs() < 1e-9);
+
+        let solo = match_with_placements(&players, &[(d, 1)]);
+        ladder.record(&solo);
+        assert!(ladder.get(&d).is_none());
+    }
+
+    #[test]
+    fn test_only_registered_players_are_rated() {
+        let (players, ids, _dir) = registered(2);
+        let [a, b] = [ids[0], ids[1]];
+        let anonymous = Uuid::new_v4();
+
+        let history = [match_with_placements(
+            &players,
+            &[(anonymous, 1), (a, 2), (b, 3)],
+        )];
+        let ladder = RatingLadder::from_matches(players.clone(), &history);
+        let without = RatingLadder::from_matches(
+            players.clone(),
+            &[match_with_placements(&players, &[(a, 2), (b, 3)])],
+        );
+
+        // The anonymous winner is neither listed nor moves anyone's rating
+        assert!(ladder.get(&anonymous).is_none());
+        assert_eq!(ladder.leaderboard(&LeaderboardQuery::default()).len(), 2);
+        for id in [a, b] {
+            assert_eq!(ladder.get(&id), without.get(&id));
+        }
+
+        // A registered player alone with anonymous ones is not rated
+        ladder.record(&match_with_placements(&players, &[(a, 1), (anonymous, 2)]));
+        assert_eq!(ladder.get(&a).unwrap().games, 1);
+
+        // Revoking a registration leaves the ratings rebuilt from the history as they were
+        players.remove(a).unwrap();
+        let rebuilt = RatingLadder::from_matches(players, &history);
+        assert_eq!(rebuilt.get(&b), ladder.get(&b));
+    }
+}
@@ -0,0 +1,438 @@
+//! Game replay recording and storage
+//!
+//! Every finished game is written as a JSON Lines file: a header record with
+//! the rules, seed, players and initial state, one record per processed tick
+//! (moves submitted, timeouts, deaths with cause, fruit spawns) and a final
+//! record with the result. Because games are deterministic for a given seed,
+//! the header and the recorded moves are enough to rebuild every tick.
+
+use crate::config::GameConfig;
+use crate::game::{GameEngine, TickSummary};
+use crate::rooms::RoomId;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Version of the replay file format
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+
+/// Extension of replay files
+const REPLAY_EXTENSION: &str = "jsonl";
+
+/// A single line of a replay file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ReplayRecord {
+    Header(ReplayHeader),
+    Tick(ReplayTick),
+    End(ReplayEnd),
+}
+
+/// First record of a replay, describing how the game was set up
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub version: u32,
+    pub game_id: Uuid,
+    pub room: RoomId,
+    /// Unix timestamp (seconds) of the game start
+    pub started_at: u64,
+    pub config: GameConfig,
+    pub seed: u64,
+    /// Players in join order
+    pub players: Vec<LobbyPlayer>,
+    pub initial_state: GameState,
+}
+
+/// Everything that happened during one tick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayTick {
+    pub tick: u64,
+    /// Moves submitted before the deadline
+    pub moves: BTreeMap<Uuid, Direction>,
+    /// Alive snakes that did not submit a move in time
+    pub timeouts: Vec<Uuid>,
+    /// Moves made for timed out snakes under the missed move policy
+    #[serde(default)]
+    pub fallback_moves: BTreeMap<Uuid, Direction>,
+    pub deaths: Vec<Death>,
+    pub fruit_spawns: Vec<Position>,
+}
+
+/// Last record of a replay
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayEnd {
+    /// Number of ticks played
+    pub ticks: u64,
+    pub winner: Option<Uuid>,
+    /// Unix timestamp (seconds) of the game end
+    pub ended_at: u64,
+}
+
+/// Overview of a stored replay
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReplayInfo {
+    /// Replay identifier (the game id)
+    pub id: Uuid,
+    /// Room the game was played in
+    pub room: String,
+    /// Unix timestamp (seconds) of the game start
+    pub started_at: u64,
+    /// Seed the game was played with
+    pub seed: u64,
+    /// Names of the players in join order
+    pub players: Vec<String>,
+    /// Number of ticks played, if the game finished
+    pub ticks: Option<u64>,
+    /// Winner of the game, if any
+    pub winner: Option<Uuid>,
+}
+
+/// Expand a replay into the game state at every tick, for playback
+///
+/// Every game state is rebuilt by running the recorded moves through the
+/// engine.
+pub fn expand_replay(records: &[ReplayRecord]) -> GameResult<ReplayFrames> {
+    let header = match records.first() {
+        Some(ReplayRecord::Header(header)) => header,
+        _ => {
+            return Err(GameError::Internal(
+                "replay must start with a header".to_string(),
+            ))
+        }
+    };
+    if header.version != REPLAY_FORMAT_VERSION {
+        return Err(GameError::Internal(format!(
+            "unsupported replay format version {}",
+            header.version
+        )));
+    }
+
+    let players: HashMap<Uuid, LobbyPlayer> =
+        header.players.iter().map(|p| (p.id, p.clone())).collect();
+    let mut engine = GameEngine::new(header.config.clone());
+    engine.initialize_game(&players, Some(header.seed))?;
+    if engine.state != header.initial_state {
+        return Err(GameError::Internal(
+            "replay initial state does not match its seed".to_string(),
+        ));
+    }
+
+    let mut frames = vec![engine.state.clone()];
+    for record in &records[1..] {
+        if let ReplayRecord::Tick(tick) = record {
+            // Disconnects and kicks happen between ticks and are not implied by the moves
+            for death in &tick.deaths {
+                if matches!(death.cause, DeathCause::Disconnected | DeathCause::Kicked) {
+                    engine.kill_snake(&death.snake_id, death.cause.clone());
+                }
+            }
+
+            let moves = tick.moves.iter().map(|(id, dir)| (*id, *dir)).collect();
+            engine.process_tick(moves)?;
+            frames.push(engine.state.clone());
+        }
+    }
+
+    Ok(ReplayFrames {
+        id: header.game_id,
+        players: header.players.clone(),
+        winner: engine.state.winner,
+        frames,
+    })
+}
+
+/// Collects the records of a game while it is being played
+#[derive(Debug)]
+pub struct ReplayRecorder {
+    header: ReplayHeader,
+    records: Vec<ReplayRecord>,
+}
+
+impl ReplayRecorder {
+    /// Start recording a freshly initialized game
+    pub fn new(
+        room: RoomId,
+        config: GameConfig,
+        players: &HashMap<Uuid, LobbyPlayer>,
+        initial_state: &GameState,
+    ) -> Self {
+        let mut players: Vec<LobbyPlayer> = players.values().cloned().collect();
+        players.sort_by_key(|p| (p.color_index, p.id));
+
+        let header = ReplayHeader {
+            version: REPLAY_FORMAT_VERSION,
+            game_id: Uuid::new_v4(),
+            room,
+            started_at: unix_now(),
+            config,
+            seed: initial_state.seed.unwrap_or_default(),
+            players,
+            initial_state: initial_state.clone(),
+        };
+
+        Self {
+            header,
+            records: Vec::new(),
+        }
+    }
+
+    /// Identifier of the recorded game
+    pub fn game_id(&self) -> Uuid {
+        self.header.game_id
+    }
+
+    /// Record a processed tick
+    pub fn record_tick(&mut self, moves: &HashMap<Uuid, Direction>, summary: TickSummary) {
+        self.records.push(ReplayRecord::Tick(ReplayTick {
+            tick: summary.tick,
+            moves: moves.iter().map(|(id, dir)| (*id, *dir)).collect(),
+            timeouts: summary.timeouts,
+            fallback_moves: summary.fallback_moves,
+            deaths: summary.deaths,
+            fruit_spawns: summary.fruit_spawns,
+        }));
+    }
+
+    /// Finish the recording and return all records in file order
+    pub fn finish(self, final_state: &GameState) -> Vec<ReplayRecord> {
+        let mut records = Vec::with_capacity(self.records.len() + 2);
+        records.push(ReplayRecord::Header(self.header));
+        records.extend(self.records);
+        records.push(ReplayRecord::End(ReplayEnd {
+            ticks: final_state.tick,
+            winner: final_state.winner,
+            ended_at: unix_now(),
+        }));
+        records
+    }
+}
+
+/// Directory of replay files shared by all rooms
+#[derive(Debug, Clone)]
+pub struct ReplayStore {
+    dir: Arc<PathBuf>,
+}
+
+impl ReplayStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Arc::new(dir.into()),
+        }
+    }
+
+    /// Path of the replay file for a game
+    pub fn path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, REPLAY_EXTENSION))
+    }
+
+    /// Write a finished replay to disk
+    pub fn save(&self, records: &[ReplayRecord]) -> io::Result<PathBuf> {
+        let id = match records.first() {
+            Some(ReplayRecord::Header(header)) => header.game_id,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "replay must start with a header",
+                ))
+            }
+        };
+
+        let mut content = String::new();
+        for record in records {
+            content.push_str(&serde_json::to_string(record)?);
+            content.push('\n');
+        }
+
+        std::fs::create_dir_all(self.dir.as_path())?;
+        let path = self.path(id);
+        std::fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Read all records of a replay
+    pub fn load(&self, id: Uuid) -> io::Result<Vec<ReplayRecord>> {
+        let file = std::fs::File::open(self.path(id))?;
+        BufReader::new(file)
+            .lines()
+            .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect()
+    }
+
+    /// Summaries of all stored replays, newest first
+    pub fn list(&self) -> io::Result<Vec<ReplayInfo>> {
+        let entries = match std::fs::read_dir(self.dir.as_path()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut replays = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if let Some(info) = read_info(&path) {
+                replays.push(info);
+            }
+        }
+
+        replays.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+        Ok(replays)
+    }
+}
+
+/// Build a replay summary from the header and end records of a file
+fn read_info(path: &Path) -> Option<ReplayInfo> {
+    if path.extension()? != REPLAY_EXTENSION {
+        return None;
+    }
+
+    let content = std::fs::read_to_string(path).ok()?;
+    let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+
+    let header = match serde_json::from_str(lines.next()?).ok()? {
+        ReplayRecord::Header(header) => header,
+        _ => return None,
+    };
+    let end = lines
+        .next_back()
+        .and_then(|line| serde_json::from_str(line).ok())
+        .and_then(|record| match record {
+            ReplayRecord::End(end) => Some(end),
+            _ => None,
+        });
+
+    Some(ReplayInfo {
+        id: header.game_id,
+        room: header.room,
+        started_at: header.started_at,
+        seed: header.seed,
+        players: header.players.into_iter().map(|p| p.name).collect(),
+        ticks: end.as_ref().map(|e| e.ticks),
+        winner: end.and_then(|e| e.winner),
+    })
+}
+
+/// Current time as a Unix timestamp in seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameEngine;
+    use crate::test_support::TempDir;
+
+    fn record_game(seed: u64) -> Vec<ReplayRecord> {
+        record_game_kicking(seed, None).0
+    }
+
+    /// Record a game, kicking the first player before the given tick
+    fn record_game_kicking(seed: u64, kick_at: Option<u64>) -> (Vec<ReplayRecord>, GameState) {
+        let players: HashMap<Uuid, LobbyPlayer> = (0..3)
+            .map(|i| {
+                let id = Uuid::from_u128(i as u128 + 1);
+                let player = LobbyPlayer {
+                    id,
+                    name: format!("Bot{}", i),
+                    color_index: i,
+                    is_ready: true,
+                };
+                (id, player)
+            })
+            .collect();
+
+        let mut engine = GameEngine::default();
+        engine.initialize_game(&players, Some(seed)).unwrap();
+        let mut recorder = ReplayRecorder::new(
+            "default".to_string(),
+            engine.config().clone(),
+            &players,
+            &engine.state,
+        );
+
+        while engine.state.is_running && engine.state.tick < 30 {
+            if kick_at == Some(engine.state.tick) {
+                engine.kill_snake(&Uuid::from_u128(1), DeathCause::Kicked);
+            }
+            let moves: HashMap<Uuid, Direction> = engine
+                .state
+                .snakes
+                .values()
+                .filter(|s| s.is_alive)
+                .map(|s| (s.id, s.valid_directions()[0]))
+                .collect();
+            let summary = engine.process_tick(moves.clone()).unwrap();
+            recorder.record_tick(&moves, summary);
+        }
+
+        (recorder.finish(&engine.state), engine.state)
+    }
+
+    #[test]
+    fn test_recording_has_header_ticks_and_end() {
+        let records = record_game(3);
+
+        assert!(matches!(&records[0], ReplayRecord::Header(h) if h.seed == 3));
+        assert!(matches!(records.last(), Some(ReplayRecord::End(_))));
+        assert!(records[1..records.len() - 1]
+            .iter()
+            .all(|r| matches!(r, ReplayRecord::Tick(_))));
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let dir = TempDir::new("replays");
+        let store = ReplayStore::new(dir.to_path_buf());
+        let records = record_game(5);
+
+        store.save(&records).unwrap();
+        let listed = store.list().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].seed, 5);
+        assert_eq!(listed[0].players, vec!["Bot0", "Bot1", "Bot2"]);
+
+        let loaded = store.load(listed[0].id).unwrap();
+        assert_eq!(loaded, records);
+        assert!(store.load(Uuid::new_v4()).is_err());
+    }
+
+    #[test]
+    fn test_frames_rebuild_recorded_game() {
+        let records = record_game(7);
+        let replay = expand_replay(&records).unwrap();
+
+        let ticks = records.len() - 2;
+        assert_eq!(replay.frames.len(), ticks + 1);
+        assert_eq!(replay.frames[0].tick, 0);
+        assert_eq!(replay.frames.last().unwrap().tick, ticks as u64);
+
+        // Fruit spawned in each frame must match what was recorded live
+        for (frame, record) in replay.frames[1..].iter().zip(&records[1..]) {
+            if let ReplayRecord::Tick(tick) = record {
+                for spawn in &tick.fruit_spawns {
+                    assert!(frame.fruits.iter().any(|f| f.position == *spawn));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_frames_replay_kicked_players() {
+        let (records, live) = record_game_kicking(7, Some(3));
+        let replay = expand_replay(&records).unwrap();
+
+        let kicked = &replay.frames.last().unwrap().snakes[&Uuid::from_u128(1)];
+        assert_eq!(kicked.death_cause, Some(DeathCause::Kicked));
+        assert_eq!(replay.frames.last(), Some(&live));
+    }
+}
@@ -0,0 +1,1432 @@
+//! Game room registry for running several matches on one server
+//!
+//! Each room owns its own lobby, game engine, player connections, broadcast
+//! channel and game loop task, so events from one room never reach players
+//! in another. The registry keeps track of all rooms by id.
+
+use crate::bots::{create_bot, Bot, BOT_NAMES};
+use crate::config::GameConfig;
+use crate::constants::{DEFAULT_ROOM_ID, RESULTS_DISPLAY_MS};
+use crate::game::GameEngine;
+use crate::matches::MatchStore;
+use crate::replay::{ReplayRecorder, ReplayStore};
+use crate::server::game_loop;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    sync::{broadcast, broadcast::error::RecvError, watch, Mutex, Notify, RwLock},
+    task::JoinHandle,
+    time::Instant,
+};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Identifier of a game room
+pub type RoomId = String;
+
+/// Maximum length of a room id
+const MAX_ROOM_ID_LENGTH: usize = 64;
+
+/// Shared state of a single game room
+#[derive(Clone)]
+pub struct RoomState {
+    pub id: RoomId,
+    pub game_room: Arc<RwLock<GameRoom>>,
+    pub connections: Arc<RwLock<HashMap<Uuid, PlayerConnection>>>,
+    pub game_engine: Arc<RwLock<GameEngine>>,
+    pub event_sender: broadcast::Sender<GameEvent>,
+    pub replays: ReplayStore,
+    pub matches: MatchStore,
+    /// Built-in bots playing in this room as house players
+    pub house_bots: Arc<Mutex<HashMap<Uuid, Box<dyn Bot>>>>,
+    /// Wakes the game loop when a player submits a move
+    pub moves_submitted: Arc<Notify>,
+    /// Whether an admin paused the running game
+    pub paused: Arc<watch::Sender<bool>>,
+}
+
+impl RoomState {
+    pub fn new(id: RoomId, config: GameConfig, replays: ReplayStore, matches: MatchStore) -> Self {
+        let (event_sender, _) = broadcast::channel(1000);
+
+        Self {
+            id,
+            game_room: Arc::new(RwLock::new(GameRoom::new(config.clone()))),
+            connections: Arc::new(RwLock::new(HashMap::new())),
+            game_engine: Arc::new(RwLock::new(GameEngine::new(config))),
+            event_sender,
+            replays,
+            matches,
+            house_bots: Arc::new(Mutex::new(HashMap::new())),
+            moves_submitted: Arc::new(Notify::new()),
+            paused: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    /// Start a game with everyone in the lobby
+    ///
+    /// Returns the seed the game was initialized with, which can be passed
+    /// again to replay the same spawn and fruit placement.
+    pub async fn start_game(&self, seed: Option<u64>) -> GameResult<u64> {
+        self.start(seed, None).await
+    }
+
+    /// Start a game with some of the players in the lobby
+    ///
+    /// The others stay in the lobby and watch, e.g. tournament entrants
+    /// waiting for their next match.
+    pub async fn start_game_with(&self, seed: Option<u64>, players: &[Uuid]) -> GameResult<u64> {
+        self.start(seed, Some(players)).await
+    }
+
+    async fn start(&self, seed: Option<u64>, only: Option<&[Uuid]>) -> GameResult<u64> {
+        let mut room = self.game_room.write().await;
+        let players: HashMap<Uuid, LobbyPlayer> = room
+            .players
+            .iter()
+            .filter(|(id, _)| only.is_none_or(|only| only.contains(id)))
+            .map(|(id, player)| (*id, player.clone()))
+            .collect();
+
+        // Check if we have enough players to start (players are ready by default)
+        if players.len() < room.config.min_players {
+            return Err(GameError::NotEnoughPlayers {
+                required: room.config.min_players,
+                current: players.len(),
+            });
+        }
+
+        let seed = {
+            let mut engine = self.game_engine.write().await;
+            if engine.state.is_running || room.phase == RoomPhase::Running {
+                return Err(GameError::GameAlreadyRunning);
+            }
+
+            info!("🎯 Initializing game with {} players", players.len());
+            let seed = engine.initialize_game(&players, seed)?;
+            info!("🐍 Game engine initialized successfully (seed {})", seed);
+
+            // Recorded before anyone can leave and change the initial state
+            room.recorder = Some(ReplayRecorder::new(
+                self.id.clone(),
+                engine.config().clone(),
+                &players,
+                &engine.state,
+            ));
+
+            // Moves for the first tick are accepted as soon as players hear of the game
+            let timeout = Duration::from_millis(engine.config().move_timeout_ms);
+            room.open_moves(engine.state.tick, Instant::now() + timeout);
+            seed
+        };
+        room.game_number += 1;
+        self.set_phase(&mut room, RoomPhase::Running);
+        drop(room);
+        self.paused.send_replace(false);
+
+        let _ = self.event_sender.send(GameEvent::GameStarted);
+        info!("📡 GameStarted event sent");
+
+        Ok(seed)
+    }
+
+    /// Stop the running game without a winner
+    ///
+    /// No replay is saved for an aborted game.
+    pub async fn abort_game(&self) -> GameResult<()> {
+        let outcome = {
+            let mut room = self.game_room.write().await;
+            let mut engine = self.game_engine.write().await;
+            engine.abort()?;
+            room.close_moves();
+            GameOutcome::new(&room, engine.state.clone())
+        };
+        self.paused.send_replace(false);
+
+        // Wake the game loop so it notices the game is over
+        self.moves_submitted.notify_one();
+        // Whoever hears of the end may start the next game right away
+        self.end_game().await;
+        let _ = self
+            .event_sender
+            .send(GameEvent::GameEnded(Arc::new(outcome)));
+        info!("🛑 Game in room {} aborted", self.id);
+
+        Ok(())
+    }
+
+    /// Hold the running game before its next tick is processed
+    pub async fn pause_game(&self) -> GameResult<()> {
+        if !self.game_engine.read().await.state.is_running {
+            return Err(GameError::GameNotRunning);
+        }
+
+        if !self.paused.send_replace(true) {
+            let _ = self.event_sender.send(GameEvent::GamePaused);
+            info!("⏸️ Game in room {} paused", self.id);
+        }
+        Ok(())
+    }
+
+    /// Continue a paused game, giving players the full move timeout again
+    pub async fn resume_game(&self) -> GameResult<()> {
+        {
+            let mut room = self.game_room.write().await;
+            let engine = self.game_engine.read().await;
+            if !engine.state.is_running {
+                return Err(GameError::GameNotRunning);
+            }
+            if !*self.paused.borrow() {
+                return Ok(());
+            }
+
+            if room.move_tick.is_some() {
+                let timeout = Duration::from_millis(engine.config().move_timeout_ms);
+                room.move_deadline = Some(Instant::now() + timeout);
+            }
+        }
+
+        self.paused.send_replace(false);
+        let _ = self.event_sender.send(GameEvent::GameResumed);
+        info!("▶️ Game in room {} resumed", self.id);
+        Ok(())
+    }
+
+    /// Rename a player waiting in the lobby, as `JoinLobby` asks
+    ///
+    /// Registered players keep their registered name. Anyone else takes the
+    /// new name unless it is empty or taken, as long as the room is in its
+    /// lobby. Names are trimmed like registered ones. Returns whether the
+    /// player was renamed, which they are not if they already have the name.
+    pub async fn rename_player(&self, player_id: Uuid, name: String) -> GameResult<bool> {
+        let name = name.trim().to_string();
+        let registry = self.matches.ladder().players();
+        let mut room = self.game_room.write().await;
+        let Some(player) = room.players.get(&player_id) else {
+            return Ok(false);
+        };
+        if player.name == name {
+            return Ok(false);
+        }
+        if registry.is_registered(player_id) {
+            return Err(GameError::InvalidRequest(format!(
+                "Registered players keep their name {}",
+                player.name
+            )));
+        }
+
+        if room.phase != RoomPhase::Lobby {
+            return Err(GameError::InvalidRequest(
+                "Players can only be renamed in the lobby".to_string(),
+            ));
+        }
+        if name.is_empty() {
+            return Err(GameError::InvalidRequest(
+                "Player names must not be empty".to_string(),
+            ));
+        }
+        if registry.is_registered_name(&name)
+            || room.players.values().any(|player| player.name == name)
+        {
+            return Err(GameError::NameTaken(name));
+        }
+        if let Some(player) = room.players.get_mut(&player_id) {
+            player.name = name;
+        }
+        Ok(true)
+    }
+
+    /// Remove a player from the room, killing their snake if a game is running
+    pub async fn kick_player(&self, player_id: Uuid) -> GameResult<()> {
+        self.evict_player(player_id, "You were kicked from the room")
+            .await?;
+        info!("👢 Player {} kicked from room {}", player_id, self.id);
+        Ok(())
+    }
+
+    /// Remove everyone from the lobby between games
+    pub async fn reset_lobby(&self) -> GameResult<()> {
+        self.reset_lobby_except(&[]).await
+    }
+
+    /// Remove everyone but the given players from the lobby between games
+    pub async fn reset_lobby_except(&self, keep: &[Uuid]) -> GameResult<()> {
+        if self.game_engine.read().await.state.is_running {
+            return Err(GameError::GameAlreadyRunning);
+        }
+
+        let players: Vec<Uuid> = {
+            let room = self.game_room.read().await;
+            room.players
+                .keys()
+                .filter(|id| !keep.contains(id))
+                .copied()
+                .collect()
+        };
+        for player_id in players {
+            // A player may have left on their own in the meantime
+            let _ = self.evict_player(player_id, "The lobby was reset").await;
+        }
+        info!("🧹 Lobby of room {} reset", self.id);
+
+        Ok(())
+    }
+
+    /// Change the rules of the room for the next game
+    pub async fn update_config(&self, config: GameConfig) -> GameResult<()> {
+        config.validate().map_err(GameError::InvalidRequest)?;
+
+        let mut room = self.game_room.write().await;
+        if room.players.len() > config.max_players {
+            return Err(GameError::InvalidRequest(format!(
+                "max_players is {} but the lobby has {} players",
+                config.max_players,
+                room.players.len()
+            )));
+        }
+
+        self.game_engine.write().await.set_config(config.clone())?;
+        room.config = config;
+        info!("⚙️ Rules of room {} changed", self.id);
+
+        Ok(())
+    }
+
+    /// Take a player's seat, telling them why and closing their connection
+    async fn evict_player(&self, player_id: Uuid, reason: &str) -> GameResult<()> {
+        {
+            let mut room = self.game_room.write().await;
+            if !room.players.contains_key(&player_id) {
+                return Err(GameError::PlayerNotFound(player_id));
+            }
+            room.remove_player(&player_id);
+        }
+        self.house_bots.lock().await.remove(&player_id);
+
+        if let Some(connection) = self.connections.write().await.remove(&player_id) {
+            let _ = connection.sender.send(ServerMessage::Error {
+                message: reason.to_string(),
+            });
+            connection.close.notify_one();
+        }
+
+        self.player_left(player_id, DeathCause::Kicked).await;
+        Ok(())
+    }
+
+    /// Add a player connecting over WebSocket to the lobby
+    ///
+    /// A registered player who already has a seat in the room, e.g. after a
+    /// dropped connection, gets that seat back.
+    pub async fn join_player(
+        &self,
+        player_id: Uuid,
+        player_name: String,
+    ) -> GameResult<PlayerSession> {
+        let resume_token = {
+            let mut room = self.game_room.write().await;
+            if room.players.contains_key(&player_id) {
+                room.disconnected.remove(&player_id);
+                return Ok(PlayerSession {
+                    player_id,
+                    player_name,
+                    resume_token: room.create_session(player_id),
+                    resumed: true,
+                });
+            }
+
+            room.add_player(player_id, player_name.clone())
+                .map_err(GameError::InvalidRequest)?;
+            room.create_session(player_id)
+        };
+
+        let _ = self
+            .event_sender
+            .send(GameEvent::PlayerJoined(player_id, player_name.clone()));
+
+        Ok(PlayerSession {
+            player_id,
+            player_name,
+            resume_token,
+            resumed: false,
+        })
+    }
+
+    /// Give a reconnecting player back the seat their resume token belongs to
+    ///
+    /// `player_id` is who the connection identified as. The seat of a
+    /// registered player is only given back to that player, and a registered
+    /// player cannot take anyone else's seat.
+    pub async fn resume_player(
+        &self,
+        resume_token: Uuid,
+        player_id: Uuid,
+    ) -> GameResult<PlayerSession> {
+        let registry = self.matches.ladder().players();
+        let mut room = self.game_room.write().await;
+        if let Some(&owner) = room.sessions.get(&resume_token) {
+            let registered = registry.is_registered(owner) || registry.is_registered(player_id);
+            if registered && owner != player_id {
+                return Err(GameError::SessionNotFound);
+            }
+        }
+        let player = room
+            .resume_session(&resume_token)
+            .ok_or(GameError::SessionNotFound)?;
+
+        Ok(PlayerSession {
+            player_id: player.id,
+            player_name: player.name.clone(),
+            resume_token,
+            resumed: true,
+        })
+    }
+
+    /// Handle the connection of a player dropping
+    ///
+    /// While their snake is alive in a running game the player keeps their
+    /// seat for the reconnect grace period, missing moves like any other
+    /// player who does not answer. Otherwise, or once the grace period is
+    /// over, they leave the room and their snake dies.
+    pub async fn disconnect_player(&self, player_id: Uuid) {
+        let grace = {
+            let engine = self.game_engine.read().await;
+            if engine.is_snake_alive(&player_id) {
+                Duration::from_millis(engine.config().reconnect_grace_ms)
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if grace.is_zero() {
+            self.game_room.write().await.remove_player(&player_id);
+            self.player_left(player_id, DeathCause::Disconnected).await;
+            return;
+        }
+
+        let since = Instant::now();
+        self.game_room
+            .write()
+            .await
+            .disconnected
+            .insert(player_id, since);
+        info!(
+            "⏳ Holding seat of {} for {}ms",
+            player_id,
+            grace.as_millis()
+        );
+
+        let state = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(grace).await;
+            state.expire_session(player_id, since).await;
+        });
+    }
+
+    /// Remove a disconnected player unless they resumed in the meantime
+    async fn expire_session(&self, player_id: Uuid, since: Instant) {
+        {
+            let mut room = self.game_room.write().await;
+            if room.disconnected.get(&player_id) != Some(&since) {
+                return;
+            }
+            room.remove_player(&player_id);
+        }
+
+        info!("⌛ Reconnect grace period of {} expired", player_id);
+        self.player_left(player_id, DeathCause::Disconnected).await;
+    }
+
+    /// Kill the snake of a player who left and tell everyone about it
+    async fn player_left(&self, player_id: Uuid, cause: DeathCause) {
+        // A snake whose player left mid-game dies instead of waiting out a timeout
+        if let Some(death) = self.game_engine.write().await.kill_snake(&player_id, cause) {
+            info!(
+                "💀 Snake of {} died at tick {}: {:?}",
+                player_id, death.tick, death.cause
+            );
+        }
+
+        let _ = self.event_sender.send(GameEvent::PlayerLeft(player_id));
+    }
+
+    /// Add a built-in bot to the lobby as a house player
+    pub async fn add_house_bot(&self, request: AddBotRequest) -> GameResult<LobbyPlayer> {
+        self.add_house_bot_as(Uuid::new_v4(), request).await
+    }
+
+    /// Add a built-in bot to the lobby under a given player id
+    ///
+    /// Used for entrants that keep their id from one game to the next.
+    pub async fn add_house_bot_as(
+        &self,
+        id: Uuid,
+        request: AddBotRequest,
+    ) -> GameResult<LobbyPlayer> {
+        let bot = create_bot(&request.bot, rand::random()).ok_or_else(|| {
+            GameError::InvalidRequest(format!(
+                "Unknown bot '{}' (available: {})",
+                request.bot,
+                BOT_NAMES.join(", ")
+            ))
+        })?;
+
+        if self.game_engine.read().await.state.is_running {
+            return Err(GameError::GameAlreadyRunning);
+        }
+
+        let mut room = self.game_room.write().await;
+        if room.players.len() >= room.config.max_players {
+            return Err(GameError::RoomFull);
+        }
+
+        let name = match request.name {
+            Some(name) => name,
+            None => (1..)
+                .map(|n| format!("{}-bot-{}", request.bot, n))
+                .find(|name| room.players.values().all(|p| &p.name != name))
+                .expect("an unused bot name exists"),
+        };
+        if room.players.values().any(|p| p.name == name) {
+            return Err(GameError::NameTaken(name));
+        }
+
+        room.add_player(id, name.clone())
+            .map_err(GameError::InvalidRequest)?;
+        let player = room.players[&id].clone();
+        drop(room);
+
+        self.house_bots.lock().await.insert(id, bot);
+        let _ = self.event_sender.send(GameEvent::PlayerJoined(id, name));
+
+        Ok(player)
+    }
+
+    /// Remove a house player from the room, killing their snake if a game
+    /// is running as if they were kicked
+    pub async fn remove_house_bot(&self, id: Uuid) -> GameResult<()> {
+        if self.house_bots.lock().await.remove(&id).is_none() {
+            return Err(GameError::PlayerNotFound(id));
+        }
+
+        self.game_room.write().await.remove_player(&id);
+        self.player_left(id, DeathCause::Kicked).await;
+
+        Ok(())
+    }
+
+    /// Moves of all house players whose snakes are still alive
+    pub async fn house_moves(&self) -> HashMap<Uuid, Direction> {
+        let engine = self.game_engine.read().await;
+        let mut bots = self.house_bots.lock().await;
+
+        bots.iter_mut()
+            .filter(|(id, _)| engine.is_snake_alive(id))
+            .map(|(id, bot)| (*id, bot.next_move(&engine.state, *id)))
+            .collect()
+    }
+
+    /// Move the room to another phase of its lifecycle and tell everyone
+    fn set_phase(&self, room: &mut GameRoom, phase: RoomPhase) {
+        if room.phase != phase {
+            room.phase = phase;
+            let _ = self.event_sender.send(GameEvent::PhaseChanged(phase));
+            info!("🔁 Room {} is now in phase {:?}", self.id, phase);
+        }
+    }
+
+    /// Current phase of the room's lifecycle
+    pub async fn phase(&self) -> RoomPhase {
+        self.game_room.read().await.phase
+    }
+
+    /// Show the results of a game that is over until the lobby opens again
+    pub(crate) async fn end_game(&self) {
+        let mut room = self.game_room.write().await;
+        if room.phase == RoomPhase::Running && !self.game_engine.read().await.state.is_running {
+            self.set_phase(&mut room, RoomPhase::Ended);
+        }
+    }
+
+    /// Open the lobby for the next game once the results were shown
+    ///
+    /// The board of the last game is cleared, and players whose connection
+    /// dropped during the game give up their seats instead of waiting out
+    /// the grace period in the lobby.
+    async fn return_to_lobby(&self) {
+        let gone: Vec<Uuid> = {
+            let mut room = self.game_room.write().await;
+            if room.phase != RoomPhase::Ended {
+                return;
+            }
+            if self.game_engine.write().await.clear_board().is_err() {
+                return;
+            }
+
+            let gone: Vec<Uuid> = room.disconnected.keys().copied().collect();
+            for player_id in &gone {
+                room.remove_player(player_id);
+            }
+            self.set_phase(&mut room, RoomPhase::Lobby);
+            gone
+        };
+
+        for player_id in gone {
+            self.player_left(player_id, DeathCause::Disconnected).await;
+        }
+        info!("🏠 Lobby of room {} is open again", self.id);
+    }
+
+    /// Start a countdown if the rules turn auto-start on and the lobby is
+    /// ready for a game, returning how long it runs
+    async fn begin_countdown(&self) -> Option<Duration> {
+        let mut room = self.game_room.write().await;
+        let countdown = room.config.auto_start_countdown_ms?;
+        if room.phase != RoomPhase::Lobby || room.players.len() < room.config.min_players {
+            return None;
+        }
+
+        self.set_phase(&mut room, RoomPhase::Countdown);
+        let _ = self.event_sender.send(GameEvent::GameStarting(countdown));
+        info!("⏳ Game in room {} starts in {} ms", self.id, countdown);
+        Some(Duration::from_millis(countdown))
+    }
+
+    /// Whether a countdown is still on with enough players to start
+    async fn countdown_holds(&self) -> bool {
+        let room = self.game_room.read().await;
+        room.phase == RoomPhase::Countdown && room.players.len() >= room.config.min_players
+    }
+
+    /// Go back to waiting for players if the countdown is still on
+    async fn cancel_countdown(&self) {
+        let mut room = self.game_room.write().await;
+        if room.phase == RoomPhase::Countdown {
+            self.set_phase(&mut room, RoomPhase::Lobby);
+            let _ = self.event_sender.send(GameEvent::CountdownCancelled);
+            info!("⏳ Countdown in room {} cancelled", self.id);
+        }
+    }
+
+    /// Wait out a countdown, returning whether the lobby is still ready for
+    /// a game at the end
+    async fn count_down(
+        &self,
+        countdown: Duration,
+        events: &mut broadcast::Receiver<GameEvent>,
+    ) -> bool {
+        let deadline = Instant::now() + countdown;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => break,
+                event = events.recv() => {
+                    if matches!(event, Ok(GameEvent::RoomClosed) | Err(RecvError::Closed)) {
+                        return false;
+                    }
+                    // Does nothing if an admin started the game in the meantime
+                    if !self.countdown_holds().await {
+                        self.cancel_countdown().await;
+                        return false;
+                    }
+                }
+            }
+        }
+
+        if !self.countdown_holds().await {
+            self.cancel_countdown().await;
+            return false;
+        }
+        true
+    }
+
+    /// Keep the results of the last game up for a while, unless a new game
+    /// is started first
+    async fn show_results(&self, events: &mut broadcast::Receiver<GameEvent>) {
+        let deadline = Instant::now() + Duration::from_millis(RESULTS_DISPLAY_MS);
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => return,
+                event = events.recv() => {
+                    if matches!(event, Ok(GameEvent::RoomClosed) | Err(RecvError::Closed)) {
+                        return;
+                    }
+                    if self.phase().await != RoomPhase::Ended {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Build a summary of the room for listing
+    pub async fn summary(&self) -> RoomSummary {
+        let room = self.game_room.read().await;
+        let engine = self.game_engine.read().await;
+
+        RoomSummary {
+            id: self.id.clone(),
+            players: room.players.len(),
+            max_players: room.config.max_players,
+            is_running: engine.state.is_running,
+            phase: room.phase,
+            tick: engine.state.tick,
+        }
+    }
+}
+
+/// Drive a room through its lifecycle between games
+///
+/// Once the results of a game were shown for `RESULTS_DISPLAY_MS`, the lobby
+/// opens again with the players who are still connected. If the room's
+/// rules set `auto_start_countdown_ms`, a game is started on its own after
+/// the countdown whenever enough players are in the lobby.
+async fn lifecycle_loop(state: RoomState, mut events: broadcast::Receiver<GameEvent>) {
+    loop {
+        match state.phase().await {
+            RoomPhase::Lobby => {
+                if let Some(countdown) = state.begin_countdown().await {
+                    if state.count_down(countdown, &mut events).await {
+                        if let Err(e) = state.start_game(None).await {
+                            warn!("Auto-start in room {} failed: {}", state.id, e);
+                            state.cancel_countdown().await;
+                        }
+                    }
+                }
+            }
+            RoomPhase::Ended => {
+                state.show_results(&mut events).await;
+                state.return_to_lobby().await;
+                continue;
+            }
+            RoomPhase::Countdown | RoomPhase::Running => {}
+        }
+
+        // Look again whenever anything happens in the room
+        match events.recv().await {
+            Ok(GameEvent::RoomClosed) | Err(RecvError::Closed) => return,
+            _ => {}
+        }
+    }
+}
+
+/// A room together with the tasks running its game loop and lifecycle
+struct RoomEntry {
+    state: RoomState,
+    game_loop: JoinHandle<()>,
+    lifecycle: JoinHandle<()>,
+}
+
+/// Overview of a game room
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RoomSummary {
+    /// Room identifier, used as `?room=<id>` when connecting
+    pub id: RoomId,
+    /// Number of players in the lobby
+    pub players: usize,
+    /// Maximum number of players allowed in the room
+    pub max_players: usize,
+    /// Whether a game is currently running
+    pub is_running: bool,
+    /// Where the room is in its game lifecycle
+    pub phase: RoomPhase,
+    /// Current game tick
+    pub tick: u64,
+}
+
+/// Request body for creating a room
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct CreateRoomRequest {
+    /// Room identifier (letters, digits, `-` and `_`); generated if omitted
+    pub id: Option<RoomId>,
+    /// Rules for the room; the server defaults are used if omitted
+    pub config: Option<GameConfig>,
+}
+
+/// Seat of a WebSocket player in a room
+#[derive(Debug, Clone)]
+pub struct PlayerSession {
+    pub player_id: Uuid,
+    pub player_name: String,
+    /// Token the player can reconnect with to keep their seat
+    pub resume_token: Uuid,
+    /// Whether the player reconnected to an existing seat
+    pub resumed: bool,
+}
+
+/// Request body for adding a house player
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AddBotRequest {
+    /// Built-in strategy: `random`, `greedy`, `flood-fill` or `tail-chaser`
+    pub bot: String,
+    /// Lobby name; `<bot>-bot-<n>` if omitted
+    pub name: Option<String>,
+}
+
+/// Request body for starting a game
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct StartGameRequest {
+    /// Seed for spawn and fruit placement; random if omitted
+    pub seed: Option<u64>,
+}
+
+/// Response to starting a game
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StartGameResponse {
+    /// Room the game was started in
+    pub room: RoomId,
+    /// Seed the game was initialized with
+    pub seed: u64,
+}
+
+/// Registry of all game rooms on the server
+#[derive(Clone)]
+pub struct RoomRegistry {
+    rooms: Arc<RwLock<HashMap<RoomId, RoomEntry>>>,
+    default_config: GameConfig,
+    replays: ReplayStore,
+    matches: MatchStore,
+}
+
+impl RoomRegistry {
+    /// Create a registry containing only the default room
+    pub fn new(default_config: GameConfig, replays: ReplayStore, matches: MatchStore) -> Self {
+        let mut rooms = HashMap::new();
+        let id = DEFAULT_ROOM_ID.to_string();
+        let entry = spawn_room(
+            id.clone(),
+            default_config.clone(),
+            replays.clone(),
+            matches.clone(),
+        );
+        rooms.insert(id, entry);
+
+        Self {
+            rooms: Arc::new(RwLock::new(rooms)),
+            default_config,
+            replays,
+            matches,
+        }
+    }
+
+    /// Create a new room and start its game loop
+    pub async fn create(&self, request: CreateRoomRequest) -> GameResult<RoomState> {
+        let id = match request.id {
+            Some(id) => {
+                validate_room_id(&id)?;
+                id
+            }
+            None => Uuid::new_v4().simple().to_string()[..8].to_string(),
+        };
+
+        let config = request
+            .config
+            .unwrap_or_else(|| self.default_config.clone());
+        config.validate().map_err(GameError::InvalidRequest)?;
+
+        let mut rooms = self.rooms.write().await;
+        if rooms.contains_key(&id) {
+            return Err(GameError::RoomExists(id));
+        }
+
+        let entry = spawn_room(
+            id.clone(),
+            config,
+            self.replays.clone(),
+            self.matches.clone(),
+        );
+        let state = entry.state.clone();
+        rooms.insert(id, entry);
+
+        Ok(state)
+    }
+
+    /// Rules new rooms get when none are given
+    pub fn default_config(&self) -> &GameConfig {
+        &self.default_config
+    }
+
+    /// Look up a room by id
+    pub async fn get(&self, id: &str) -> GameResult<RoomState> {
+        self.rooms
+            .read()
+            .await
+            .get(id)
+            .map(|entry| entry.state.clone())
+            .ok_or_else(|| GameError::RoomNotFound(id.to_string()))
+    }
+
+    /// Look up a room by an optional id, falling back to the default room
+    pub async fn resolve(&self, id: Option<&str>) -> GameResult<RoomState> {
+        self.get(id.unwrap_or(DEFAULT_ROOM_ID)).await
+    }
+
+    /// Summaries of all rooms, ordered by id
+    pub async fn list(&self) -> Vec<RoomSummary> {
+        let states: Vec<RoomState> = self
+            .rooms
+            .read()
+            .await
+            .values()
+            .map(|entry| entry.state.clone())
+            .collect();
+
+        let mut summaries = Vec::with_capacity(states.len());
+        for state in states {
+            summaries.push(state.summary().await);
+        }
+        summaries.sort_by(|a, b| a.id.cmp(&b.id));
+        summaries
+    }
+
+    /// Stop a room's game loop, disconnect its clients and forget it
+    pub async fn remove(&self, id: &str) -> GameResult<()> {
+        if id == DEFAULT_ROOM_ID {
+            return Err(GameError::InvalidRequest(
+                "The default room cannot be deleted".to_string(),
+            ));
+        }
+
+        let entry = self
+            .rooms
+            .write()
+            .await
+            .remove(id)
+            .ok_or_else(|| GameError::RoomNotFound(id.to_string()))?;
+
+        entry.game_loop.abort();
+        entry.lifecycle.abort();
+        let _ = entry.state.event_sender.send(GameEvent::RoomClosed);
+
+        Ok(())
+    }
+}
+
+/// Create a room and spawn the task driving its game loop
+fn spawn_room(
+    id: RoomId,
+    config: GameConfig,
+    replays: ReplayStore,
+    matches: MatchStore,
+) -> RoomEntry {
+    let state = RoomState::new(id, config, replays, matches);
+    let events = state.event_sender.subscribe();
+    let game_loop = tokio::spawn(game_loop(state.clone(), events));
+    let events = state.event_sender.subscribe();
+    let lifecycle = tokio::spawn(lifecycle_loop(state.clone(), events));
+
+    RoomEntry {
+        state,
+        game_loop,
+        lifecycle,
+    }
+}
+
+/// Check that a room id is usable in a URL query
+fn validate_room_id(id: &str) -> GameResult<()> {
+    let valid = !id.is_empty()
+        && id.len() <= MAX_ROOM_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(GameError::InvalidRequest(format!(
+            "Invalid room id '{}': use up to {} letters, digits, '-' or '_'",
+            id, MAX_ROOM_ID_LENGTH
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::PlayerRegistry;
+    use crate::replay::expand_replay;
+    use crate::test_support::TempDir;
+
+    fn test_registry() -> (RoomRegistry, TempDir) {
+        let dir = TempDir::new("rooms");
+        let registry = RoomRegistry::new(
+            GameConfig::default(),
+            ReplayStore::new(dir.join("replays")),
+            MatchStore::load(
+                dir.join("matches.jsonl"),
+                PlayerRegistry::load(dir.join("players.json")).unwrap(),
+            )
+            .unwrap(),
+        );
+        (registry, dir)
+    }
+
+    async fn join(room: &RoomState, name: &str) -> PlayerSession {
+        room.join_player(Uuid::new_v4(), name.to_string())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_registry_starts_with_default_room() {
+        let (registry, _dir) = test_registry();
+        let rooms = registry.list().await;
+
+        assert_eq!(rooms.len(), 1);
+        assert_eq!(rooms[0].id, DEFAULT_ROOM_ID);
+        assert!(registry.resolve(None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_create_and_remove_room() {
+        let (registry, _dir) = test_registry();
+        let room = registry
+            .create(CreateRoomRequest {
+                id: Some("experiment-1".to_string()),
+                config: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(room.id, "experiment-1");
+
+        let duplicate = registry
+            .create(CreateRoomRequest {
+                id: Some("experiment-1".to_string()),
+                config: None,
+            })
+            .await;
+        assert!(matches!(duplicate, Err(GameError::RoomExists(_))));
+
+        registry.remove("experiment-1").await.unwrap();
+        assert!(registry.get("experiment-1").await.is_err());
+        assert!(registry.remove(DEFAULT_ROOM_ID).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rooms_have_separate_channels() {
+        let (registry, _dir) = test_registry();
+        let other = registry.create(CreateRoomRequest::default()).await.unwrap();
+        let default = registry.resolve(None).await.unwrap();
+
+        let mut default_events = default.event_sender.subscribe();
+        let _ = other.event_sender.send(GameEvent::GameTick);
+
+        assert!(default_events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_house_bots_join_and_move() {
+        let (registry, _dir) = test_registry();
+        let room = registry.resolve(None).await.unwrap();
+
+        let request = |bot: &str| AddBotRequest {
+            bot: bot.to_string(),
+            name: None,
+        };
+        let first = room.add_house_bot(request("greedy")).await.unwrap();
+        let second = room.add_house_bot(request("greedy")).await.unwrap();
+        assert_eq!(first.name, "greedy-bot-1");
+        assert_eq!(second.name, "greedy-bot-2");
+        assert!(room.add_house_bot(request("nope")).await.is_err());
+        join(&room, "alice").await;
+
+        room.start_game(Some(4)).await.unwrap();
+        let moves = room.house_moves().await;
+        assert_eq!(moves.len(), 2);
+
+        // A bot removed mid-game leaves its snake dead on the board
+        room.remove_house_bot(first.id).await.unwrap();
+        assert_eq!(room.game_room.read().await.players.len(), 2);
+        {
+            let engine = room.game_engine.read().await;
+            let snake = &engine.state.snakes[&first.id];
+            assert_eq!(snake.death_cause, Some(DeathCause::Kicked));
+        }
+        assert_eq!(room.house_moves().await.len(), 1);
+
+        room.abort_game().await.unwrap();
+        room.remove_house_bot(second.id).await.unwrap();
+        assert_eq!(room.game_room.read().await.players.len(), 1);
+    }
+
+    // The clock only moves once every task is idle, so the grace timers fire
+    // in order with the sleeps below however loaded the machine is
+    #[tokio::test(start_paused = true)]
+    async fn test_dropped_player_can_resume_within_grace_period() {
+        let (registry, _dir) = test_registry();
+        let room = registry
+            .create(CreateRoomRequest {
+                id: None,
+                config: Some(GameConfig {
+                    reconnect_grace_ms: 50,
+                    ..Default::default()
+                }),
+            })
+            .await
+            .unwrap();
+
+        let alice = join(&room, "alice").await;
+        join(&room, "bob").await;
+        room.start_game(Some(1)).await.unwrap();
+
+        // The seat is kept and the same snake is handed back
+        room.disconnect_player(alice.player_id).await;
+        let resumed = room
+            .resume_player(alice.resume_token, Uuid::new_v4())
+            .await
+            .unwrap();
+        assert!(resumed.resumed);
+        assert_eq!(resumed.player_id, alice.player_id);
+
+        // The first timer must not remove a player who resumed
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(room
+            .game_engine
+            .read()
+            .await
+            .is_snake_alive(&alice.player_id));
+
+        room.disconnect_player(alice.player_id).await;
+        tokio::time::sleep(Duration::from_millis(80)).await;
+
+        let engine = room.game_engine.read().await;
+        let snake = &engine.state.snakes[&alice.player_id];
+        assert_eq!(snake.death_cause, Some(DeathCause::Disconnected));
+        assert!(room
+            .resume_player(alice.resume_token, Uuid::new_v4())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_registered_player_reclaims_their_seat() {
+        let (registry, _dir) = test_registry();
+        let room = registry.create(CreateRoomRequest::default()).await.unwrap();
+        let id = Uuid::from_u128(7);
+
+        let first = room.join_player(id, "alice".to_string()).await.unwrap();
+        let second = room.join_player(id, "alice".to_string()).await.unwrap();
+        assert!(!first.resumed);
+        assert!(second.resumed);
+        assert_eq!(second.player_id, id);
+        assert_eq!(room.game_room.read().await.players.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resume_tokens_of_registered_players_need_their_key() {
+        let (registry, _dir) = test_registry();
+        let room = registry.create(CreateRoomRequest::default()).await.unwrap();
+        let players = room.matches.ladder().players().clone();
+        let carol = players.register("carol").unwrap();
+        let dave = players.register("dave").unwrap();
+
+        let seat = room.join_player(carol.id, carol.name).await.unwrap();
+        let bob = join(&room, "bob").await;
+
+        // Only carol's key resumes carol's seat
+        assert!(room
+            .resume_player(seat.resume_token, Uuid::new_v4())
+            .await
+            .is_err());
+        assert!(room
+            .resume_player(seat.resume_token, dave.id)
+            .await
+            .is_err());
+        let resumed = room
+            .resume_player(seat.resume_token, carol.id)
+            .await
+            .unwrap();
+        assert_eq!(resumed.player_id, carol.id);
+
+        // ... and does not resume anyone else's
+        assert!(room
+            .resume_player(bob.resume_token, carol.id)
+            .await
+            .is_err());
+        assert!(room
+            .resume_player(bob.resume_token, Uuid::new_v4())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_moves_are_only_accepted_for_the_open_tick() {
+        let (registry, _dir) = test_registry();
+        let room = registry.create(CreateRoomRequest::default()).await.unwrap();
+        let alice = join(&room, "alice").await;
+        join(&room, "bob").await;
+        room.start_game(Some(2)).await.unwrap();
+
+        let mut game_room = room.game_room.write().await;
+        assert_eq!(game_room.move_tick, Some(0));
+        assert!(matches!(
+            game_room.submit_move(alice.player_id, Direction::Up, 1),
+            Err(GameError::StaleMove {
+                submitted: 1,
+                current: 0
+            })
+        ));
+        game_room
+            .submit_move(alice.player_id, Direction::Up, 0)
+            .unwrap();
+
+        let moves = game_room.close_moves();
+        assert_eq!(moves.get(&alice.player_id), Some(&Direction::Up));
+        assert!(matches!(
+            game_room.submit_move(alice.player_id, Direction::Up, 0),
+            Err(GameError::NoMoveRequested)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_admin_can_pause_and_abort_game() {
+        let (registry, _dir) = test_registry();
+        let room = registry.create(CreateRoomRequest::default()).await.unwrap();
+        join(&room, "alice").await;
+        join(&room, "bob").await;
+        assert!(matches!(
+            room.pause_game().await,
+            Err(GameError::GameNotRunning)
+        ));
+
+        room.start_game(Some(3)).await.unwrap();
+        let mut events = room.event_sender.subscribe();
+        room.pause_game().await.unwrap();
+        room.pause_game().await.unwrap();
+        assert!(*room.paused.borrow());
+        assert!(matches!(events.try_recv(), Ok(GameEvent::GamePaused)));
+        assert!(events.try_recv().is_err());
+
+        room.abort_game().await.unwrap();
+        assert!(!*room.paused.borrow());
+        assert!(matches!(
+            events.try_recv(),
+            Ok(GameEvent::PhaseChanged(RoomPhase::Ended))
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(GameEvent::GameEnded(outcome)) if outcome.winner.is_none()
+        ));
+        assert!(room.game_room.read().await.move_tick.is_none());
+        assert!(room.abort_game().await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_game_replaced_during_a_tick_is_saved_with_its_own_seed() {
+        let (registry, _dir) = test_registry();
+        let room = registry
+            .create(CreateRoomRequest {
+                id: None,
+                config: Some(GameConfig {
+                    game_tick_duration_ms: 1000,
+                    ..Default::default()
+                }),
+            })
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            room.add_house_bot(AddBotRequest {
+                bot: "greedy".to_string(),
+                name: None,
+            })
+            .await
+            .unwrap();
+        }
+        let mut events = room.event_sender.subscribe();
+
+        // The game loop has all moves and sleeps out the minimum tick duration
+        room.start_game(Some(1)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        room.abort_game().await.unwrap();
+        room.start_game(Some(2)).await.unwrap();
+
+        let match_id = loop {
+            if let GameEvent::GameSaved(id) = events.recv().await.unwrap() {
+                break id;
+            }
+        };
+        assert_eq!(room.matches.get(match_id).unwrap().seed, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replay_loads_when_a_player_leaves_right_after_the_start() {
+        let (registry, _dir) = test_registry();
+        let room = registry.create(CreateRoomRequest::default()).await.unwrap();
+        let alice = join(&room, "alice").await;
+        for _ in 0..2 {
+            room.add_house_bot(AddBotRequest {
+                bot: "greedy".to_string(),
+                name: None,
+            })
+            .await
+            .unwrap();
+        }
+        let mut events = room.event_sender.subscribe();
+
+        // Alice is gone before the game loop hears of the game
+        room.start_game(Some(6)).await.unwrap();
+        room.kick_player(alice.player_id).await.unwrap();
+
+        let match_id = loop {
+            if let GameEvent::GameSaved(id) = events.recv().await.unwrap() {
+                break id;
+            }
+        };
+        let records = room.replays.load(match_id).unwrap();
+        assert!(expand_replay(&records).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_room_returns_to_lobby_after_game() {
+        let (registry, _dir) = test_registry();
+        let room = registry.create(CreateRoomRequest::default()).await.unwrap();
+        let alice = join(&room, "alice").await;
+        let bob = join(&room, "bob").await;
+        assert_eq!(room.phase().await, RoomPhase::Lobby);
+
+        room.start_game(Some(5)).await.unwrap();
+        assert_eq!(room.phase().await, RoomPhase::Running);
+        assert!(matches!(
+            room.start_game(None).await,
+            Err(GameError::GameAlreadyRunning)
+        ));
+
+        // Bob's seat is held for the rest of the game, but not into the next one
+        room.disconnect_player(bob.player_id).await;
+        let mut events = room.event_sender.subscribe();
+        room.abort_game().await.unwrap();
+        assert_eq!(room.phase().await, RoomPhase::Ended);
+
+        room.return_to_lobby().await;
+        assert_eq!(room.phase().await, RoomPhase::Lobby);
+
+        // The end of the game is announced with its board, even when heard late
+        let outcome = loop {
+            if let GameEvent::GameEnded(outcome) = events.recv().await.unwrap() {
+                break outcome;
+            }
+        };
+        assert_eq!(outcome.final_state.snakes.len(), 2);
+        assert_eq!(outcome.ranking.len(), 2);
+        let players: Vec<Uuid> = room
+            .game_room
+            .read()
+            .await
+            .players
+            .keys()
+            .copied()
+            .collect();
+        assert_eq!(players, vec![alice.player_id]);
+        assert!(room.game_engine.read().await.state.snakes.is_empty());
+        assert_eq!(room.summary().await.phase, RoomPhase::Lobby);
+    }
+
+    // On a paused clock the cancelled countdown is sure to have run out
+    // before the game is checked
+    #[tokio::test(start_paused = true)]
+    async fn test_auto_start_countdown_is_cancelled_when_players_leave() {
+        let (registry, _dir) = test_registry();
+        let room = registry
+            .create(CreateRoomRequest {
+                id: Some("auto-start".to_string()),
+                config: Some(GameConfig {
+                    auto_start_countdown_ms: Some(100),
+                    ..Default::default()
+                }),
+            })
+            .await
+            .unwrap();
+        let mut events = room.event_sender.subscribe();
+
+        join(&room, "alice").await;
+        let bob = join(&room, "bob").await;
+        loop {
+            if let GameEvent::GameStarting(ms) = events.recv().await.unwrap() {
+                assert_eq!(ms, 100);
+                break;
+            }
+        }
+
+        room.kick_player(bob.player_id).await.unwrap();
+        loop {
+            if let GameEvent::CountdownCancelled = events.recv().await.unwrap() {
+                break;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(!room.game_engine.read().await.state.is_running);
+
+        // With two players again the countdown runs out and the game starts
+        join(&room, "carol").await;
+        loop {
+            if let GameEvent::GameStarted = events.recv().await.unwrap() {
+                break;
+            }
+        }
+        assert!(room.game_engine.read().await.state.is_running);
+    }
+
+    #[tokio::test]
+    async fn test_kick_reset_and_config_changes() {
+        let (registry, _dir) = test_registry();
+        let room = registry.create(CreateRoomRequest::default()).await.unwrap();
+        let alice = join(&room, "alice").await;
+        join(&room, "bob").await;
+        room.add_house_bot(AddBotRequest {
+            bot: "greedy".to_string(),
+            name: None,
+        })
+        .await
+        .unwrap();
+
+        room.start_game(Some(5)).await.unwrap();
+        room.kick_player(alice.player_id).await.unwrap();
+        {
+            let engine = room.game_engine.read().await;
+            let snake = &engine.state.snakes[&alice.player_id];
+            assert_eq!(snake.death_cause, Some(DeathCause::Kicked));
+        }
+        assert!(room.kick_player(alice.player_id).await.is_err());
+        assert!(room.reset_lobby().await.is_err());
+
+        let smaller = GameConfig {
+            min_players: 1,
+            max_players: 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            room.update_config(smaller.clone()).await,
+            Err(GameError::InvalidRequest(_))
+        ));
+
+        room.abort_game().await.unwrap();
+        room.reset_lobby().await.unwrap();
+        assert!(room.game_room.read().await.players.is_empty());
+        assert!(room.house_bots.lock().await.is_empty());
+
+        room.update_config(smaller).await.unwrap();
+        assert_eq!(room.game_room.read().await.config.max_players, 1);
+        assert_eq!(room.game_engine.read().await.config().max_players, 1);
+    }
+
+    #[test]
+    fn test_players_get_the_lowest_free_color() {
+        let mut room = GameRoom::new(GameConfig::default());
+        let alice = Uuid::new_v4();
+        assert_eq!(room.add_player(alice, "alice".to_string()), Ok(0));
+        assert_eq!(room.add_player(Uuid::new_v4(), "bob".to_string()), Ok(1));
+
+        room.remove_player(&alice);
+        assert_eq!(room.add_player(Uuid::new_v4(), "carol".to_string()), Ok(0));
+        assert_eq!(room.add_player(Uuid::new_v4(), "dave".to_string()), Ok(2));
+    }
+
+    #[test]
+    fn test_room_id_validation() {
+        assert!(validate_room_id("team_a-2").is_ok());
+        assert!(validate_room_id("").is_err());
+        assert!(validate_room_id("has space").is_err());
+        assert!(validate_room_id(&"x".repeat(MAX_ROOM_ID_LENGTH + 1)).is_err());
+    }
+}
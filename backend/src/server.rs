@@ -3,26 +3,36 @@
 //! This module implements the axum web server with WebSocket endpoints
 //! for handling player connections and game communication.
 
-use crate::constants::*;
+use crate::accounts::{hash_secret, verify_secret, PlayerRegistry, RegisterPlayerRequest};
+use crate::bots::BOT_NAMES;
+use crate::config::{Config, GameConfig};
 use crate::docs::{ApiDoc, API_DOCUMENTATION};
-use crate::game::GameEngine;
+use crate::handshake;
+use crate::matches::{MatchQuery, MatchRecord, MatchStore};
+use crate::matchmaking::Matchmaker;
+use crate::ratings::LeaderboardQuery;
+use crate::replay::{expand_replay, ReplayRecorder, ReplayStore};
+use crate::rooms::{
+    AddBotRequest, CreateRoomRequest, PlayerSession, RoomRegistry, RoomState, StartGameRequest,
+    StartGameResponse,
+};
+use crate::tournaments::{CreateTournamentRequest, TournamentRegistry};
 use crate::types::*;
+use crate::updates::UpdateStream;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Query, State,
+        FromRequestParts, Path, Query, State,
     },
+    http::{header, request::Parts, HeaderMap, StatusCode},
     response::{Html, IntoResponse, Response},
-    routing::get,
-    Router,
+    routing::{delete, get, post, put},
+    Json, Router,
 };
 
-use futures_util::{sink::SinkExt, stream::StreamExt};
-use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
-use tokio::{
-    sync::{broadcast, mpsc, RwLock},
-
-};
+use futures_util::{sink::SinkExt, stream::{SplitSink, StreamExt}};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{broadcast, mpsc, Notify};
 use tower_http::{cors::CorsLayer, services::ServeDir};
 use tracing::{error, info, warn};
 use utoipa::OpenApi;
@@ -31,21 +41,72 @@ use uuid::Uuid;
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
-    pub game_room: Arc<RwLock<GameRoom>>,
-    pub connections: Arc<RwLock<HashMap<Uuid, PlayerConnection>>>,
-    pub game_engine: Arc<RwLock<GameEngine>>,
-    pub event_sender: broadcast::Sender<GameEvent>,
+    pub rooms: RoomRegistry,
+    pub tournaments: TournamentRegistry,
+    pub matchmaker: Matchmaker,
+    pub replays: ReplayStore,
+    pub matches: MatchStore,
+    /// Hash of the token admin requests must carry; game control is
+    /// disabled without one
+    pub admin_token_hash: Option<Arc<str>>,
+    /// Players who connect with an API key
+    pub players: PlayerRegistry,
+    /// Whether players without an API key are turned away
+    pub require_api_key: bool,
 }
 
 impl AppState {
-    pub fn new() -> Self {
-        let (event_sender, _) = broadcast::channel(1000);
-
-        Self {
-            game_room: Arc::new(RwLock::new(GameRoom::new())),
-            connections: Arc::new(RwLock::new(HashMap::new())),
-            game_engine: Arc::new(RwLock::new(GameEngine::new())),
-            event_sender,
+    pub fn new(config: Config) -> std::io::Result<Self> {
+        let replays = ReplayStore::new(config.server.replay_dir);
+        let players = PlayerRegistry::load(config.server.players_file)?;
+        let matches = MatchStore::load(config.server.match_history_file, players.clone())?;
+
+        let rooms = RoomRegistry::new(config.game, replays.clone(), matches.clone());
+
+        Ok(Self {
+            tournaments: TournamentRegistry::new(rooms.clone(), players.clone()),
+            matchmaker: Matchmaker::new(rooms.clone()),
+            rooms,
+            replays,
+            matches,
+            admin_token_hash: config
+                .server
+                .admin_token
+                .map(|token| Arc::from(hash_secret(&token))),
+            players,
+            require_api_key: config.server.require_api_key,
+        })
+    }
+
+    /// Check a token presented for an admin action
+    pub fn is_admin(&self, token: Option<&str>) -> bool {
+        match (&self.admin_token_hash, token) {
+            (Some(expected), Some(token)) => verify_secret(token, expected),
+            _ => false,
+        }
+    }
+}
+
+/// Extractor for requests authenticated with the admin token
+///
+/// The token is sent as `Authorization: Bearer <token>`.
+pub struct Admin;
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = GameError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        if state.is_admin(token) {
+            Ok(Admin)
+        } else {
+            Err(GameError::Unauthorized)
         }
     }
 }
@@ -55,20 +116,126 @@ impl AppState {
 pub struct ConnectParams {
     pub player_name: Option<String>,
     pub is_gui: Option<bool>,
+    pub room: Option<String>,
+    /// Token from `LobbyJoined` to reclaim a seat after a dropped connection
+    pub resume_token: Option<Uuid>,
+    /// API key of a registered player, for clients that cannot set the
+    /// `X-API-Key` header
+    pub api_key: Option<String>,
+    /// Send game updates as deltas between keyframes
+    #[serde(default)]
+    pub delta: bool,
+    /// Encoding of the messages, instead of a WebSocket subprotocol
+    pub encoding: Option<Encoding>,
 }
 
-/// Create the main application router
-pub fn create_app() -> Router {
-    let state = AppState::new();
+/// Query parameters selecting a room (the default room if omitted)
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+pub struct RoomParams {
+    /// Room identifier
+    pub room: Option<String>,
+}
 
-    // Start the game loop
-    tokio::spawn(game_loop(state.clone()));
+/// Query parameters of the controlling GUI connection
+#[derive(serde::Deserialize)]
+pub struct GuiParams {
+    pub room: Option<String>,
+    /// Admin token; the GUI controls games, so it is required
+    pub token: Option<String>,
+    /// Send game updates as deltas between keyframes
+    #[serde(default)]
+    pub delta: bool,
+    /// Encoding of the messages, instead of a WebSocket subprotocol
+    pub encoding: Option<Encoding>,
+}
+
+/// Query parameters of a spectator connection
+#[derive(serde::Deserialize)]
+pub struct SpectateParams {
+    pub room: Option<String>,
+    /// Send game updates as deltas between keyframes
+    #[serde(default)]
+    pub delta: bool,
+    /// Encoding of the messages, instead of a WebSocket subprotocol
+    pub encoding: Option<Encoding>,
+}
+
+/// Error body returned by HTTP endpoints
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+impl IntoResponse for GameError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            GameError::RoomNotFound(_)
+            | GameError::SessionNotFound
+            | GameError::PlayerNotFound(_)
+            | GameError::ReplayNotFound(_)
+            | GameError::MatchNotFound(_)
+            | GameError::TournamentNotFound(_) => StatusCode::NOT_FOUND,
+            GameError::RoomExists(_)
+            | GameError::NameTaken(_)
+            | GameError::RoomFull
+            | GameError::NotEnoughPlayers { .. }
+            | GameError::GameAlreadyRunning
+            | GameError::GameNotRunning
+            | GameError::StaleMove { .. }
+            | GameError::NoMoveRequested => StatusCode::CONFLICT,
+            GameError::InvalidRequest(_)
+            | GameError::InvalidMove(_)
+            | GameError::Codec(_)
+            | GameError::IncompatibleProtocol { .. } => StatusCode::BAD_REQUEST,
+            GameError::Unauthorized | GameError::InvalidApiKey => StatusCode::UNAUTHORIZED,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
 
-    Router::new()
+        (
+            status,
+            Json(ErrorResponse {
+                error: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+/// Create the main application router
+pub fn create_app(config: Config) -> std::io::Result<Router> {
+    // Creating the state starts the default room and its game loop
+    let state = AppState::new(config)?;
+
+    let router = Router::new()
         .route("/lobby", get(websocket_handler))
+        .route("/queue", get(queue_websocket_handler))
         .route("/gui", get(gui_websocket_handler))
+        .route("/spectate", get(spectate_websocket_handler))
         .route("/health", get(health_check))
         .route("/stats", get(game_stats))
+        .route("/config", get(game_config))
+        .route("/rooms", get(list_rooms).post(create_room))
+        .route("/rooms/:id", delete(delete_room))
+        .route("/rooms/:id/start", post(start_room_game))
+        .route("/rooms/:id/abort", post(abort_room_game))
+        .route("/rooms/:id/pause", post(pause_room_game))
+        .route("/rooms/:id/resume", post(resume_room_game))
+        .route("/rooms/:id/reset", post(reset_room_lobby))
+        .route("/rooms/:id/config", put(update_room_config))
+        .route("/rooms/:id/players/:player_id", delete(kick_player))
+        .route("/rooms/:id/bots", post(add_house_bot))
+        .route("/rooms/:id/bots/:player_id", delete(remove_house_bot))
+        .route("/bots", get(list_bots))
+        .route("/players", get(list_players).post(register_player))
+        .route("/players/:id", delete(remove_player))
+        .route("/matches", get(list_matches))
+        .route("/matches/:id", get(get_match))
+        .route("/leaderboard", get(leaderboard))
+        .route("/tournaments", get(list_tournaments).post(create_tournament))
+        .route("/tournaments/:id", get(get_tournament).delete(cancel_tournament))
+        .route("/replays", get(list_replays))
+        .route("/replays/:id", get(download_replay))
+        .route("/replays/:id/frames", get(replay_frames))
         .route("/", get(serve_index))
         .route("/docs", get(serve_api_docs))
         .route("/swagger", get(serve_swagger_ui))
@@ -77,7 +244,9 @@ pub fn create_app() -> Router {
         .route("/docs/websocket/gui", get(gui_documentation))
         .nest_service("/static", ServeDir::new("static"))
         .layer(CorsLayer::permissive())
-        .with_state(state)
+        .with_state(state);
+
+    Ok(router)
 }
 
 /// Health check endpoint
@@ -99,14 +268,241 @@ async fn health_check() -> impl IntoResponse {
     get,
     path = "/stats",
     tag = "game",
+    params(RoomParams),
     responses(
-        (status = 200, description = "Current game statistics", body = GameStats)
+        (status = 200, description = "Current game statistics", body = GameStats),
+        (status = 404, description = "Room not found", body = ErrorResponse)
     )
 )]
-async fn game_stats(State(state): State<AppState>) -> impl IntoResponse {
-    let engine = state.game_engine.read().await;
+async fn game_stats(
+    State(state): State<AppState>,
+    Query(params): Query<RoomParams>,
+) -> GameResult<impl IntoResponse> {
+    let room = state.rooms.resolve(params.room.as_deref()).await?;
+    let engine = room.game_engine.read().await;
     let stats = engine.get_game_stats();
-    axum::Json(stats)
+    Ok(axum::Json(stats))
+}
+
+/// Get the game rules currently in force
+#[utoipa::path(
+    get,
+    path = "/config",
+    tag = "game",
+    params(RoomParams),
+    responses(
+        (status = 200, description = "Active game configuration", body = GameConfig),
+        (status = 404, description = "Room not found", body = ErrorResponse)
+    )
+)]
+async fn game_config(
+    State(state): State<AppState>,
+    Query(params): Query<RoomParams>,
+) -> GameResult<impl IntoResponse> {
+    let room = state.rooms.resolve(params.room.as_deref()).await?;
+    let game_room = room.game_room.read().await;
+    Ok(axum::Json(game_room.config.clone()))
+}
+
+/// List all game rooms
+#[utoipa::path(
+    get,
+    path = "/rooms",
+    tag = "rooms",
+    responses(
+        (status = 200, description = "All game rooms", body = Vec<RoomSummary>)
+    )
+)]
+async fn list_rooms(State(state): State<AppState>) -> impl IntoResponse {
+    axum::Json(state.rooms.list().await)
+}
+
+/// Create a new game room with its own lobby and game loop
+#[utoipa::path(
+    post,
+    path = "/rooms",
+    tag = "rooms",
+    security(("admin_token" = [])),
+    request_body = CreateRoomRequest,
+    responses(
+        (status = 201, description = "Room created", body = RoomSummary),
+        (status = 400, description = "Invalid room id or config", body = ErrorResponse),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 409, description = "Room already exists", body = ErrorResponse)
+    )
+)]
+async fn create_room(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Json(request): Json<CreateRoomRequest>,
+) -> GameResult<impl IntoResponse> {
+    let room = state.rooms.create(request).await?;
+    info!("🏠 Room {} created", room.id);
+    Ok((StatusCode::CREATED, axum::Json(room.summary().await)))
+}
+
+/// Delete a game room, disconnecting everyone in it
+#[utoipa::path(
+    delete,
+    path = "/rooms/{id}",
+    tag = "rooms",
+    security(("admin_token" = [])),
+    params(("id" = String, Path, description = "Room identifier")),
+    responses(
+        (status = 204, description = "Room deleted"),
+        (status = 400, description = "The default room cannot be deleted", body = ErrorResponse),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse)
+    )
+)]
+async fn delete_room(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> GameResult<impl IntoResponse> {
+    state.rooms.remove(&id).await?;
+    info!("🗑️ Room {} deleted", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the built-in bots that can join a lobby as house players
+#[utoipa::path(
+    get,
+    path = "/bots",
+    tag = "rooms",
+    responses(
+        (status = 200, description = "Names of the built-in bots", body = Vec<String>)
+    )
+)]
+async fn list_bots() -> impl IntoResponse {
+    axum::Json(BOT_NAMES)
+}
+
+/// Add a built-in bot to a room's lobby as a house player
+#[utoipa::path(
+    post,
+    path = "/rooms/{id}/bots",
+    tag = "rooms",
+    security(("admin_token" = [])),
+    params(("id" = String, Path, description = "Room identifier")),
+    request_body = AddBotRequest,
+    responses(
+        (status = 201, description = "House player joined the lobby", body = LobbyPlayer),
+        (status = 400, description = "Unknown bot", body = ErrorResponse),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 409, description = "Room full, name taken or game running", body = ErrorResponse)
+    )
+)]
+async fn add_house_bot(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<AddBotRequest>,
+) -> GameResult<impl IntoResponse> {
+    let room = state.rooms.get(&id).await?;
+    let player = room.add_house_bot(request).await?;
+    info!("🤖 House player {} joined room {}", player.name, room.id);
+    broadcast_lobby_state(&room).await;
+    Ok((StatusCode::CREATED, axum::Json(player)))
+}
+
+/// Remove a house player from a room, killing its snake in a running game
+#[utoipa::path(
+    delete,
+    path = "/rooms/{id}/bots/{player_id}",
+    tag = "rooms",
+    security(("admin_token" = [])),
+    params(
+        ("id" = String, Path, description = "Room identifier"),
+        ("player_id" = Uuid, Path, description = "Id of the house player")
+    ),
+    responses(
+        (status = 204, description = "House player removed"),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room or house player not found", body = ErrorResponse)
+    )
+)]
+async fn remove_house_bot(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path((id, player_id)): Path<(String, Uuid)>,
+) -> GameResult<impl IntoResponse> {
+    let room = state.rooms.get(&id).await?;
+    room.remove_house_bot(player_id).await?;
+    info!("🤖 House player {} left room {}", player_id, room.id);
+    broadcast_lobby_state(&room).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List registered players
+#[utoipa::path(
+    get,
+    path = "/players",
+    tag = "players",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Registered players, ordered by name", body = Vec<RegisteredPlayer>),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse)
+    )
+)]
+async fn list_players(_admin: Admin, State(state): State<AppState>) -> impl IntoResponse {
+    axum::Json(state.players.list())
+}
+
+/// Register a player and issue their API key
+#[utoipa::path(
+    post,
+    path = "/players",
+    tag = "players",
+    request_body = RegisterPlayerRequest,
+    security(("admin_token" = [])),
+    responses(
+        (status = 201, description = "Player registered; the API key is only shown here", body = PlayerAccount),
+        (status = 400, description = "Empty name", body = ErrorResponse),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 409, description = "Name already registered", body = ErrorResponse)
+    )
+)]
+async fn register_player(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Json(request): Json<RegisterPlayerRequest>,
+) -> GameResult<impl IntoResponse> {
+    let players = state.players.clone();
+    let account = tokio::task::spawn_blocking(move || players.register(&request.name))
+        .await
+        .map_err(|e| GameError::Internal(e.to_string()))??;
+
+    info!("🪪 Player {} registered as {}", account.name, account.id);
+    Ok((StatusCode::CREATED, axum::Json(account)))
+}
+
+/// Revoke the API key of a registered player
+#[utoipa::path(
+    delete,
+    path = "/players/{id}",
+    tag = "players",
+    params(("id" = Uuid, Path, description = "Id of the registered player")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Player removed"),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Player not found", body = ErrorResponse)
+    )
+)]
+async fn remove_player(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> GameResult<impl IntoResponse> {
+    let players = state.players.clone();
+    tokio::task::spawn_blocking(move || players.remove(id))
+        .await
+        .map_err(|e| GameError::Internal(e.to_string()))??;
+
+    info!("🪪 Registered player {} removed", id);
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// Serve the main index page
@@ -189,14 +585,14 @@ async fn serve_api_docs() -> impl IntoResponse {
 async fn serve_swagger_ui() -> impl IntoResponse {
     match tokio::fs::read_to_string("static/swagger-ui.html").await {
         Ok(content) => Html(content),
-        Err(_) => Html(format!(
+        Err(_) => Html(
             r#"
             <!DOCTYPE html>
             <html>
             <head>
                 <title>Swagger UI - Snake Game API</title>
                 <style>
-                    body {{ font-family: Arial, sans-serif; margin: 40px; line-height: 1.6; }}
+                    body { font-family: Arial, sans-serif; margin: 40px; line-height: 1.6; }
                 </style>
             </head>
             <body>
@@ -206,7 +602,8 @@ async fn serve_swagger_ui() -> impl IntoResponse {
             </body>
             </html>
             "#
-        )),
+            .to_string(),
+        ),
     }
 }
 
@@ -223,6 +620,388 @@ async fn serve_openapi_spec() -> impl IntoResponse {
     axum::Json(ApiDoc::openapi())
 }
 
+/// Start a game in a room, optionally with a fixed seed
+#[utoipa::path(
+    post,
+    path = "/rooms/{id}/start",
+    tag = "rooms",
+    params(("id" = String, Path, description = "Room identifier")),
+    request_body = StartGameRequest,
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Game started", body = StartGameResponse),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 409, description = "Not enough players or game already running", body = ErrorResponse)
+    )
+)]
+async fn start_room_game(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    request: Option<Json<StartGameRequest>>,
+) -> GameResult<impl IntoResponse> {
+    let room = state.rooms.get(&id).await?;
+    let seed = request.and_then(|Json(request)| request.seed);
+    let seed = room.start_game(seed).await?;
+
+    Ok(axum::Json(StartGameResponse { room: id, seed }))
+}
+
+/// Abort the running game in a room without a winner
+#[utoipa::path(
+    post,
+    path = "/rooms/{id}/abort",
+    tag = "admin",
+    params(("id" = String, Path, description = "Room identifier")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Game aborted"),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 409, description = "No game running", body = ErrorResponse)
+    )
+)]
+async fn abort_room_game(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> GameResult<impl IntoResponse> {
+    state.rooms.get(&id).await?.abort_game().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Pause the tick loop of the running game in a room
+#[utoipa::path(
+    post,
+    path = "/rooms/{id}/pause",
+    tag = "admin",
+    params(("id" = String, Path, description = "Room identifier")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Game paused"),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 409, description = "No game running", body = ErrorResponse)
+    )
+)]
+async fn pause_room_game(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> GameResult<impl IntoResponse> {
+    state.rooms.get(&id).await?.pause_game().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Resume a paused game, restarting the move deadline of the open tick
+#[utoipa::path(
+    post,
+    path = "/rooms/{id}/resume",
+    tag = "admin",
+    params(("id" = String, Path, description = "Room identifier")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Game resumed"),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 409, description = "No game running", body = ErrorResponse)
+    )
+)]
+async fn resume_room_game(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> GameResult<impl IntoResponse> {
+    state.rooms.get(&id).await?.resume_game().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Remove every player and house bot from a room's lobby
+#[utoipa::path(
+    post,
+    path = "/rooms/{id}/reset",
+    tag = "admin",
+    params(("id" = String, Path, description = "Room identifier")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Lobby emptied"),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 409, description = "A game is running", body = ErrorResponse)
+    )
+)]
+async fn reset_room_lobby(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> GameResult<impl IntoResponse> {
+    let room = state.rooms.get(&id).await?;
+    room.reset_lobby().await?;
+    broadcast_lobby_state(&room).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Kick a player out of a room, killing their snake if a game is running
+#[utoipa::path(
+    delete,
+    path = "/rooms/{id}/players/{player_id}",
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "Room identifier"),
+        ("player_id" = Uuid, Path, description = "Id of the player or house player")
+    ),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Player kicked"),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room or player not found", body = ErrorResponse)
+    )
+)]
+async fn kick_player(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path((id, player_id)): Path<(String, Uuid)>,
+) -> GameResult<impl IntoResponse> {
+    let room = state.rooms.get(&id).await?;
+    room.kick_player(player_id).await?;
+    broadcast_lobby_state(&room).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Change the rules of a room, taking effect with the next game
+#[utoipa::path(
+    put,
+    path = "/rooms/{id}/config",
+    tag = "admin",
+    params(("id" = String, Path, description = "Room identifier")),
+    request_body = GameConfig,
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Rules changed", body = GameConfig),
+        (status = 400, description = "Invalid config, or fewer seats than players in the lobby", body = ErrorResponse),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Room not found", body = ErrorResponse),
+        (status = 409, description = "A game is running", body = ErrorResponse)
+    )
+)]
+async fn update_room_config(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(config): Json<GameConfig>,
+) -> GameResult<impl IntoResponse> {
+    let room = state.rooms.get(&id).await?;
+    room.update_config(config.clone()).await?;
+    Ok(axum::Json(config))
+}
+
+/// List finished matches, newest first
+#[utoipa::path(
+    get,
+    path = "/matches",
+    tag = "matches",
+    params(MatchQuery),
+    responses(
+        (status = 200, description = "Matches passing the filters", body = MatchPage)
+    )
+)]
+async fn list_matches(
+    State(state): State<AppState>,
+    Query(query): Query<MatchQuery>,
+) -> impl IntoResponse {
+    axum::Json(state.matches.query(&query))
+}
+
+/// Get the summary of a finished match
+#[utoipa::path(
+    get,
+    path = "/matches/{id}",
+    tag = "matches",
+    params(("id" = Uuid, Path, description = "Match identifier, the same as its replay id")),
+    responses(
+        (status = 200, description = "Match summary", body = MatchRecord),
+        (status = 404, description = "Match not found", body = ErrorResponse)
+    )
+)]
+async fn get_match(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> GameResult<impl IntoResponse> {
+    let record = state.matches.get(id).ok_or(GameError::MatchNotFound(id))?;
+    Ok(axum::Json(record))
+}
+
+/// List tournaments
+#[utoipa::path(
+    get,
+    path = "/tournaments",
+    tag = "tournaments",
+    responses(
+        (status = 200, description = "All tournaments", body = Vec<TournamentSummary>)
+    )
+)]
+async fn list_tournaments(State(state): State<AppState>) -> impl IntoResponse {
+    axum::Json(state.tournaments.list().await)
+}
+
+/// Create a tournament of built-in bots and registered players and start
+/// playing its matches
+#[utoipa::path(
+    post,
+    path = "/tournaments",
+    tag = "tournaments",
+    request_body = CreateTournamentRequest,
+    security(("admin_token" = [])),
+    responses(
+        (status = 201, description = "Tournament created and started", body = Tournament),
+        (status = 400, description = "Invalid entrants, group size or config", body = ErrorResponse),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Registered player not found", body = ErrorResponse),
+        (status = 409, description = "Two entrants share a name", body = ErrorResponse)
+    )
+)]
+async fn create_tournament(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Json(request): Json<CreateTournamentRequest>,
+) -> GameResult<impl IntoResponse> {
+    let tournament = state.tournaments.create(request).await?;
+    Ok((StatusCode::CREATED, axum::Json(tournament)))
+}
+
+/// Get a tournament with its rounds and standings
+#[utoipa::path(
+    get,
+    path = "/tournaments/{id}",
+    tag = "tournaments",
+    params(("id" = Uuid, Path, description = "Tournament identifier")),
+    responses(
+        (status = 200, description = "Tournament", body = Tournament),
+        (status = 404, description = "Tournament not found", body = ErrorResponse)
+    )
+)]
+async fn get_tournament(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> GameResult<impl IntoResponse> {
+    Ok(axum::Json(state.tournaments.get(id).await?))
+}
+
+/// Cancel a running tournament, aborting the match being played
+#[utoipa::path(
+    delete,
+    path = "/tournaments/{id}",
+    tag = "tournaments",
+    params(("id" = Uuid, Path, description = "Tournament identifier")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Tournament cancelled"),
+        (status = 400, description = "Tournament no longer running", body = ErrorResponse),
+        (status = 401, description = "Admin token missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Tournament not found", body = ErrorResponse)
+    )
+)]
+async fn cancel_tournament(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> GameResult<impl IntoResponse> {
+    state.tournaments.cancel(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Rank players by their rating over all finished matches
+#[utoipa::path(
+    get,
+    path = "/leaderboard",
+    tag = "matches",
+    params(LeaderboardQuery),
+    responses(
+        (status = 200, description = "Players ordered by rating, best first", body = [LeaderboardEntry])
+    )
+)]
+async fn leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<LeaderboardQuery>,
+) -> impl IntoResponse {
+    axum::Json(state.matches.ladder().leaderboard(&query))
+}
+
+/// List recorded game replays
+#[utoipa::path(
+    get,
+    path = "/replays",
+    tag = "replays",
+    responses(
+        (status = 200, description = "Recorded replays, newest first", body = Vec<ReplayInfo>)
+    )
+)]
+async fn list_replays(State(state): State<AppState>) -> GameResult<impl IntoResponse> {
+    let store = state.replays.clone();
+    let replays = tokio::task::spawn_blocking(move || store.list())
+        .await
+        .map_err(|e| GameError::Internal(e.to_string()))?
+        .map_err(|e| GameError::Internal(format!("Failed to list replays: {}", e)))?;
+
+    Ok(axum::Json(replays))
+}
+
+/// Download a replay as a JSON Lines file
+#[utoipa::path(
+    get,
+    path = "/replays/{id}",
+    tag = "replays",
+    params(("id" = Uuid, Path, description = "Replay identifier")),
+    responses(
+        (status = 200, description = "Replay file: a Header line, one Tick line per tick and an End line", content_type = "application/x-ndjson"),
+        (status = 404, description = "Replay not found", body = ErrorResponse)
+    )
+)]
+async fn download_replay(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> GameResult<impl IntoResponse> {
+    let content = tokio::fs::read(state.replays.path(id))
+        .await
+        .map_err(|_| GameError::ReplayNotFound(id))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.jsonl\"", id),
+            ),
+        ],
+        content,
+    ))
+}
+
+/// Game state at every tick of a replay, for playback in the GUI
+#[utoipa::path(
+    get,
+    path = "/replays/{id}/frames",
+    tag = "replays",
+    params(("id" = Uuid, Path, description = "Replay identifier")),
+    responses(
+        (status = 200, description = "Replay expanded into one game state per tick", body = ReplayFrames),
+        (status = 404, description = "Replay not found", body = ErrorResponse)
+    )
+)]
+async fn replay_frames(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> GameResult<impl IntoResponse> {
+    let store = state.replays.clone();
+    let records = tokio::task::spawn_blocking(move || store.load(id))
+        .await
+        .map_err(|e| GameError::Internal(e.to_string()))?
+        .map_err(|_| GameError::ReplayNotFound(id))?;
+
+    Ok(axum::Json(expand_replay(&records)?))
+}
+
 /// WebSocket lobby endpoint documentation
 #[utoipa::path(
     get,
@@ -250,13 +1029,15 @@ async fn websocket_documentation() -> impl IntoResponse {
             <div class="endpoint">
                 <p><span class="method">WebSocket</span> <strong>/lobby</strong></p>
                 <p><strong>Description:</strong> Player connection endpoint for joining game lobby and real-time gameplay</p>
-                <p><strong>URL:</strong> <code>ws://localhost:3000/lobby?player_name=YourName</code></p>
+                <p><strong>URL:</strong> <code>ws://localhost:3000/lobby?player_name=YourName&amp;room=default</code></p>
                 <p><strong>Parameters:</strong></p>
                 <ul>
                     <li><code>player_name</code> (optional): Your display name in the game</li>
+                    <li><code>room</code> (optional): Room to join (defaults to <code>default</code>)</li>
                 </ul>
                 <p><strong>Protocol:</strong> WebSocket with JSON message exchange</p>
-                <p><strong>Supported Messages:</strong> JoinLobby, SubmitMove, StartGame, Ping</p>
+                <p><strong>Supported Messages:</strong> Hello, JoinLobby, SubmitMove, Resync, Ping</p>
+                <p><strong>Starting games:</strong> needs the admin token, through <code>/gui</code> or <code>POST /rooms/{id}/start</code></p>
             </div>
             <p><a href="/docs">← Back to API Documentation</a></p>
         </body>
@@ -291,12 +1072,18 @@ async fn gui_documentation() -> impl IntoResponse {
             <h1>👁️ WebSocket GUI Endpoint</h1>
             <div class="endpoint">
                 <p><span class="method">WebSocket</span> <strong>/gui</strong></p>
-                <p><strong>Description:</strong> Spectator and control interface for game observation and lobby management</p>
-                <p><strong>URL:</strong> <code>ws://localhost:3000/gui</code></p>
+                <p><strong>Description:</strong> Admin interface for game observation and control</p>
+                <p><strong>URL:</strong> <code>ws://localhost:3000/gui?room=default&amp;token=ADMIN_TOKEN</code></p>
                 <p><strong>Protocol:</strong> WebSocket with JSON message exchange</p>
-                <p><strong>Purpose:</strong> Read-only game state monitoring and lobby control</p>
+                <p><strong>Purpose:</strong> Game state monitoring and starting games; refused with 401 without the admin token</p>
                 <p><strong>Supported Messages:</strong> StartGame (send), all server messages (receive)</p>
             </div>
+            <div class="endpoint">
+                <p><span class="method">WebSocket</span> <strong>/spectate</strong></p>
+                <p><strong>Description:</strong> Read-only view of a room for displays and viewers</p>
+                <p><strong>URL:</strong> <code>ws://localhost:3000/spectate?room=default</code></p>
+                <p><strong>Supported Messages:</strong> LobbyState, GameUpdate and GameEnded (receive); anything sent is ignored</p>
+            </div>
             <p><a href="/docs">← Back to API Documentation</a></p>
         </body>
         </html>
@@ -308,86 +1095,335 @@ async fn gui_documentation() -> impl IntoResponse {
 async fn websocket_handler(
     ws: WebSocketUpgrade,
     Query(params): Query<ConnectParams>,
+    headers: HeaderMap,
     State(state): State<AppState>,
 ) -> Response {
-    let player_name = params
-        .player_name
-        .unwrap_or_else(|| format!("Player_{}", Uuid::new_v4()));
+    let room = match state.rooms.resolve(params.room.as_deref()).await {
+        Ok(room) => room,
+        Err(e) => return e.into_response(),
+    };
+    let (player_id, player_name) = match identify_player(&state, &params, &headers) {
+        Ok(player) => player,
+        Err(e) => return e.into_response(),
+    };
 
-    ws.on_upgrade(move |socket| handle_player_connection(socket, player_name, state))
+    ws.protocols(Encoding::SUBPROTOCOLS).on_upgrade(move |socket| {
+        let encoding = connection_encoding(&socket, params.encoding);
+        let updates = UpdateStream::new(params.delta);
+        handle_player_connection(
+            socket,
+            player_id,
+            player_name,
+            params.resume_token,
+            room,
+            encoding,
+            updates,
+        )
+    })
 }
 
-/// WebSocket handler for GUI connections
-async fn gui_websocket_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
-    ws.on_upgrade(move |socket| handle_gui_connection(socket, state))
-}
+/// WebSocket handler for players joining through the matchmaking queue
+async fn queue_websocket_handler(
+    ws: WebSocketUpgrade,
+    Query(params): Query<ConnectParams>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Response {
+    if params.resume_token.is_some() {
+        return GameError::InvalidRequest(
+            "Reconnect through /lobby with the room from LobbyJoined".to_string(),
+        )
+        .into_response();
+    }
+    let (player_id, player_name) = match identify_player(&state, &params, &headers) {
+        Ok(player) => player,
+        Err(e) => return e.into_response(),
+    };
 
-/// Handle a player WebSocket connection
-async fn handle_player_connection(socket: WebSocket, player_name: String, state: AppState) {
-    let player_id = Uuid::new_v4();
-    info!("Player {} ({}) connected", player_name, player_id);
-
-    // Try to add player to the game room
-    let _color_index = {
-        let mut room = state.game_room.write().await;
-        match room.add_player(player_id, player_name.clone()) {
-            Ok(color_index) => color_index,
+    ws.protocols(Encoding::SUBPROTOCOLS).on_upgrade(move |mut socket| async move {
+        let encoding = connection_encoding(&socket, params.encoding);
+        match state.matchmaker.join(player_id, player_name.clone()).await {
+            Ok((room, session)) => {
+                let updates = UpdateStream::new(params.delta);
+                run_player_session(socket, session, room, encoding, updates).await
+            }
             Err(error) => {
-                warn!("Failed to add player {}: {}", player_name, error);
-                return;
+                warn!("Failed to queue player {}: {}", player_name, error);
+                send_error(&mut socket, &error, encoding).await;
             }
         }
+    })
+}
+
+/// Id and name a connecting player plays under
+///
+/// Registered players are known by their API key, anyone else gets a fresh
+/// id and the name they asked for.
+fn identify_player(
+    state: &AppState,
+    params: &ConnectParams,
+    headers: &HeaderMap,
+) -> GameResult<(Uuid, String)> {
+    let api_key = headers
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or(params.api_key.as_deref());
+    let registered = match api_key {
+        Some(key) => Some(
+            state
+                .players
+                .authenticate(key)
+                .ok_or(GameError::InvalidApiKey)?,
+        ),
+        None if state.require_api_key => return Err(GameError::InvalidApiKey),
+        None => None,
     };
 
+    match registered {
+        Some(player) => Ok((player.id, player.name)),
+        None => {
+            let name = params
+                .player_name
+                .clone()
+                .unwrap_or_else(|| format!("Player_{}", Uuid::new_v4()));
+            if state.players.is_registered_name(&name) {
+                return Err(GameError::NameTaken(name));
+            }
+            Ok((Uuid::new_v4(), name))
+        }
+    }
+}
+
+/// Tell a client why it could not be seated
+async fn send_error(socket: &mut WebSocket, error: &GameError, encoding: Encoding) {
+    let message = ServerMessage::Error {
+        message: error.to_string(),
+    };
+    if let Some(frame) = encode_message(&message, encoding) {
+        let _ = socket.send(frame).await;
+    }
+}
+
+/// Encoding a client asked for with `?encoding=`, or else with the
+/// WebSocket subprotocol picked during the handshake
+fn connection_encoding(socket: &WebSocket, requested: Option<Encoding>) -> Encoding {
+    requested
+        .or_else(|| {
+            socket
+                .protocol()
+                .and_then(|protocol| protocol.to_str().ok())
+                .and_then(Encoding::from_subprotocol)
+        })
+        .unwrap_or_default()
+}
+
+/// Frame carrying a message in the connection's encoding
+fn encode_message(message: &ServerMessage, encoding: Encoding) -> Option<Message> {
+    let frame = match encoding {
+        Encoding::Json => serde_json::to_string(message)
+            .map(Message::Text)
+            .map_err(GameError::from),
+        binary => binary
+            .encode(message)
+            .map(Message::Binary)
+            .map_err(GameError::from),
+    };
+    frame
+        .inspect_err(|e| error!("Failed to encode {:?}: {}", message, e))
+        .ok()
+}
+
+/// Message from a client's data frame
+///
+/// Text frames are JSON on any connection; binary frames need a binary
+/// encoding to have been negotiated.
+fn decode_message(frame: Message, encoding: Encoding) -> GameResult<ClientMessage> {
+    match frame {
+        Message::Text(text) => Ok(serde_json::from_str(&text)?),
+        Message::Binary(bytes) if encoding.is_binary() => Ok(encoding.decode(&bytes)?),
+        _ => Err(GameError::InvalidRequest(
+            "Binary messages need ?encoding=msgpack or ?encoding=cbor".to_string(),
+        )),
+    }
+}
+
+/// Forward queued messages to a client until either side goes away
+fn spawn_writer(
+    mut ws_sender: SplitSink<WebSocket, Message>,
+    mut rx: mpsc::UnboundedReceiver<ServerMessage>,
+    encoding: Encoding,
+) {
+    tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let Some(frame) = encode_message(&message, encoding) else {
+                continue;
+            };
+            if ws_sender.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// WebSocket handler for GUI connections
+async fn gui_websocket_handler(
+    ws: WebSocketUpgrade,
+    Query(params): Query<GuiParams>,
+    State(state): State<AppState>,
+) -> Response {
+    if !state.is_admin(params.token.as_deref()) {
+        return GameError::Unauthorized.into_response();
+    }
+
+    let room = match state.rooms.resolve(params.room.as_deref()).await {
+        Ok(room) => room,
+        Err(e) => return e.into_response(),
+    };
+
+    ws.protocols(Encoding::SUBPROTOCOLS).on_upgrade(move |socket| {
+        let encoding = connection_encoding(&socket, params.encoding);
+        handle_gui_connection(socket, room, encoding, UpdateStream::new(params.delta))
+    })
+}
+
+/// WebSocket handler for read-only spectator connections
+async fn spectate_websocket_handler(
+    ws: WebSocketUpgrade,
+    Query(params): Query<SpectateParams>,
+    State(state): State<AppState>,
+) -> Response {
+    let room = match state.rooms.resolve(params.room.as_deref()).await {
+        Ok(room) => room,
+        Err(e) => return e.into_response(),
+    };
+
+    ws.protocols(Encoding::SUBPROTOCOLS).on_upgrade(move |socket| {
+        let encoding = connection_encoding(&socket, params.encoding);
+        handle_spectator_connection(socket, room, encoding, UpdateStream::new(params.delta))
+    })
+}
+
+/// Handle a player WebSocket connection
+async fn handle_player_connection(
+    mut socket: WebSocket,
+    player_id: Uuid,
+    player_name: String,
+    resume_token: Option<Uuid>,
+    state: RoomState,
+    encoding: Encoding,
+    updates: UpdateStream,
+) {
+    // Take a new seat in the lobby or reclaim the one the token belongs to
+    let joined = match resume_token {
+        Some(token) => state.resume_player(token, player_id).await,
+        None => state.join_player(player_id, player_name.clone()).await,
+    };
+    match joined {
+        Ok(session) => run_player_session(socket, session, state, encoding, updates).await,
+        Err(error) => {
+            warn!("Failed to add player {}: {}", player_name, error);
+            send_error(&mut socket, &error, encoding).await;
+        }
+    }
+}
+
+/// Serve a player who has been seated in a room until they leave
+async fn run_player_session(
+    socket: WebSocket,
+    session: PlayerSession,
+    state: RoomState,
+    encoding: Encoding,
+    mut updates: UpdateStream,
+) {
+    let player_id = session.player_id;
+    let player_name = session.player_name.clone();
+
+    if session.resumed {
+        info!("Player {} ({}) reconnected to room {}", player_name, player_id, state.id);
+    } else {
+        info!("Player {} ({}) connected to room {}", player_name, player_id, state.id);
+    }
+
     // Set up connection
-    let (mut ws_sender, mut ws_receiver) = socket.split();
-    let (tx, mut rx) = mpsc::unbounded_channel::<ServerMessage>();
+    let (ws_sender, mut ws_receiver) = socket.split();
+    let (tx, rx) = mpsc::unbounded_channel::<ServerMessage>();
+    let close = Arc::new(Notify::new());
 
-    // Store connection
+    // Store connection, replacing a stale one the player resumed from
     {
         let mut connections = state.connections.write().await;
-        connections.insert(
+        let previous = connections.insert(
             player_id,
             PlayerConnection {
                 player_id,
                 sender: tx.clone(),
+                close: close.clone(),
             },
         );
+        if let Some(previous) = previous {
+            previous.close.notify_one();
+        }
     }
 
-    // Send lobby joined confirmation
+    // Greet the client before anything else, then confirm the seat
+    let _ = tx.send(hello(&state).await);
     let _ = tx.send(ServerMessage::LobbyJoined {
         player_id,
         player_name: player_name.clone(),
+        room: state.id.clone(),
+        resume_token: session.resume_token,
     });
 
-    // Notify that a player joined
-    let _ = state.event_sender.send(GameEvent::PlayerJoined(player_id, player_name.clone()));
-
-    // Spawn task to handle outgoing messages
-    tokio::spawn(async move {
-        while let Some(message) = rx.recv().await {
-            if let Ok(json) = serde_json::to_string(&message) {
-                if ws_sender.send(Message::Text(json)).await.is_err() {
-                    break;
-                }
+    // Bring a resumed player back into the running game
+    if session.resumed {
+        let in_game = {
+            let engine = state.game_engine.read().await;
+            let in_game = engine.state.is_running && engine.state.snakes.contains_key(&player_id);
+            if in_game {
+                let _ = tx.send(ServerMessage::GameStarted {
+                    game_state: engine.state.clone(),
+                    your_snake_id: player_id,
+                });
+            }
+            in_game
+        };
+        if in_game {
+            if let Some(request) = move_request(&state, player_id).await {
+                let _ = tx.send(request);
             }
         }
-    });
+    }
+
+    // Spawn task to handle outgoing messages
+    spawn_writer(ws_sender, rx, encoding);
 
     // Handle incoming messages
     let mut event_receiver = state.event_sender.subscribe();
     loop {
         tokio::select! {
+            // The player resumed their session on a new connection or was kicked
+            _ = close.notified() => {
+                info!("Connection of player {} closed by the server", player_name);
+                break;
+            }
+
             // Handle WebSocket messages
             msg = ws_receiver.next() => {
                 match msg {
-                    Some(Ok(Message::Text(text))) => {
-                        if let Err(e) = handle_player_message(text, player_id, &state).await {
+                    Some(Ok(frame @ (Message::Text(_) | Message::Binary(_)))) => {
+                        let result = match decode_message(frame, encoding) {
+                            Ok(message) => handle_player_message(message, player_id, &state, &mut updates).await,
+                            Err(e) => Err(e),
+                        };
+                        if let Err(e) = result {
                             error!("Error handling player message: {}", e);
                             let _ = tx.send(ServerMessage::Error {
                                 message: format!("Error processing message: {}", e),
                             });
+                            // The client would misread everything that follows
+                            if let GameError::IncompatibleProtocol { .. } = e {
+                                break;
+                            }
                         }
                     }
                     Some(Ok(Message::Close(_))) | None => {
@@ -398,8 +1434,8 @@ async fn handle_player_connection(socket: WebSocket, player_name: String, state:
                         error!("WebSocket error for player {}: {}", player_name, e);
                         break;
                     }
-                    Some(Ok(Message::Binary(_))) | Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => {
-                        // Ignore binary, ping, and pong messages
+                    Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => {
+                        // Ignore ping and pong messages
                     }
                 }
             }
@@ -408,98 +1444,133 @@ async fn handle_player_connection(socket: WebSocket, player_name: String, state:
             Ok(event) = event_receiver.recv() => {
                 match event {
                     GameEvent::GameStarted => {
-                        let engine = state.game_engine.read().await;
-                        if engine.state.snakes.contains_key(&player_id) {
-                            let _ = tx.send(ServerMessage::GameStarted {
-                                game_state: engine.state.clone(),
-                                your_snake_id: player_id,
-                            });
-                            
-                            // Send initial move request
-                            if engine.is_snake_alive(&player_id) {
-                                let valid_directions = engine.get_valid_moves(&player_id);
-                                info!("🎯 Sending initial move request to player {}", player_name);
-                                let _ = tx.send(ServerMessage::MoveRequest {
-                                    valid_directions,
-                                    time_limit_ms: MOVE_TIMEOUT_MS,
+                        updates.restart();
+                        let in_game = {
+                            let engine = state.game_engine.read().await;
+                            let in_game = engine.state.snakes.contains_key(&player_id);
+                            if in_game {
+                                let _ = tx.send(ServerMessage::GameStarted {
+                                    game_state: engine.state.clone(),
+                                    your_snake_id: player_id,
                                 });
                             }
+                            in_game
+                        };
+
+                        // Send initial move request
+                        if in_game {
+                            if let Some(request) = move_request(&state, player_id).await {
+                                info!("🎯 Sending initial move request to player {}", player_name);
+                                let _ = tx.send(request);
+                            }
                         }
                     }
                     GameEvent::GameTick => {
-                        let engine = state.game_engine.read().await;
-                        let _ = tx.send(ServerMessage::GameUpdate {
-                            game_state: engine.state.clone(),
-                        });
+                        {
+                            let room = state.game_room.read().await;
+                            let engine = state.game_engine.read().await;
+                            let _ = tx.send(updates.update(&engine.state, room.phase));
+
+                            // Tell the player about a move made on their behalf
+                            if let Some(fallback) = ServerMessage::move_fallback(&engine.state, &player_id) {
+                                let _ = tx.send(fallback);
+                            }
+                        }
 
                         // Request next move if snake is alive
-                        if engine.is_snake_alive(&player_id) {
-                            let valid_directions = engine.get_valid_moves(&player_id);
-                            let _ = tx.send(ServerMessage::MoveRequest {
-                                valid_directions,
-                                time_limit_ms: MOVE_TIMEOUT_MS,
-                            });
+                        if let Some(request) = move_request(&state, player_id).await {
+                            let _ = tx.send(request);
                         }
                     }
-                    GameEvent::GameEnded(winner_id) => {
-                        let room = state.game_room.read().await;
-                        let winner = winner_id.and_then(|id| room.players.get(&id).cloned());
-                        let engine = state.game_engine.read().await;
-
-                        let _ = tx.send(ServerMessage::GameEnded {
-                            winner,
-                            final_state: engine.state.clone(),
-                        });
+                    GameEvent::GameStarting(starts_in_ms) => {
+                        let _ = tx.send(ServerMessage::GameStarting { starts_in_ms });
+                    }
+                    GameEvent::CountdownCancelled => {
+                        let _ = tx.send(ServerMessage::CountdownCancelled);
+                    }
+                    GameEvent::PhaseChanged(phase) => {
+                        let _ = tx.send(ServerMessage::PhaseChanged { phase });
+                    }
+                    GameEvent::GamePaused => {
+                        let _ = tx.send(ServerMessage::GamePaused);
                     }
-                    GameEvent::PlayerJoined(_, _) | GameEvent::PlayerLeft(_) => {
+                    GameEvent::GameResumed => {
+                        // The deadline moved, so the player hears of the new time limit
+                        let _ = tx.send(ServerMessage::GameResumed);
+                        if let Some(request) = move_request(&state, player_id).await {
+                            let _ = tx.send(request);
+                        }
+                    }
+                    GameEvent::GameEnded(outcome) => {
+                        let _ = tx.send(outcome.message());
+                    }
+                    GameEvent::PlayerJoined(_, _) | GameEvent::PlayerLeft(_) | GameEvent::GameSaved(_) => {
                         // These events don't affect individual player connections
                     }
+                    GameEvent::RoomClosed => {
+                        info!("Room {} closed - disconnecting player {}", state.id, player_name);
+                        let _ = tx.send(ServerMessage::Error {
+                            message: "Room closed".to_string(),
+                        });
+                        break;
+                    }
                 }
             }
         }
     }
 
-    // Clean up connection
+    // Clean up connection, unless the player already resumed elsewhere
     {
         let mut connections = state.connections.write().await;
-        connections.remove(&player_id);
+        match connections.get(&player_id) {
+            Some(connection) if connection.sender.same_channel(&tx) => {
+                connections.remove(&player_id);
+            }
+            _ => return,
+        }
     }
 
-    {
-        let mut room = state.game_room.write().await;
-        room.remove_player(&player_id);
-    }
+    state.disconnect_player(player_id).await;
+}
+
+/// Move request for a player whose snake is alive, while moves are open
+///
+/// The time limit is what is left until the deadline of the open tick, so a
+/// player hearing of the tick late is not promised more time than they have.
+async fn move_request(state: &RoomState, player_id: Uuid) -> Option<ServerMessage> {
+    let (tick, time_left) = {
+        let room = state.game_room.read().await;
+        (room.move_tick?, room.time_left())
+    };
 
-    let _ = state.event_sender.send(GameEvent::PlayerLeft(player_id));
+    let engine = state.game_engine.read().await;
+    engine
+        .is_snake_alive(&player_id)
+        .then(|| ServerMessage::MoveRequest {
+            tick,
+            valid_directions: engine.get_valid_moves(&player_id),
+            time_limit_ms: time_left.as_millis() as u64,
+        })
 }
 
 /// Handle GUI WebSocket connection
-async fn handle_gui_connection(socket: WebSocket, state: AppState) {
-    info!("🎮 GUI connected - initializing interface");
+async fn handle_gui_connection(
+    socket: WebSocket,
+    state: RoomState,
+    encoding: Encoding,
+    mut updates: UpdateStream,
+) {
+    info!("🎮 GUI connected to room {} - initializing interface", state.id);
 
-    let (mut ws_sender, mut ws_receiver) = socket.split();
-    let (tx, mut rx) = mpsc::unbounded_channel::<ServerMessage>();
+    let (ws_sender, mut ws_receiver) = socket.split();
+    let (tx, rx) = mpsc::unbounded_channel::<ServerMessage>();
 
     // Spawn task to handle outgoing messages
-    tokio::spawn(async move {
-        while let Some(message) = rx.recv().await {
-            if let Ok(json) = serde_json::to_string(&message) {
-                if ws_sender.send(Message::Text(json)).await.is_err() {
-                    break;
-                }
-            }
-        }
-    });
+    spawn_writer(ws_sender, rx, encoding);
 
-
-    // Send initial lobby state directly to this GUI connection
-    {
-        let room = state.game_room.read().await;
-        let players: Vec<LobbyPlayer> = room.players.values().cloned().collect();
-        info!("📤 Sending initial lobby state with {} players", players.len());
-        let message = ServerMessage::LobbyState { players };
-        let _ = tx.send(message);
-    }
+    // Send the greeting and initial lobby state directly to this GUI connection
+    let _ = tx.send(hello(&state).await);
+    let _ = tx.send(lobby_state(&state).await);
 
     // Handle incoming messages and events
     let mut event_receiver = state.event_sender.subscribe();
@@ -508,9 +1579,19 @@ async fn handle_gui_connection(socket: WebSocket, state: AppState) {
             // Handle WebSocket messages
             msg = ws_receiver.next() => {
                 match msg {
-                    Some(Ok(Message::Text(text))) => {
-                        if let Err(e) = handle_gui_message(text, &state, &tx).await {
+                    Some(Ok(frame @ (Message::Text(_) | Message::Binary(_)))) => {
+                        let result = match decode_message(frame, encoding) {
+                            Ok(message) => handle_gui_message(message, &state, &tx, &mut updates).await,
+                            Err(e) => Err(e),
+                        };
+                        if let Err(e) = result {
                             error!("Error handling GUI message: {}", e);
+                            if let GameError::IncompatibleProtocol { .. } = e {
+                                let _ = tx.send(ServerMessage::Error {
+                                    message: e.to_string(),
+                                });
+                                break;
+                            }
                         }
                     }
                     Some(Ok(Message::Close(_))) | None => {
@@ -527,79 +1608,194 @@ async fn handle_gui_connection(socket: WebSocket, state: AppState) {
 
             // Handle game events
             Ok(event) = event_receiver.recv() => {
-                match event {
-                    GameEvent::PlayerJoined(_, _) | GameEvent::PlayerLeft(_) => {
-                        let room = state.game_room.read().await;
-                        let players: Vec<LobbyPlayer> = room.players.values().cloned().collect();
-                        info!("👥 Lobby updated: {} players", players.len());
-                        let message = ServerMessage::LobbyState { players };
-                        let _ = tx.send(message);
-                    }
-                    GameEvent::GameStarted => {
-                        info!("🚀 Game started! Sending initial game state to GUI");
-                        let engine = state.game_engine.read().await;
-                        let _ = tx.send(ServerMessage::GameUpdate {
-                            game_state: engine.state.clone(),
-                        });
+                if let GameEvent::RoomClosed = event {
+                    info!("Room {} closed - disconnecting GUI", state.id);
+                    let _ = tx.send(ServerMessage::Error {
+                        message: "Room closed".to_string(),
+                    });
+                    break;
+                }
+
+                if let Some(message) = spectator_update(&state, &event, &mut updates).await {
+                    let _ = tx.send(message);
+                }
+            }
+        }
+    }
+}
+
+/// Handle a read-only spectator WebSocket connection
+///
+/// Spectators receive the same lobby and game updates as the GUI, but
+/// anything they send other than `Resync` is ignored.
+async fn handle_spectator_connection(
+    socket: WebSocket,
+    state: RoomState,
+    encoding: Encoding,
+    mut updates: UpdateStream,
+) {
+    info!("👀 Spectator connected to room {}", state.id);
+
+    let (ws_sender, mut ws_receiver) = socket.split();
+    let (tx, rx) = mpsc::unbounded_channel::<ServerMessage>();
+
+    // Spawn task to handle outgoing messages
+    spawn_writer(ws_sender, rx, encoding);
+
+    let _ = tx.send(hello(&state).await);
+    let _ = tx.send(lobby_state(&state).await);
+
+    let mut event_receiver = state.event_sender.subscribe();
+    loop {
+        tokio::select! {
+            msg = ws_receiver.next() => {
+                match msg {
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => {
+                        info!("Spectator left room {}", state.id);
+                        break;
                     }
-                    GameEvent::GameTick => {
-                        let engine = state.game_engine.read().await;
-                        if engine.state.tick % 10 == 0 {
-                            info!("⏱️  Game tick {}", engine.state.tick);
+                    Some(Ok(frame @ (Message::Text(_) | Message::Binary(_)))) => {
+                        // Spectators cannot control anything, but may shake hands
+                        // and catch up
+                        match decode_message(frame, encoding) {
+                            Ok(ClientMessage::Hello { protocol_version, min_protocol_version, features }) => {
+                                match handshake::welcome(protocol_version, min_protocol_version, &features, &mut updates) {
+                                    Ok(welcome) => {
+                                        let _ = tx.send(welcome);
+                                    }
+                                    Err(e) => {
+                                        let _ = tx.send(ServerMessage::Error {
+                                            message: e.to_string(),
+                                        });
+                                        break;
+                                    }
+                                }
+                            }
+                            Ok(ClientMessage::Resync) => {
+                                if let Some(message) = resync(&state, &mut updates).await {
+                                    let _ = tx.send(message);
+                                }
+                            }
+                            _ => {}
                         }
-                        let _ = tx.send(ServerMessage::GameUpdate {
-                            game_state: engine.state.clone(),
-                        });
-                    }
-                    GameEvent::GameEnded(winner_id) => {
-                        let room = state.game_room.read().await;
-                        let winner = winner_id.and_then(|id| room.players.get(&id).cloned());
-                        let engine = state.game_engine.read().await;
-
-                        let _ = tx.send(ServerMessage::GameEnded {
-                            winner,
-                            final_state: engine.state.clone(),
-                        });
                     }
+                    Some(Ok(_)) => {}
+                }
+            }
+
+            Ok(event) = event_receiver.recv() => {
+                if let GameEvent::RoomClosed = event {
+                    break;
+                }
+
+                if let Some(message) = spectator_update(&state, &event, &mut updates).await {
+                    let _ = tx.send(message);
                 }
             }
         }
     }
 }
 
-/// Handle player messages
-async fn handle_player_message(text: String, player_id: Uuid, state: &AppState) -> GameResult<()> {
-    let message: ClientMessage = serde_json::from_str(&text)?;
+/// Current lobby of a room
+async fn lobby_state(state: &RoomState) -> ServerMessage {
+    let room = state.game_room.read().await;
+    let players: Vec<LobbyPlayer> = room.players.values().cloned().collect();
+    info!("📤 Sending lobby state with {} players", players.len());
+    ServerMessage::LobbyState {
+        players,
+        phase: room.phase,
+    }
+}
 
+/// Greeting for a new connection to a room
+async fn hello(state: &RoomState) -> ServerMessage {
+    handshake::hello(&state.game_room.read().await.config)
+}
+
+/// Full update for a connection that lost track of the running game
+async fn resync(state: &RoomState, updates: &mut UpdateStream) -> Option<ServerMessage> {
+    let room = state.game_room.read().await;
+    let engine = state.game_engine.read().await;
+    (room.phase == RoomPhase::Running).then(|| updates.keyframe(&engine.state, room.phase))
+}
+
+/// Message showing a room event to the GUI and spectators, if it is visible
+async fn spectator_update(
+    state: &RoomState,
+    event: &GameEvent,
+    updates: &mut UpdateStream,
+) -> Option<ServerMessage> {
+    match event {
+        GameEvent::PlayerJoined(_, _) | GameEvent::PlayerLeft(_) => Some(lobby_state(state).await),
+        GameEvent::GameStarted => {
+            info!("🚀 Game started! Sending initial game state to viewers");
+            let room = state.game_room.read().await;
+            let engine = state.game_engine.read().await;
+            Some(updates.keyframe(&engine.state, room.phase))
+        }
+        GameEvent::GameTick => {
+            let room = state.game_room.read().await;
+            let engine = state.game_engine.read().await;
+            if engine.state.tick % 10 == 0 {
+                info!("⏱️  Game tick {}", engine.state.tick);
+            }
+            Some(updates.update(&engine.state, room.phase))
+        }
+        GameEvent::GameStarting(starts_in_ms) => Some(ServerMessage::GameStarting {
+            starts_in_ms: *starts_in_ms,
+        }),
+        GameEvent::CountdownCancelled => Some(ServerMessage::CountdownCancelled),
+        GameEvent::PhaseChanged(phase) => Some(ServerMessage::PhaseChanged { phase: *phase }),
+        GameEvent::GamePaused => Some(ServerMessage::GamePaused),
+        GameEvent::GameResumed => Some(ServerMessage::GameResumed),
+        GameEvent::GameEnded(outcome) => Some(outcome.message()),
+        GameEvent::GameSaved(_) | GameEvent::RoomClosed => None,
+    }
+}
+
+/// Handle player messages
+async fn handle_player_message(
+    message: ClientMessage,
+    player_id: Uuid,
+    state: &RoomState,
+    updates: &mut UpdateStream,
+) -> GameResult<()> {
     match message {
+        ClientMessage::Hello {
+            protocol_version,
+            min_protocol_version,
+            features,
+        } => {
+            let welcome =
+                handshake::welcome(protocol_version, min_protocol_version, &features, updates)?;
+            if let Some(connection) = state.connections.read().await.get(&player_id) {
+                let _ = connection.sender.send(welcome);
+            }
+        }
         ClientMessage::JoinLobby { player_name } => {
-            // Add or update player in the game room
-            let mut room = state.game_room.write().await;
-            match room.add_player(player_id, player_name.clone()) {
-                Ok(_) => {
-                    // Player successfully added or updated
-                }
-                Err(error) => {
-                    // Send error
-                    if let Some(connection) = state.connections.read().await.get(&player_id) {
-                        let _ = connection.sender.send(ServerMessage::Error {
-                            message: format!("Error {}", error),
-                        });
-                    }
-                }
+            // Players are seated when they connect; clients that name
+            // themselves afterwards are renamed if they may take the name
+            if state.rename_player(player_id, player_name).await? {
+                broadcast_lobby_state(state).await;
             }
-            drop(room);
-
-            // Broadcast updated lobby state to all connections (only once)
-            broadcast_lobby_state(state).await;
         }
-        ClientMessage::SubmitMove { direction } => {
-            let mut room = state.game_room.write().await;
-            room.pending_moves.insert(player_id, direction);
+        ClientMessage::SubmitMove { direction, tick } => {
+            state
+                .game_room
+                .write()
+                .await
+                .submit_move(player_id, direction, tick)?;
             info!("🎮 Player {} submitted move: {:?}", player_id, direction);
 
-            // Note: We don't send MovesSubmitted event anymore, 
-            // the game loop uses polling to check for all moves
+            // Wake the game loop so it can check whether everyone has moved
+            state.moves_submitted.notify_one();
+        }
+        ClientMessage::Resync => {
+            if let Some(message) = resync(state, updates).await {
+                if let Some(connection) = state.connections.read().await.get(&player_id) {
+                    let _ = connection.sender.send(message);
+                }
+            }
         }
         ClientMessage::Ping => {
             if let Some(connection) = state.connections.read().await.get(&player_id) {
@@ -618,41 +1814,41 @@ async fn handle_player_message(text: String, player_id: Uuid, state: &AppState)
 }
 
 /// Handle GUI messages
-async fn handle_gui_message(text: String, state: &AppState, tx: &mpsc::UnboundedSender<ServerMessage>) -> GameResult<()> {
-    let message: ClientMessage = serde_json::from_str(&text)?;
-
+async fn handle_gui_message(
+    message: ClientMessage,
+    state: &RoomState,
+    tx: &mpsc::UnboundedSender<ServerMessage>,
+    updates: &mut UpdateStream,
+) -> GameResult<()> {
     match message {
-        ClientMessage::StartGame => {
-            let room = state.game_room.read().await;
-            info!("🎮 GUI requested game start. Current players: {}", room.players.len());
-            
-            // Check if we have enough players to start (players are ready by default)
-            if room.players.len() >= MIN_PLAYERS {
-                drop(room);
-                
-                let room = state.game_room.read().await;
-                // Initialize game engine
-                {
-                    let mut engine = state.game_engine.write().await;
-                    info!("🎯 Initializing game with {} players", room.players.len());
-                    engine.initialize_game(&room.players)?;
-                    info!("🐍 Game engine initialized successfully");
-                }
+        ClientMessage::Hello {
+            protocol_version,
+            min_protocol_version,
+            features,
+        } => {
+            let welcome =
+                handshake::welcome(protocol_version, min_protocol_version, &features, updates)?;
+            let _ = tx.send(welcome);
+        }
+        ClientMessage::StartGame { seed } => {
+            info!("🎮 GUI requested game start in room {}", state.id);
 
-                let _ = state.event_sender.send(GameEvent::GameStarted);
-                info!("📡 GameStarted event sent");
-            } else {
-                let error_msg = format!("Need at least {} players to start (current: {})", MIN_PLAYERS, room.players.len());
-                info!("❌ {}", error_msg);
+            if let Err(e) = state.start_game(seed).await {
+                info!("❌ {}", e);
                 let _ = tx.send(ServerMessage::Error {
-                    message: error_msg,
+                    message: e.to_string(),
                 });
             }
         }
+        ClientMessage::Resync => {
+            if let Some(message) = resync(state, updates).await {
+                let _ = tx.send(message);
+            }
+        }
         ClientMessage::JoinLobby { .. } => {
             // GUI should not be able to add players - only real clients can join
             let _ = tx.send(ServerMessage::Error {
-                message: "GUI cannot add players directly. Use bot.py or other clients to join.".to_string(),
+                message: "GUI cannot add players directly. Connect a client to /lobby or add a house bot with POST /rooms/{id}/bots.".to_string(),
             });
         }
         _ => {
@@ -666,11 +1862,14 @@ async fn handle_gui_message(text: String, state: &AppState, tx: &mpsc::Unbounded
 }
 
 /// Broadcast lobby state to all connections including GUI
-async fn broadcast_lobby_state(state: &AppState) {
+async fn broadcast_lobby_state(state: &RoomState) {
     let room = state.game_room.read().await;
     let players: Vec<LobbyPlayer> = room.players.values().cloned().collect();
 
-    let message = ServerMessage::LobbyState { players };
+    let message = ServerMessage::LobbyState {
+        players,
+        phase: room.phase,
+    };
 
     // Send to all player connections
     let connections = state.connections.read().await;
@@ -679,98 +1878,191 @@ async fn broadcast_lobby_state(state: &AppState) {
     }
 }
 
-/// Main game loop that processes ticks
-async fn game_loop(state: AppState) {
-    let mut event_receiver = state.event_sender.subscribe();
-    
-    info!("Game loop started - waiting for game events");
+/// Game loop of a single room that processes ticks
+///
+/// The receiver is subscribed by the caller before the task is spawned, so
+/// a game started right after the room is created is not missed.
+pub(crate) async fn game_loop(
+    state: RoomState,
+    mut event_receiver: broadcast::Receiver<GameEvent>,
+) {
+    info!("Game loop for room {} started - waiting for game events", state.id);
 
     loop {
         if let Ok(event) = event_receiver.recv().await {
             match event {
                 GameEvent::GameStarted => {
                     info!("🚀 Game started - beginning tick processing");
-                    
+
+                    let mut paused = state.paused.subscribe();
+                    // A game aborted and replaced while this loop was asleep is
+                    // left to the next GameStarted, which plays the new one. The
+                    // current game was taken already if it started before this
+                    // GameStarted was heard.
+                    let (game_number, mut recorder) = {
+                        let mut room = state.game_room.write().await;
+                        match room.recorder.take() {
+                            Some(recorder) => (room.game_number, recorder),
+                            None => continue,
+                        }
+                    };
+
                     // Run the game loop
                     loop {
                         let tick_start_time = tokio::time::Instant::now();
-                        
-                        // Check if game is still running
-                        let is_running = {
+
+                        // Moves are open for the next tick until its deadline
+                        let (tick, deadline, min_tick_duration) = {
+                            let room = state.game_room.read().await;
                             let engine = state.game_engine.read().await;
-                            engine.state.is_running
+                            if !engine.state.is_running || room.game_number != game_number {
+                                break;
+                            }
+                            match (room.move_tick, room.move_deadline) {
+                                (Some(tick), Some(deadline)) => (
+                                    tick,
+                                    deadline,
+                                    Duration::from_millis(engine.config().game_tick_duration_ms),
+                                ),
+                                _ => break,
+                            }
                         };
-                        
-                        if !is_running {
-                            break;
+
+                        // House players answer straight away
+                        let house_moves = state.house_moves().await;
+                        if !house_moves.is_empty() {
+                            let mut room = state.game_room.write().await;
+                            if room.game_number != game_number {
+                                break;
+                            }
+                            room.pending_moves.extend(house_moves);
                         }
-                        
-                        info!("⏳ Waiting for player moves (5 second timeout)...");
-                        
-                        // Wait for moves with 5-second timeout
-                        let moves = loop {
-                            // Check if all moves are submitted
-                            let all_submitted = {
+
+                        info!(
+                            "⏳ Waiting for player moves for tick {} ({}ms left)...",
+                            tick,
+                            deadline.saturating_duration_since(tick_start_time).as_millis()
+                        );
+
+                        // Wait until everyone has moved or the deadline passes,
+                        // woken up by each submitted move
+                        loop {
+                            let submitted = state.moves_submitted.notified();
+
+                            // A paused game holds the tick until it is resumed
+                            if *paused.borrow_and_update() {
+                                info!("⏸️ Game paused - waiting to be resumed");
+                                let _ = paused.wait_for(|paused| !paused).await;
+                                continue;
+                            }
+
+                            // Resuming moves the deadline, so it is read again every time
+                            let (all_submitted, deadline) = {
                                 let room = state.game_room.read().await;
                                 let engine = state.game_engine.read().await;
-                                room.all_moves_submitted(&engine.state)
+                                if !engine.state.is_running || room.game_number != game_number {
+                                    break;
+                                }
+                                (room.all_moves_submitted(&engine.state), room.move_deadline.unwrap_or(deadline))
                             };
-                            
                             if all_submitted {
                                 info!("✅ All moves submitted");
-                                let mut room = state.game_room.write().await;
-                                let moves = room.pending_moves.clone();
-                                room.pending_moves.clear();
-                                break moves;
+                                break;
+                            }
+
+                            tokio::select! {
+                                _ = submitted => {}
+                                _ = paused.changed() => {}
+                                _ = tokio::time::sleep_until(deadline) => {
+                                    info!("⏰ Move deadline passed - processing with available moves");
+                                    break;
+                                }
                             }
-                            
-                            // Check for timeout
-                            if tick_start_time.elapsed() >= Duration::from_millis(MOVE_TIMEOUT_MS) {
-                                info!("⏰ Move timeout - processing with available moves");
-                                let mut room = state.game_room.write().await;
-                                let moves = room.pending_moves.clone();
-                                room.pending_moves.clear();
-                                break moves;
+                        }
+                        let moves = {
+                            let mut room = state.game_room.write().await;
+                            if room.game_number != game_number {
+                                break;
                             }
-                            
-                            // Wait a bit before checking again
-                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            room.close_moves()
                         };
-                        
-                        // Ensure minimum 200ms delay for UI visibility
+
+                        // Ensure minimum tick duration for UI visibility
                         let elapsed = tick_start_time.elapsed();
-                        if elapsed < Duration::from_millis(GAME_TICK_DURATION_MS) {
-                            let remaining = Duration::from_millis(GAME_TICK_DURATION_MS) - elapsed;
+                        if elapsed < min_tick_duration {
+                            let remaining = min_tick_duration - elapsed;
                             info!("⏱️ Waiting {}ms for minimum tick duration", remaining.as_millis());
                             tokio::time::sleep(remaining).await;
                         }
-                        
+
                         // Log submitted moves
                         info!("🎮 Processing tick with {} moves submitted", moves.len());
                         for (player_id, direction) in &moves {
                             info!("  - Player {}: {:?}", player_id, direction);
                         }
-                        
+
                         // Process the game tick
-                        {
+                        let (next_tick, move_timeout) = {
+                            // Holding the engine keeps a new game from starting
+                            // once the room was checked
+                            let room = state.game_room.read().await;
                             let mut engine = state.game_engine.write().await;
-                            if let Err(e) = engine.process_tick(moves) {
-                                error!("❌ Error processing game tick: {}", e);
+                            let current = room.game_number == game_number;
+                            drop(room);
+                            if !engine.state.is_running || !current {
+                                info!("🛑 Game aborted - stopping tick processing");
                                 break;
                             }
-                            
+                            match engine.process_tick(moves.clone()) {
+                                Ok(summary) => recorder.record_tick(&moves, summary),
+                                Err(e) => {
+                                    error!("❌ Error processing game tick: {}", e);
+                                    break;
+                                }
+                            }
+
                             // Check if game ended
                             if !engine.state.is_running {
-                                let winner_id = engine.state.winner;
-                                info!("🏁 Game ended! Winner: {:?}", winner_id);
-                                let _ = state.event_sender.send(GameEvent::GameEnded(winner_id));
+                                // Release the engine before writing the replay and match,
+                                // so connections and HTTP requests are not held up by file I/O
+                                let final_state = engine.state.clone();
+                                drop(engine);
+                                info!("🏁 Game ended! Winner: {:?}", final_state.winner);
+                                let outcome = GameOutcome::new(&*state.game_room.read().await, final_state);
+                                if let Some(match_id) = save_game(&state, recorder, &outcome.final_state).await {
+                                    let _ = state.event_sender.send(GameEvent::GameSaved(match_id));
+                                }
+                                // Whoever hears of the end may start the next game right away
+                                state.end_game().await;
+                                let _ = state.event_sender.send(GameEvent::GameEnded(Arc::new(outcome)));
+                                break;
+                            }
+
+                            (
+                                engine.state.tick,
+                                Duration::from_millis(engine.config().move_timeout_ms),
+                            )
+                        };
+
+                        // Open moves for the next tick before anyone is asked for one,
+                        // unless the game was aborted in the meantime
+                        {
+                            let mut room = state.game_room.write().await;
+                            if !state.game_engine.read().await.state.is_running
+                                || room.game_number != game_number
+                            {
                                 break;
                             }
+                            room.open_moves(next_tick, tokio::time::Instant::now() + move_timeout);
                         }
-                        
+
                         // Send game update
                         let _ = state.event_sender.send(GameEvent::GameTick);
                     }
+
+                    // Finished and aborted games have ended already, but one
+                    // stopped by an error has not
+                    state.end_game().await;
                 }
                 GameEvent::GameEnded(_) => {
                     info!("🏁 Game ended - stopping game loop");
@@ -784,23 +2076,164 @@ async fn game_loop(state: AppState) {
     }
 }
 
-/// Start the server
-pub async fn start_server() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "backend=debug,tower_http=debug".into()),
-        )
-        .init();
+/// Write the replay of a finished game and add it to the match history
+///
+/// Returns the id of the match record, if one was added.
+async fn save_game(
+    state: &RoomState,
+    recorder: ReplayRecorder,
+    final_state: &GameState,
+) -> Option<Uuid> {
+    let game_id = recorder.game_id();
+    let records = recorder.finish(final_state);
+    let players = state.matches.ladder().players();
+    let summary = MatchRecord::from_replay(&records, final_state, players);
+    let replays = state.replays.clone();
+    let matches = state.matches.clone();
+
+    let saved = tokio::task::spawn_blocking(move || {
+        let path = replays.save(&records)?;
+        let match_id = match summary {
+            Some(summary) => {
+                let id = summary.id;
+                matches.add(summary)?;
+                Some(id)
+            }
+            None => None,
+        };
+        Ok::<_, std::io::Error>((path, match_id))
+    });
+    match saved.await {
+        Ok(Ok((path, match_id))) => {
+            info!("💾 Replay {} saved to {}", game_id, path.display());
+            match_id
+        }
+        Ok(Err(e)) => {
+            error!("❌ Failed to save game {}: {}", game_id, e);
+            None
+        }
+        Err(e) => {
+            error!("❌ Replay writer for {} panicked: {}", game_id, e);
+            None
+        }
+    }
+}
 
-    let app = create_app();
-    let addr = SocketAddr::from(([0, 0, 0, 0], SERVER_PORT));
+/// Start the server
+pub async fn start_server(config: Config) -> Result<(), Box<dyn std::error::Error>> {
+    let addr = (config.server.host.clone(), config.server.port);
+    let app = create_app(config)?;
 
-    info!("Starting server on {}", addr);
+    info!("Starting server on {}:{}", addr.0, addr.1);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
     axum::serve(listener, app).await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::Service;
+
+    #[tokio::test]
+    async fn test_room_management_needs_the_admin_token() {
+        let dir = TempDir::new("server");
+        let mut config = Config::default();
+        config.server.replay_dir = dir.join("replays");
+        config.server.match_history_file = dir.join("matches.jsonl");
+        config.server.players_file = dir.join("players.json");
+        config.server.admin_token = Some("secret".to_string());
+        let mut app = create_app(config).unwrap();
+
+        let request = |method: &str, uri: &str, body: &str, token: Option<&str>| {
+            let mut request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            request.body(Body::from(body.to_string())).unwrap()
+        };
+        let bot = format!("/rooms/default/bots/{}", Uuid::new_v4());
+        let routes = [
+            ("POST", "/rooms", r#"{"id": "experiment-1"}"#),
+            ("DELETE", "/rooms/default", ""),
+            ("POST", "/rooms/default/bots", r#"{"bot": "greedy"}"#),
+            ("DELETE", bot.as_str(), ""),
+        ];
+
+        for (method, uri, body) in routes {
+            for token in [None, Some("wrong")] {
+                let response = app
+                    .call(request(method, uri, body, token))
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{} {}", method, uri);
+            }
+        }
+
+        let response = app
+            .call(request("POST", "/rooms", routes[0].2, Some("secret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_join_lobby_renames_only_unregistered_players() {
+        let dir = TempDir::new("server");
+        let players = PlayerRegistry::load(dir.join("players.json")).unwrap();
+        let carol = players.register("carol").unwrap();
+        let room = RoomState::new(
+            "default".to_string(),
+            GameConfig::default(),
+            ReplayStore::new(dir.join("replays")),
+            MatchStore::load(dir.join("matches.jsonl"), players).unwrap(),
+        );
+        let anonymous = Uuid::new_v4();
+        room.join_player(anonymous, "Player_1".to_string())
+            .await
+            .unwrap();
+        room.join_player(carol.id, carol.name.clone()).await.unwrap();
+        let mut updates = UpdateStream::new(false);
+
+        let join = |name: &str| ClientMessage::JoinLobby {
+            player_name: name.to_string(),
+        };
+
+        // Connecting without a name and then sending one renames the player
+        handle_player_message(join("alice"), anonymous, &room, &mut updates)
+            .await
+            .unwrap();
+        assert_eq!(room.game_room.read().await.players[&anonymous].name, "alice");
+
+        // Taken and empty names are refused
+        let renamed = handle_player_message(join("carol"), anonymous, &room, &mut updates).await;
+        assert!(matches!(renamed, Err(GameError::NameTaken(_))));
+        let renamed = handle_player_message(join(" carol "), anonymous, &room, &mut updates).await;
+        assert!(matches!(renamed, Err(GameError::NameTaken(_))));
+        let renamed = handle_player_message(join(" "), anonymous, &room, &mut updates).await;
+        assert!(matches!(renamed, Err(GameError::InvalidRequest(_))));
+        assert_eq!(room.game_room.read().await.players[&anonymous].name, "alice");
+        handle_player_message(join("bob"), Uuid::new_v4(), &room, &mut updates)
+            .await
+            .unwrap();
+        assert_eq!(room.game_room.read().await.players.len(), 2);
+
+        // Registered players keep their name
+        let renamed = handle_player_message(join("mallory"), carol.id, &room, &mut updates).await;
+        assert!(matches!(renamed, Err(GameError::InvalidRequest(_))));
+        assert_eq!(room.game_room.read().await.players[&carol.id].name, "carol");
+
+        // Nobody is renamed once the game started
+        room.start_game(Some(1)).await.unwrap();
+        let renamed = handle_player_message(join("dave"), anonymous, &room, &mut updates).await;
+        assert!(matches!(renamed, Err(GameError::InvalidRequest(_))));
+    }
+}
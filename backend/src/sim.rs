@@ -0,0 +1,240 @@
+//! Headless match simulation between local bots
+//!
+//! Games are driven straight through the `GameEngine`: there is no tick
+//! duration, move timeout or network in the way, so thousands of games can
+//! be played in seconds to compare bot strategies.
+
+use crate::bots::Bot;
+use crate::config::GameConfig;
+use crate::game::GameEngine;
+use crate::types::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Settings for a batch of simulated games
+#[derive(Debug, Clone)]
+pub struct SimOptions {
+    /// Rules every game is played with
+    pub config: GameConfig,
+    /// Number of games to play
+    pub games: usize,
+    /// Seed of the first game; game `n` uses `seed + n`
+    pub seed: u64,
+    /// Ticks after which an unfinished game counts as a draw
+    pub max_ticks: u64,
+    /// Number of worker threads
+    pub jobs: usize,
+}
+
+/// Result of a single simulated game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GameOutcome {
+    /// Seed the game was played with
+    pub seed: u64,
+    /// Number of ticks played
+    pub ticks: u64,
+    /// Index of the winning bot, `None` for a draw
+    pub winner: Option<usize>,
+}
+
+/// Aggregated results of a batch of games
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SimReport {
+    /// Bot names in seat order
+    pub bots: Vec<String>,
+    pub games: usize,
+    /// Games won by each bot, in seat order
+    pub wins: Vec<usize>,
+    pub draws: usize,
+    pub total_ticks: u64,
+}
+
+impl SimReport {
+    /// Fraction of games won by the bot in the given seat
+    pub fn win_rate(&self, seat: usize) -> f64 {
+        self.rate(self.wins.get(seat).copied().unwrap_or_default())
+    }
+
+    /// Fraction of games without a winner
+    pub fn draw_rate(&self) -> f64 {
+        self.rate(self.draws)
+    }
+
+    /// Mean game length in ticks
+    pub fn average_ticks(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.total_ticks as f64 / self.games as f64
+        }
+    }
+
+    fn rate(&self, count: usize) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            count as f64 / self.games as f64
+        }
+    }
+
+    fn add(&mut self, outcome: &GameOutcome) {
+        self.games += 1;
+        self.total_ticks += outcome.ticks;
+        match outcome.winner {
+            Some(seat) => self.wins[seat] += 1,
+            None => self.draws += 1,
+        }
+    }
+}
+
+/// Play one game between the given bots
+///
+/// Bot `i` controls the snake of player `i`, which joins with color index
+/// `i`, so the same seed and bots always produce the same game.
+pub fn run_game(
+    config: &GameConfig,
+    bots: &mut [Box<dyn Bot>],
+    seed: u64,
+    max_ticks: u64,
+) -> GameResult<GameOutcome> {
+    let seats: Vec<Uuid> = (0..bots.len())
+        .map(|i| Uuid::from_u128(i as u128 + 1))
+        .collect();
+    let players: HashMap<Uuid, LobbyPlayer> = bots
+        .iter()
+        .zip(&seats)
+        .enumerate()
+        .map(|(i, (bot, &id))| {
+            let player = LobbyPlayer {
+                id,
+                name: bot.name().to_string(),
+                color_index: i,
+                is_ready: true,
+            };
+            (id, player)
+        })
+        .collect();
+
+    let mut engine = GameEngine::new(config.clone());
+    engine.initialize_game(&players, Some(seed))?;
+
+    while engine.state.is_running && engine.state.tick < max_ticks {
+        let mut moves = HashMap::new();
+        for (bot, &id) in bots.iter_mut().zip(&seats) {
+            if engine.is_snake_alive(&id) {
+                moves.insert(id, bot.next_move(&engine.state, id));
+            }
+        }
+        engine.process_tick(moves)?;
+    }
+
+    let winner = engine
+        .state
+        .winner
+        .and_then(|id| seats.iter().position(|&seat| seat == id));
+
+    Ok(GameOutcome {
+        seed,
+        ticks: engine.state.tick,
+        winner,
+    })
+}
+
+/// Play a batch of games, spreading them over `options.jobs` threads
+///
+/// `make_bots` is called with each game's seed and returns the bots for
+/// that game in seat order.
+pub fn run_games<F>(options: &SimOptions, make_bots: F) -> GameResult<SimReport>
+where
+    F: Fn(u64) -> Vec<Box<dyn Bot>> + Sync,
+{
+    let bots: Vec<String> = make_bots(options.seed)
+        .iter()
+        .map(|bot| bot.name().to_string())
+        .collect();
+    let jobs = options.jobs.clamp(1, options.games.max(1));
+
+    let outcomes: Vec<GameResult<Vec<GameOutcome>>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|job| {
+                let make_bots = &make_bots;
+                scope.spawn(move || {
+                    (job..options.games)
+                        .step_by(jobs)
+                        .map(|game| {
+                            let seed = options.seed.wrapping_add(game as u64);
+                            let mut bots = make_bots(seed);
+                            run_game(&options.config, &mut bots, seed, options.max_ticks)
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("simulation worker panicked"))
+            .collect()
+    });
+
+    let mut report = SimReport {
+        wins: vec![0; bots.len()],
+        bots,
+        ..Default::default()
+    };
+    for outcome in outcomes {
+        for game in outcome? {
+            report.add(&game);
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::create_bot;
+
+    fn small_config() -> GameConfig {
+        GameConfig {
+            grid_width: 15,
+            grid_height: 15,
+            winning_snake_length: 8,
+            ..Default::default()
+        }
+    }
+
+    fn random_bots(seed: u64) -> Vec<Box<dyn Bot>> {
+        (0..3)
+            .map(|i| create_bot("random", seed.wrapping_mul(31).wrapping_add(i)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_run_game_is_reproducible() {
+        let config = small_config();
+        let first = run_game(&config, &mut random_bots(9), 9, 500).unwrap();
+        let second = run_game(&config, &mut random_bots(9), 9, 500).unwrap();
+
+        assert_eq!(first, second);
+        assert!(first.ticks <= 500);
+    }
+
+    #[test]
+    fn test_report_counts_every_game() {
+        let options = SimOptions {
+            config: small_config(),
+            games: 20,
+            seed: 1,
+            max_ticks: 500,
+            jobs: 4,
+        };
+        let report = run_games(&options, random_bots).unwrap();
+
+        assert_eq!(report.games, 20);
+        assert_eq!(report.bots, vec!["random"; 3]);
+        assert_eq!(report.wins.iter().sum::<usize>() + report.draws, 20);
+    }
+}
@@ -0,0 +1,29 @@
+//! Helpers shared by the unit tests
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// A directory of its own under the system temp dir, removed with
+/// everything in it when dropped, even if the test fails
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(prefix: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("snake-{}-{}", prefix, Uuid::new_v4())))
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
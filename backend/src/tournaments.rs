@@ -0,0 +1,971 @@
+//! Tournaments of bots, played back to back in their own room
+//!
+//! A tournament seats its entrants in groups of up to `group_size` players
+//! and plays the groups' matches one after another in a room created for
+//! it, so the games can be watched like any other. Entrants are built-in
+//! bots, seated as house players for each match, or registered players, who
+//! connect to the room with their API key and wait in its lobby between
+//! their matches. Each match earns a player a point for every opponent they
+//! placed ahead of, and the standings add those points up. The format
+//! decides the groups:
+//!
+//! - Round robin keeps playing rounds until every pair of entrants has met
+//! - Swiss plays a fixed number of rounds, grouping players on equal points
+//! - Single elimination sends the better half of every group to the next
+//!   round until one player is left
+//!
+//! Tournaments are kept in memory only; their matches end up in the match
+//! history like any other game. The room goes away once the tournament is
+//! over.
+
+use crate::accounts::PlayerRegistry;
+use crate::bots::BOT_NAMES;
+use crate::config::GameConfig;
+use crate::constants::ENTRANT_WAIT_MS;
+use crate::rooms::{AddBotRequest, CreateRoomRequest, RoomId, RoomRegistry, RoomState};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::{broadcast::error::RecvError, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// How often a match checks whether its registered entrants have connected
+const ENTRANT_POLL_MS: u64 = 100;
+
+/// How a tournament groups its entrants into matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TournamentFormat {
+    RoundRobin,
+    Swiss,
+    SingleElimination,
+}
+
+/// Progress of a tournament
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TournamentStatus {
+    Running,
+    Finished,
+    /// Stopped by an admin
+    Cancelled,
+    /// Stopped because a match could not be played, see `error`
+    Failed,
+}
+
+/// Request body for creating a tournament
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateTournamentRequest {
+    pub format: TournamentFormat,
+    /// Built-in bots and registered players taking part, in seeding order
+    pub entrants: Vec<EntrantRequest>,
+    /// Players per match; defaults to the rules' `max_players`
+    pub group_size: Option<usize>,
+    /// Number of Swiss rounds; defaults to the base-2 logarithm of the
+    /// number of entrants, rounded up
+    pub rounds: Option<usize>,
+    /// Rules for every match; the server defaults are used if omitted
+    pub config: Option<GameConfig>,
+    /// Seed of the first match, the next match uses the seed plus one and so
+    /// on; random if omitted
+    pub seed: Option<u64>,
+}
+
+/// An entrant of a new tournament
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum EntrantRequest {
+    /// A registered player, e.g. `{"player": "<id>"}`, who plays every match
+    /// over their own connection to the tournament's room
+    Player {
+        /// Id of the registered player
+        player: Uuid,
+    },
+    /// A built-in bot, e.g. `{"bot": "greedy"}`, seated as a house player
+    Bot(AddBotRequest),
+}
+
+/// A player taking part in a tournament
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Entrant {
+    /// Player id the entrant plays every match under
+    pub id: Uuid,
+    pub name: String,
+    /// Built-in strategy the entrant plays with, or `null` for a registered
+    /// player
+    pub bot: Option<String>,
+}
+
+/// A match between one group of entrants
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TournamentMatch {
+    /// Ids of the entrants playing
+    pub players: Vec<Uuid>,
+    pub seed: u64,
+    /// Id of the match in the match history, once played
+    pub match_id: Option<Uuid>,
+    /// Placements of the players, empty until the match is played
+    pub ranking: Vec<Placement>,
+}
+
+/// One round of a tournament, in which every remaining entrant plays once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TournamentRound {
+    /// Round number, starting at 1
+    pub number: usize,
+    pub matches: Vec<TournamentMatch>,
+    /// Entrants left without opponents, who sit the round out (and advance,
+    /// in single elimination)
+    pub byes: Vec<Uuid>,
+}
+
+/// An entrant's position in the tournament
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Standing {
+    /// Position in the standings, starting at 1
+    pub rank: usize,
+    pub player_id: Uuid,
+    pub name: String,
+    /// One point for every opponent placed behind in a match
+    pub points: u32,
+    pub matches: u32,
+    pub wins: u32,
+    /// Round the entrant was knocked out in, in single elimination
+    pub eliminated_in: Option<usize>,
+}
+
+/// A tournament and everything played in it so far
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Tournament {
+    pub id: Uuid,
+    /// Room the matches are played in, for watching them and for registered
+    /// entrants to connect to; removed once the tournament is over
+    pub room: RoomId,
+    pub format: TournamentFormat,
+    pub status: TournamentStatus,
+    pub group_size: usize,
+    /// Number of rounds to play, when known in advance (Swiss)
+    pub planned_rounds: Option<usize>,
+    /// Entrants in seeding order
+    pub entrants: Vec<Entrant>,
+    /// Rounds played or being played
+    pub rounds: Vec<TournamentRound>,
+    /// Entrants ordered by their standing, best first
+    pub standings: Vec<Standing>,
+    /// Winner of the tournament, once finished
+    pub champion: Option<Uuid>,
+    /// Why the tournament failed, if it did
+    pub error: Option<String>,
+    /// Seed of the first match
+    pub seed: u64,
+}
+
+/// Overview of a tournament
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TournamentSummary {
+    pub id: Uuid,
+    pub room: RoomId,
+    pub format: TournamentFormat,
+    pub status: TournamentStatus,
+    pub entrants: usize,
+    /// Number of rounds started so far
+    pub rounds: usize,
+    /// Name of the entrant at the top of the standings
+    pub leader: Option<String>,
+}
+
+impl Tournament {
+    fn new(
+        id: Uuid,
+        room: RoomId,
+        format: TournamentFormat,
+        group_size: usize,
+        planned_rounds: Option<usize>,
+        entrants: Vec<Entrant>,
+        seed: u64,
+    ) -> Self {
+        let standings = entrants
+            .iter()
+            .enumerate()
+            .map(|(index, entrant)| Standing {
+                rank: index + 1,
+                player_id: entrant.id,
+                name: entrant.name.clone(),
+                points: 0,
+                matches: 0,
+                wins: 0,
+                eliminated_in: None,
+            })
+            .collect();
+
+        Self {
+            id,
+            room,
+            format,
+            status: TournamentStatus::Running,
+            group_size,
+            planned_rounds,
+            entrants,
+            rounds: Vec::new(),
+            standings,
+            champion: None,
+            error: None,
+            seed,
+        }
+    }
+
+    pub fn summary(&self) -> TournamentSummary {
+        TournamentSummary {
+            id: self.id,
+            room: self.room.clone(),
+            format: self.format,
+            status: self.status,
+            entrants: self.entrants.len(),
+            rounds: self.rounds.len(),
+            leader: self.standings.first().map(|s| s.name.clone()),
+        }
+    }
+
+    /// Schedule the next round, or `None` once the tournament is decided
+    fn start_round(&mut self) -> Option<&TournamentRound> {
+        let groups = match self.format {
+            TournamentFormat::RoundRobin => self.round_robin_groups()?,
+            TournamentFormat::Swiss => {
+                if self.rounds.len() >= self.planned_rounds.unwrap_or(1) {
+                    return None;
+                }
+                let order: Vec<Uuid> = self.standings.iter().map(|s| s.player_id).collect();
+                split_evenly(&order, self.group_size)
+            }
+            TournamentFormat::SingleElimination => {
+                let remaining: Vec<Uuid> = self
+                    .entrants
+                    .iter()
+                    .map(|entrant| entrant.id)
+                    .filter(|id| self.standing(id).eliminated_in.is_none())
+                    .collect();
+                if remaining.len() < 2 {
+                    return None;
+                }
+                split_evenly(&remaining, self.group_size)
+            }
+        };
+
+        let played: usize = self.rounds.iter().map(|r| r.matches.len()).sum();
+        let (matches, byes): (Vec<_>, Vec<_>) = groups.into_iter().partition(|g| g.len() > 1);
+        let matches = matches
+            .into_iter()
+            .enumerate()
+            .map(|(index, players)| TournamentMatch {
+                players,
+                seed: self.seed.wrapping_add((played + index) as u64),
+                match_id: None,
+                ranking: Vec::new(),
+            })
+            .collect();
+
+        self.rounds.push(TournamentRound {
+            number: self.rounds.len() + 1,
+            matches,
+            byes: byes.into_iter().flatten().collect(),
+        });
+        self.rounds.last()
+    }
+
+    /// Groups for the next round robin round, favouring entrants who have
+    /// not met yet, or `None` once every pair has met
+    fn round_robin_groups(&self) -> Option<Vec<Vec<Uuid>>> {
+        let mut met = HashSet::new();
+        for game in self.rounds.iter().flat_map(|round| &round.matches) {
+            for a in &game.players {
+                for b in &game.players {
+                    met.insert((*a, *b));
+                }
+            }
+        }
+        let unmet = |a: &Uuid, b: &Uuid| a != b && !met.contains(&(*a, *b));
+
+        let mut pool: Vec<Uuid> = self.entrants.iter().map(|entrant| entrant.id).collect();
+        if !pool.iter().any(|a| pool.iter().any(|b| unmet(a, b))) {
+            return None;
+        }
+
+        let sizes: Vec<usize> = split_evenly(&pool, self.group_size)
+            .iter()
+            .map(Vec::len)
+            .collect();
+        let mut groups = Vec::with_capacity(sizes.len());
+        for size in sizes {
+            // Start with whoever has the most opponents left to meet, then
+            // add whoever meets the most new opponents in the group
+            let mut group: Vec<Uuid> = Vec::with_capacity(size);
+            while group.len() < size {
+                let (index, _) = pool
+                    .iter()
+                    .enumerate()
+                    .map(|(index, candidate)| {
+                        let others: &[Uuid] = if group.is_empty() { &pool } else { &group };
+                        let new = others.iter().filter(|o| unmet(candidate, o)).count();
+                        (index, new)
+                    })
+                    .rev()
+                    .max_by_key(|&(_, new)| new)
+                    .expect("the pool has a player for every seat");
+                group.push(pool.remove(index));
+            }
+            groups.push(group);
+        }
+        Some(groups)
+    }
+
+    /// Award points for a played match of the current round
+    fn record_match(&mut self, index: usize, ranking: Vec<Placement>, match_id: Option<Uuid>) {
+        let round_number = self.rounds.len();
+        let Some(game) = self
+            .rounds
+            .last_mut()
+            .and_then(|round| round.matches.get_mut(index))
+        else {
+            return;
+        };
+        game.ranking = ranking.clone();
+        game.match_id = match_id;
+
+        for placement in &ranking {
+            let beaten = ranking
+                .iter()
+                .filter(|other| other.placement > placement.placement)
+                .count();
+            let standing = self.standing_mut(&placement.player_id);
+            standing.points += beaten as u32;
+            standing.matches += 1;
+            if placement.placement == 1 && beaten + 1 == ranking.len() {
+                standing.wins += 1;
+            }
+        }
+
+        if self.format == TournamentFormat::SingleElimination {
+            // Ranking is best first; ties at the cut go to the better seed
+            let advancing = (ranking.len() / 2).max(1);
+            let mut order: Vec<&Placement> = ranking.iter().collect();
+            order.sort_by_key(|p| (p.placement, self.seed_position(&p.player_id)));
+            let knocked_out: Vec<Uuid> = order[advancing..].iter().map(|p| p.player_id).collect();
+            for player_id in knocked_out {
+                self.standing_mut(&player_id).eliminated_in = Some(round_number);
+            }
+        }
+
+        self.sort_standings();
+    }
+
+    /// Wrap up the tournament once no rounds are left
+    fn finish(&mut self) {
+        self.sort_standings();
+        self.status = TournamentStatus::Finished;
+        self.champion = self.standings.first().map(|s| s.player_id);
+    }
+
+    fn sort_standings(&mut self) {
+        let seeds: HashMap<Uuid, usize> = self
+            .entrants
+            .iter()
+            .enumerate()
+            .map(|(index, entrant)| (entrant.id, index))
+            .collect();
+
+        // Knocked out later is better, and never knocked out is best
+        self.standings.sort_by_key(|s| {
+            (
+                std::cmp::Reverse(s.eliminated_in.unwrap_or(usize::MAX)),
+                std::cmp::Reverse(s.points),
+                std::cmp::Reverse(s.wins),
+                seeds[&s.player_id],
+            )
+        });
+        for (index, standing) in self.standings.iter_mut().enumerate() {
+            standing.rank = index + 1;
+        }
+    }
+
+    fn seed_position(&self, player_id: &Uuid) -> usize {
+        self.entrants
+            .iter()
+            .position(|entrant| &entrant.id == player_id)
+            .unwrap_or(usize::MAX)
+    }
+
+    fn standing(&self, player_id: &Uuid) -> &Standing {
+        self.standings
+            .iter()
+            .find(|s| &s.player_id == player_id)
+            .expect("every entrant has a standing")
+    }
+
+    fn standing_mut(&mut self, player_id: &Uuid) -> &mut Standing {
+        self.standings
+            .iter_mut()
+            .find(|s| &s.player_id == player_id)
+            .expect("every entrant has a standing")
+    }
+}
+
+/// Split players into as few groups of at most `group_size` as possible,
+/// with sizes differing by at most one, keeping their order
+fn split_evenly(players: &[Uuid], group_size: usize) -> Vec<Vec<Uuid>> {
+    if players.is_empty() {
+        return Vec::new();
+    }
+    let groups = players.len().div_ceil(group_size);
+    let (base, extra) = (players.len() / groups, players.len() % groups);
+
+    let mut rest = players;
+    (0..groups)
+        .map(|index| {
+            let (group, tail) = rest.split_at(base + usize::from(index < extra));
+            rest = tail;
+            group.to_vec()
+        })
+        .collect()
+}
+
+/// A tournament together with the task playing its matches
+struct TournamentEntry {
+    state: Arc<RwLock<Tournament>>,
+    task: JoinHandle<()>,
+}
+
+/// Registry of all tournaments on the server
+#[derive(Clone)]
+pub struct TournamentRegistry {
+    rooms: RoomRegistry,
+    /// Players who can enter tournaments themselves
+    players: PlayerRegistry,
+    tournaments: Arc<RwLock<HashMap<Uuid, TournamentEntry>>>,
+}
+
+impl TournamentRegistry {
+    pub fn new(rooms: RoomRegistry, players: PlayerRegistry) -> Self {
+        Self {
+            rooms,
+            players,
+            tournaments: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Create a tournament with its own room and start playing it
+    pub async fn create(&self, request: CreateTournamentRequest) -> GameResult<Tournament> {
+        if request.entrants.len() < 2 {
+            return Err(GameError::InvalidRequest(
+                "A tournament needs at least 2 entrants".to_string(),
+            ));
+        }
+
+        let mut config = request
+            .config
+            .unwrap_or_else(|| self.rooms.default_config().clone());
+        // Groups can come out smaller than the usual minimum, e.g. in the
+        // last rounds of an elimination, and only the tournament starts games
+        config.min_players = 1;
+        config.auto_start_countdown_ms = None;
+
+        let group_size = request.group_size.unwrap_or(config.max_players);
+        if group_size < 2 || group_size > config.max_players {
+            return Err(GameError::InvalidRequest(format!(
+                "group_size must be between 2 and max_players ({})",
+                config.max_players
+            )));
+        }
+
+        let entrants = name_entrants(request.entrants, &self.players)?;
+        // Registered entrants wait in the lobby while others play, so it
+        // has to hold everyone at once
+        config.max_players = config.max_players.max(entrants.len());
+        let planned_rounds = match request.format {
+            TournamentFormat::Swiss => Some(
+                request
+                    .rounds
+                    .unwrap_or_else(|| entrants.len().next_power_of_two().trailing_zeros() as usize)
+                    .max(1),
+            ),
+            _ => None,
+        };
+
+        let id = Uuid::new_v4();
+        let room = self
+            .rooms
+            .create(CreateRoomRequest {
+                id: Some(format!("tournament-{}", id.simple())),
+                config: Some(config),
+            })
+            .await?;
+
+        let tournament = Tournament::new(
+            id,
+            room.id.clone(),
+            request.format,
+            group_size,
+            planned_rounds,
+            entrants,
+            request.seed.unwrap_or_else(rand::random),
+        );
+        let state = Arc::new(RwLock::new(tournament.clone()));
+        let task = tokio::spawn(run_tournament(state.clone(), room, self.rooms.clone()));
+
+        self.tournaments
+            .write()
+            .await
+            .insert(id, TournamentEntry { state, task });
+        info!(
+            "🏆 Tournament {} created with {} entrants",
+            id,
+            tournament.entrants.len()
+        );
+
+        Ok(tournament)
+    }
+
+    /// Look up a tournament by id
+    pub async fn get(&self, id: Uuid) -> GameResult<Tournament> {
+        let state = self
+            .tournaments
+            .read()
+            .await
+            .get(&id)
+            .map(|entry| entry.state.clone())
+            .ok_or(GameError::TournamentNotFound(id))?;
+        let tournament = state.read().await.clone();
+        Ok(tournament)
+    }
+
+    /// Summaries of all tournaments
+    pub async fn list(&self) -> Vec<TournamentSummary> {
+        let states: Vec<Arc<RwLock<Tournament>>> = self
+            .tournaments
+            .read()
+            .await
+            .values()
+            .map(|entry| entry.state.clone())
+            .collect();
+
+        let mut summaries = Vec::with_capacity(states.len());
+        for state in states {
+            summaries.push(state.read().await.summary());
+        }
+        summaries.sort_by(|a, b| a.room.cmp(&b.room));
+        summaries
+    }
+
+    /// Stop a running tournament, aborting the match being played
+    pub async fn cancel(&self, id: Uuid) -> GameResult<()> {
+        let tournaments = self.tournaments.read().await;
+        let entry = tournaments
+            .get(&id)
+            .ok_or(GameError::TournamentNotFound(id))?;
+
+        let mut tournament = entry.state.write().await;
+        if tournament.status != TournamentStatus::Running {
+            return Err(GameError::InvalidRequest(format!(
+                "Tournament {} is no longer running",
+                id
+            )));
+        }
+        entry.task.abort();
+        tournament.status = TournamentStatus::Cancelled;
+
+        // Closing the room stops the match being played and disconnects
+        // registered entrants; it may already be gone if the tournament
+        // ended in the meantime
+        let _ = self.rooms.remove(&tournament.room).await;
+        info!("🛑 Tournament {} cancelled", id);
+
+        Ok(())
+    }
+}
+
+/// Give every entrant an id and a unique name
+///
+/// Registered players keep their own id and name; bots get a new id.
+fn name_entrants(
+    requests: Vec<EntrantRequest>,
+    players: &PlayerRegistry,
+) -> GameResult<Vec<Entrant>> {
+    let mut entrants: Vec<Entrant> = Vec::with_capacity(requests.len());
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for request in requests {
+        let entrant = match request {
+            EntrantRequest::Player { player } => {
+                let player = players
+                    .get(player)
+                    .ok_or(GameError::PlayerNotFound(player))?;
+                if entrants.iter().any(|entrant| entrant.id == player.id) {
+                    return Err(GameError::InvalidRequest(format!(
+                        "{} entered the tournament twice",
+                        player.name
+                    )));
+                }
+                Entrant {
+                    id: player.id,
+                    name: player.name,
+                    bot: None,
+                }
+            }
+            EntrantRequest::Bot(request) => {
+                if !BOT_NAMES.contains(&request.bot.as_str()) {
+                    return Err(GameError::InvalidRequest(format!(
+                        "Unknown bot '{}' (available: {})",
+                        request.bot,
+                        BOT_NAMES.join(", ")
+                    )));
+                }
+
+                let name = match request.name {
+                    Some(name) => name,
+                    None => {
+                        let count = counts.entry(request.bot.clone()).or_default();
+                        *count += 1;
+                        format!("{}-bot-{}", request.bot, count)
+                    }
+                };
+                Entrant {
+                    id: Uuid::new_v4(),
+                    name,
+                    bot: Some(request.bot),
+                }
+            }
+        };
+
+        if entrants.iter().any(|other| other.name == entrant.name) {
+            return Err(GameError::NameTaken(entrant.name));
+        }
+        entrants.push(entrant);
+    }
+    Ok(entrants)
+}
+
+/// Play a tournament and close its room once it is over
+async fn run_tournament(state: Arc<RwLock<Tournament>>, room: RoomState, rooms: RoomRegistry) {
+    play_rounds(&state, &room).await;
+    if let Err(e) = rooms.remove(&room.id).await {
+        warn!("Failed to close tournament room {}: {}", room.id, e);
+    }
+}
+
+/// Play every round of a tournament, one match after another
+async fn play_rounds(state: &RwLock<Tournament>, room: &RoomState) {
+    let registered: Vec<Uuid> = {
+        let tournament = state.read().await;
+        tournament
+            .entrants
+            .iter()
+            .filter(|entrant| entrant.bot.is_none())
+            .map(|entrant| entrant.id)
+            .collect()
+    };
+
+    loop {
+        let (number, matches) = {
+            let mut tournament = state.write().await;
+            match tournament.start_round() {
+                Some(round) => (round.number, round.matches.clone()),
+                None => {
+                    tournament.finish();
+                    info!("🏆 Tournament {} finished", tournament.id);
+                    return;
+                }
+            }
+        };
+        info!("🏆 Round {} of {} matches", number, matches.len());
+
+        for (index, game) in matches.into_iter().enumerate() {
+            let entrants: Vec<Entrant> = {
+                let tournament = state.read().await;
+                game.players
+                    .iter()
+                    .filter_map(|id| tournament.entrants.iter().find(|e| &e.id == id))
+                    .cloned()
+                    .collect()
+            };
+
+            match play_match(room, &registered, &entrants, game.seed).await {
+                Ok((ranking, match_id)) => {
+                    state.write().await.record_match(index, ranking, match_id);
+                }
+                Err(e) => {
+                    error!("❌ Tournament match in room {} failed: {}", room.id, e);
+                    let mut tournament = state.write().await;
+                    tournament.status = TournamentStatus::Failed;
+                    tournament.error = Some(e.to_string());
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Seat a group of entrants, play a game and return their placements
+/// together with the id of the match in the history
+///
+/// Everyone but the registered entrants is cleared from the lobby first.
+/// Entrants of the group who have not connected by the time the match
+/// starts forfeit it, placing behind everyone who played.
+async fn play_match(
+    room: &RoomState,
+    registered: &[Uuid],
+    entrants: &[Entrant],
+    seed: u64,
+) -> GameResult<(Vec<Placement>, Option<Uuid>)> {
+    room.reset_lobby_except(registered).await?;
+    for entrant in entrants {
+        if let Some(bot) = &entrant.bot {
+            let request = AddBotRequest {
+                bot: bot.clone(),
+                name: Some(entrant.name.clone()),
+            };
+            room.add_house_bot_as(entrant.id, request).await?;
+        }
+    }
+
+    let group: Vec<Uuid> = entrants.iter().map(|entrant| entrant.id).collect();
+    let present = wait_for_entrants(room, &group).await;
+
+    let mut match_id = None;
+    let mut ranking = if present.len() > 1 {
+        let mut events = room.event_sender.subscribe();
+        room.start_game_with(Some(seed), &present).await?;
+        loop {
+            match events.recv().await {
+                // The game is saved before the end is announced
+                Ok(GameEvent::GameSaved(id)) => match_id = Some(id),
+                Ok(GameEvent::GameEnded(outcome)) => break outcome.ranking.clone(),
+                Ok(GameEvent::RoomClosed) | Err(RecvError::Closed) => {
+                    return Err(GameError::RoomNotFound(room.id.clone()));
+                }
+                // The end may have been among the missed events; nobody but
+                // the tournament starts games in its room
+                Err(RecvError::Lagged(_)) => {
+                    let engine = room.game_engine.read().await;
+                    if !engine.state.is_running {
+                        break engine.state.ranking();
+                    }
+                }
+                _ => {}
+            }
+        }
+    } else {
+        // Nobody showed up to play against
+        present
+            .iter()
+            .map(|&player_id| Placement {
+                player_id,
+                placement: 1,
+                length: 0,
+                death_tick: None,
+            })
+            .collect()
+    };
+
+    let last = ranking.len() + 1;
+    for &player_id in group.iter().filter(|id| !present.contains(id)) {
+        info!("🏳️ {} forfeits a match in room {}", player_id, room.id);
+        ranking.push(Placement {
+            player_id,
+            placement: last,
+            length: 0,
+            death_tick: None,
+        });
+    }
+
+    Ok((ranking, match_id))
+}
+
+/// Entrants of a group who are in the room, waiting a while for registered
+/// entrants who have not connected yet
+async fn wait_for_entrants(room: &RoomState, group: &[Uuid]) -> Vec<Uuid> {
+    let deadline = Instant::now() + Duration::from_millis(ENTRANT_WAIT_MS);
+    loop {
+        let present: Vec<Uuid> = {
+            let game_room = room.game_room.read().await;
+            group
+                .iter()
+                .filter(|id| {
+                    game_room.players.contains_key(id) && !game_room.disconnected.contains_key(id)
+                })
+                .copied()
+                .collect()
+        };
+        if present.len() == group.len() || Instant::now() >= deadline {
+            return present;
+        }
+        tokio::time::sleep(Duration::from_millis(ENTRANT_POLL_MS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::PlayerRegistry;
+    use crate::matches::MatchStore;
+    use crate::replay::ReplayStore;
+    use crate::test_support::TempDir;
+
+    fn tournament(format: TournamentFormat, entrants: usize, group_size: usize) -> Tournament {
+        let entrants = (0..entrants)
+            .map(|i| Entrant {
+                id: Uuid::from_u128(i as u128 + 1),
+                name: format!("bot-{}", i + 1),
+                bot: Some("greedy".to_string()),
+            })
+            .collect();
+        Tournament::new(
+            Uuid::new_v4(),
+            "tournament".to_string(),
+            format,
+            group_size,
+            Some(2),
+            entrants,
+            7,
+        )
+    }
+
+    /// Play out every round, the better seed always placing ahead
+    fn play_out(tournament: &mut Tournament) {
+        while let Some(round) = tournament.start_round() {
+            let matches = round.matches.clone();
+            for (index, game) in matches.iter().enumerate() {
+                let mut players = game.players.clone();
+                players.sort();
+                let ranking = players
+                    .iter()
+                    .enumerate()
+                    .map(|(place, id)| Placement {
+                        player_id: *id,
+                        placement: place + 1,
+                        length: 1,
+                        death_tick: None,
+                    })
+                    .collect();
+                tournament.record_match(index, ranking, None);
+            }
+        }
+        tournament.finish();
+    }
+
+    #[test]
+    fn test_formats_schedule_and_rank() {
+        let mut round_robin = tournament(TournamentFormat::RoundRobin, 4, 2);
+        play_out(&mut round_robin);
+        assert_eq!(round_robin.rounds.len(), 3);
+        let points: Vec<u32> = round_robin.standings.iter().map(|s| s.points).collect();
+        assert_eq!(points, vec![3, 2, 1, 0]);
+
+        let mut swiss = tournament(TournamentFormat::Swiss, 6, 3);
+        play_out(&mut swiss);
+        assert_eq!(swiss.rounds.len(), 2);
+        assert!(swiss.rounds.iter().all(|r| r.matches.len() == 2));
+
+        // Five players in pairs: two matches and a bye, then two more rounds
+        let mut elimination = tournament(TournamentFormat::SingleElimination, 5, 2);
+        play_out(&mut elimination);
+        assert_eq!(elimination.rounds.len(), 3);
+        assert_eq!(elimination.rounds[0].byes.len(), 1);
+        assert_eq!(elimination.champion, Some(Uuid::from_u128(1)));
+        assert!(elimination.standings[1..]
+            .iter()
+            .all(|s| s.eliminated_in.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_tournament_plays_its_matches() {
+        let config = GameConfig {
+            grid_width: 10,
+            grid_height: 10,
+            winning_snake_length: 3,
+            game_tick_duration_ms: 1,
+            move_timeout_ms: 20,
+            ..Default::default()
+        };
+        let dir = TempDir::new("tournament");
+        let players = PlayerRegistry::load(dir.join("players.json")).unwrap();
+        let alice = players.register("alice").unwrap();
+        let rooms = RoomRegistry::new(
+            config,
+            ReplayStore::new(dir.join("replays")),
+            MatchStore::load(dir.join("matches.jsonl"), players.clone()).unwrap(),
+        );
+        let tournaments = TournamentRegistry::new(rooms.clone(), players);
+
+        let bot = |name: &str| {
+            EntrantRequest::Bot(AddBotRequest {
+                bot: name.to_string(),
+                name: None,
+            })
+        };
+        let created = tournaments
+            .create(CreateTournamentRequest {
+                format: TournamentFormat::RoundRobin,
+                entrants: vec![
+                    bot("greedy"),
+                    bot("greedy"),
+                    bot("flood-fill"),
+                    EntrantRequest::Player { player: alice.id },
+                ],
+                group_size: Some(2),
+                rounds: None,
+                config: None,
+                seed: Some(1),
+            })
+            .await
+            .unwrap();
+        assert_eq!(created.entrants[1].name, "greedy-bot-2");
+        assert_eq!(created.entrants[3].name, "alice");
+
+        // The registered entrant plays over their own seat in the room
+        let room = rooms.get(&created.room).await.unwrap();
+        room.join_player(alice.id, alice.name.clone())
+            .await
+            .unwrap();
+
+        let finished = tokio::time::timeout(std::time::Duration::from_secs(30), async {
+            loop {
+                let tournament = tournaments.get(created.id).await.unwrap();
+                if tournament.status != TournamentStatus::Running {
+                    return tournament;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(finished.status, TournamentStatus::Finished);
+        let played: Vec<&TournamentMatch> =
+            finished.rounds.iter().flat_map(|r| &r.matches).collect();
+        assert_eq!(played.len(), 6);
+        assert!(played.iter().all(|game| game.ranking.len() == 2));
+        assert_eq!(
+            finished.standings.iter().map(|s| s.matches).sum::<u32>(),
+            12
+        );
+
+        // Every match is found in the history under its own id
+        let matches = room.matches.clone();
+        for game in &played {
+            let record = matches.get(game.match_id.unwrap()).unwrap();
+            assert_eq!(record.seed, game.seed);
+            let mut seated: Vec<Uuid> = record.players.iter().map(|p| p.id).collect();
+            let mut players = game.players.clone();
+            seated.sort();
+            players.sort();
+            assert_eq!(seated, players);
+        }
+
+        // The room closes with the tournament
+        assert!(rooms.get(&finished.room).await.is_err());
+    }
+}
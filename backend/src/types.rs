@@ -2,381 +2,64 @@
 //!
 //! This module defines all the data structures used for the multiplayer snake game API.
 //! All types are serializable with serde for JSON communication over WebSocket.
+//! The game state and messages clients see come from the `snake-protocol`
+//! crate shared with the GUI and are re-exported here.
 
+use crate::replay::ReplayRecorder;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
+use std::sync::Arc;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-/// Represents a position on the game grid
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
-pub struct Position {
-    pub x: i32,
-    pub y: i32,
-}
-
-impl Position {
-    pub fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
-    }
-
-    /// Move position in the given direction, wrapping around grid boundaries
-    pub fn move_in_direction(
-        &self,
-        direction: Direction,
-        grid_width: i32,
-        grid_height: i32,
-    ) -> Position {
-        let mut new_x = self.x;
-        let mut new_y = self.y;
-
-        match direction {
-            Direction::Up => new_y -= 1,
-            Direction::Down => new_y += 1,
-            Direction::Left => new_x -= 1,
-            Direction::Right => new_x += 1,
-        }
-
-        // Handle wrapping around boundaries
-        if new_x < 0 {
-            new_x = grid_width - 1;
-        } else if new_x >= grid_width {
-            new_x = 0;
-        }
-
-        if new_y < 0 {
-            new_y = grid_height - 1;
-        } else if new_y >= grid_height {
-            new_y = 0;
-        }
-
-        Position::new(new_x, new_y)
-    }
-}
-
-/// Movement directions for snakes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl Direction {
-    /// Get the opposite direction
-    pub fn opposite(&self) -> Direction {
-        match self {
-            Direction::Up => Direction::Down,
-            Direction::Down => Direction::Up,
-            Direction::Left => Direction::Right,
-            Direction::Right => Direction::Left,
-        }
-    }
-
-    /// Get all possible directions
-    pub fn all() -> [Direction; 4] {
-        [
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ]
-    }
-}
-
-/// Represents a player's snake
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct Snake {
-    /// Unique identifier for the snake
-    pub id: Uuid,
-    /// Player name
-    pub player_name: String,
-    /// Snake body positions (head is first, tail is last)
-    pub body: VecDeque<Position>,
-    /// Current length of the snake
-    pub length: usize,
-    /// Whether the snake is alive
-    pub is_alive: bool,
-    /// Color index for this snake (maps to SNAKE_COLORS)
-    pub color_index: usize,
-    /// Last direction moved (used to prevent moving backwards)
-    pub last_direction: Option<Direction>,
-}
-
-impl Snake {
-    pub fn new(
-        id: Uuid,
-        player_name: String,
-        initial_position: Position,
-        color_index: usize,
-    ) -> Self {
-        let mut body = VecDeque::new();
-        body.push_back(initial_position);
-
-        Self {
-            id,
-            player_name,
-            body,
-            length: crate::constants::INITIAL_SNAKE_LENGTH,
-            is_alive: true,
-            color_index,
-            last_direction: None,
-        }
-    }
-
-    /// Get the head position of the snake
-    pub fn head(&self) -> Option<Position> {
-        self.body.front().copied()
-    }
-
-    /// Get the tail position of the snake (excluding head)
-    pub fn tail(&self) -> Vec<Position> {
-        self.body.iter().skip(1).copied().collect()
-    }
-
-    /// Check if the snake contains a specific position
-    pub fn contains_position(&self, pos: Position) -> bool {
-        self.body.contains(&pos)
-    }
-
-    /// Get valid directions (cannot move backwards into tail)
-    pub fn valid_directions(&self) -> Vec<Direction> {
-        let mut valid = Direction::all().to_vec();
-
-        // If snake has a tail and we know the last direction, remove opposite
-        if self.body.len() > 1 {
-            if let Some(last_dir) = self.last_direction {
-                valid.retain(|&dir| dir != last_dir.opposite());
-            }
-        }
-
-        valid
-    }
-
-    /// Move the snake in the given direction
-    pub fn move_snake(
-        &mut self,
-        direction: Direction,
-        grid_width: i32,
-        grid_height: i32,
-        grow: bool,
-    ) {
-        if let Some(head) = self.head() {
-            let new_head = head.move_in_direction(direction, grid_width, grid_height);
-            self.body.push_front(new_head);
-            self.last_direction = Some(direction);
-
-            if grow {
-                self.length += 1;
-            } else {
-                // Remove tail if not growing
-                if self.body.len() > self.length {
-                    self.body.pop_back();
-                }
-            }
-        }
-    }
-
-    /// Kill the snake
-    pub fn kill(&mut self) {
-        self.is_alive = false;
-    }
-}
-
-/// Represents a piece of fruit
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct Fruit {
-    pub position: Position,
-    pub spawn_tick: u64,
-}
-
-impl Fruit {
-    pub fn new(position: Position, spawn_tick: u64) -> Self {
-        Self {
-            position,
-            spawn_tick,
-        }
-    }
-}
-
-/// Current state of the game
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct GameState {
-    /// All snakes in the game
-    pub snakes: HashMap<Uuid, Snake>,
-    /// All fruits on the board
-    pub fruits: Vec<Fruit>,
-    /// Current game tick
-    pub tick: u64,
-    /// Whether the game is running
-    pub is_running: bool,
-    /// Winner of the game (if any)
-    pub winner: Option<Uuid>,
-    /// Grid dimensions
-    pub grid_width: i32,
-    pub grid_height: i32,
-}
-
-impl GameState {
-    pub fn new() -> Self {
-        Self {
-            snakes: HashMap::new(),
-            fruits: Vec::new(),
-            tick: 0,
-            is_running: false,
-            winner: None,
-            grid_width: crate::constants::GRID_WIDTH as i32,
-            grid_height: crate::constants::GRID_HEIGHT as i32,
-        }
-    }
-
-    /// Get all occupied positions on the grid
-    pub fn occupied_positions(&self) -> Vec<Position> {
-        let mut positions = Vec::new();
-
-        // Add all snake body positions
-        for snake in self.snakes.values() {
-            positions.extend(snake.body.iter().copied());
-        }
-
-        // Add all fruit positions
-        for fruit in &self.fruits {
-            positions.push(fruit.position);
-        }
-
-        positions
-    }
-
-    /// Get empty positions on the grid
-    pub fn empty_positions(&self) -> Vec<Position> {
-        let occupied = self.occupied_positions();
-        let mut empty = Vec::new();
-
-        for x in 0..self.grid_width {
-            for y in 0..self.grid_height {
-                let pos = Position::new(x, y);
-                if !occupied.contains(&pos) {
-                    empty.push(pos);
-                }
-            }
-        }
-
-        empty
-    }
-
-    /// Check if the game is over
-    pub fn is_game_over(&self) -> bool {
-        let alive_snakes: Vec<_> = self.snakes.values().filter(|s| s.is_alive).collect();
-
-        // Game over if only one snake alive or someone reached winning length
-        alive_snakes.len() <= 1
-            || alive_snakes
-                .iter()
-                .any(|s| s.length >= crate::constants::WINNING_SNAKE_LENGTH)
-    }
-
-    /// Get the winner of the game
-    pub fn get_winner(&self) -> Option<Uuid> {
-        let alive_snakes: Vec<_> = self.snakes.values().filter(|s| s.is_alive).collect();
-
-        // Check for length winner first
-        for snake in &alive_snakes {
-            if snake.length >= crate::constants::WINNING_SNAKE_LENGTH {
-                return Some(snake.id);
-            }
-        }
-
-        // Check for last snake standing
-        if alive_snakes.len() == 1 {
-            return Some(alive_snakes[0].id);
-        }
-
-        None
-    }
-}
-
-/// Messages sent from client to server
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[serde(tag = "type")]
-pub enum ClientMessage {
-    /// Join the game lobby
-    JoinLobby { player_name: String },
-    /// Submit a move for the current tick
-    SubmitMove { direction: Direction },
-    /// Ready to start the game (from GUI)
-    StartGame,
-    /// Ping to keep connection alive
-    Ping,
-}
-
-/// Messages sent from server to client
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-#[serde(tag = "type")]
-pub enum ServerMessage {
-    /// Confirmation of joining lobby
-    LobbyJoined {
-        player_id: Uuid,
-        player_name: String,
-    },
-    /// Current lobby state
-    LobbyState { players: Vec<LobbyPlayer> },
-    /// Game has started
-    GameStarted {
-        game_state: GameState,
-        your_snake_id: Uuid,
-    },
-    /// Game state update
-    GameUpdate { game_state: GameState },
-    /// Request for next move
-    MoveRequest {
-        valid_directions: Vec<Direction>,
-        time_limit_ms: u64,
-    },
-    /// Game ended
-    GameEnded {
-        winner: Option<LobbyPlayer>,
-        final_state: GameState,
-    },
-    /// Error message
-    Error { message: String },
-    /// Pong response to ping
-    Pong,
-}
-
-/// Player information in lobby
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct LobbyPlayer {
-    pub id: Uuid,
-    pub name: String,
-    pub color_index: usize,
-    pub is_ready: bool,
-}
+pub use snake_protocol::*;
 
 /// Game room state for managing connections
 #[derive(Debug)]
 pub struct GameRoom {
+    pub config: GameConfig,
+    /// Where the room is in its game lifecycle
+    pub phase: RoomPhase,
     pub game_state: GameState,
     pub players: HashMap<Uuid, LobbyPlayer>,
     pub pending_moves: HashMap<Uuid, Direction>,
     pub fruit_spawn_counter: u32,
+    /// Tick moves are currently accepted for
+    pub move_tick: Option<u64>,
+    /// Time after which moves for `move_tick` are no longer waited for
     pub move_deadline: Option<tokio::time::Instant>,
+    /// Player ids by resume token
+    pub sessions: HashMap<Uuid, Uuid>,
+    /// Players whose connection dropped, with the time it dropped
+    pub disconnected: HashMap<Uuid, tokio::time::Instant>,
+    /// Number of games started in the room, telling a game loop whether
+    /// the game it plays was replaced by a newer one
+    pub game_number: u64,
+    /// Recording of the game started last, until the game loop takes it
+    pub recorder: Option<ReplayRecorder>,
 }
 
 impl GameRoom {
-    pub fn new() -> Self {
+    pub fn new(config: GameConfig) -> Self {
         Self {
-            game_state: GameState::new(),
+            game_state: GameState::new(config.grid_width, config.grid_height),
+            config,
+            phase: RoomPhase::Lobby,
             players: HashMap::new(),
             pending_moves: HashMap::new(),
             fruit_spawn_counter: 0,
+            move_tick: None,
             move_deadline: None,
+            sessions: HashMap::new(),
+            disconnected: HashMap::new(),
+            game_number: 0,
+            recorder: None,
         }
     }
 
     /// Add a new player to the room
     pub fn add_player(&mut self, id: Uuid, name: String) -> Result<usize, String> {
-        if self.players.len() >= crate::constants::MAX_PLAYERS {
+        if self.players.len() >= self.config.max_players {
             return Err("Room is full".to_string());
         }
 
@@ -384,7 +67,11 @@ impl GameRoom {
             return Err("Player name already taken".to_string());
         }
 
-        let color_index = self.players.len();
+        // Take the lowest color no seated player uses, so a newcomer does not
+        // share a color with someone who joined before a player left
+        let color_index = (0..)
+            .find(|i| self.players.values().all(|p| p.color_index != *i))
+            .unwrap_or_default();
         let player = LobbyPlayer {
             id,
             name,
@@ -401,11 +88,67 @@ impl GameRoom {
         self.players.remove(id);
         self.game_state.snakes.remove(id);
         self.pending_moves.remove(id);
+        self.sessions.retain(|_, player_id| player_id != id);
+        self.disconnected.remove(id);
+    }
+
+    /// Issue a resume token for a player
+    pub fn create_session(&mut self, player_id: Uuid) -> Uuid {
+        let token = Uuid::new_v4();
+        self.sessions.insert(token, player_id);
+        token
+    }
+
+    /// Player holding a resume token, if they are still in the room
+    pub fn resume_session(&mut self, token: &Uuid) -> Option<&LobbyPlayer> {
+        let player_id = self.sessions.get(token)?;
+        self.disconnected.remove(player_id);
+        self.players.get(player_id)
+    }
+
+    /// Start accepting moves for a tick until the deadline
+    pub fn open_moves(&mut self, tick: u64, deadline: tokio::time::Instant) {
+        self.move_tick = Some(tick);
+        self.move_deadline = Some(deadline);
+        self.pending_moves.clear();
+    }
+
+    /// Stop accepting moves and take the ones submitted
+    pub fn close_moves(&mut self) -> HashMap<Uuid, Direction> {
+        self.move_tick = None;
+        self.move_deadline = None;
+        std::mem::take(&mut self.pending_moves)
+    }
+
+    /// Record a player's move for the open tick
+    pub fn submit_move(
+        &mut self,
+        player_id: Uuid,
+        direction: Direction,
+        tick: u64,
+    ) -> GameResult<()> {
+        let current = self.move_tick.ok_or(GameError::NoMoveRequested)?;
+        if tick != current {
+            return Err(GameError::StaleMove {
+                submitted: tick,
+                current,
+            });
+        }
+
+        self.pending_moves.insert(player_id, direction);
+        Ok(())
+    }
+
+    /// Time left to submit a move for the open tick
+    pub fn time_left(&self) -> std::time::Duration {
+        self.move_deadline
+            .map(|deadline| deadline.saturating_duration_since(tokio::time::Instant::now()))
+            .unwrap_or_default()
     }
 
     /// Check if all players are ready to start
     pub fn can_start_game(&self) -> bool {
-        self.players.len() >= crate::constants::MIN_PLAYERS
+        self.players.len() >= self.config.min_players
             && self.players.values().all(|p| p.is_ready)
     }
 
@@ -437,6 +180,9 @@ impl GameRoom {
 pub struct PlayerConnection {
     pub player_id: Uuid,
     pub sender: tokio::sync::mpsc::UnboundedSender<ServerMessage>,
+    /// Notified to close the connection, when the player resumes their
+    /// session on another connection or is kicked
+    pub close: std::sync::Arc<tokio::sync::Notify>,
 }
 
 /// Game events for internal communication
@@ -444,9 +190,52 @@ pub struct PlayerConnection {
 pub enum GameEvent {
     PlayerJoined(Uuid, String),
     PlayerLeft(Uuid),
+    GameStarting(u64),
+    CountdownCancelled,
+    PhaseChanged(RoomPhase),
     GameStarted,
     GameTick,
-    GameEnded(Option<Uuid>),
+    GamePaused,
+    GameResumed,
+    /// The game is over and the room already left the running phase, so a
+    /// new game can be started
+    GameEnded(Arc<GameOutcome>),
+    /// A finished game was added to the match history under this id; sent
+    /// before `GameEnded`
+    GameSaved(Uuid),
+    RoomClosed,
+}
+
+/// How a game ended, taken when it ended so that a new game started in the
+/// meantime does not change it
+#[derive(Debug, Clone)]
+pub struct GameOutcome {
+    pub winner: Option<LobbyPlayer>,
+    /// Every player's final placement, best first
+    pub ranking: Vec<Placement>,
+    pub final_state: GameState,
+}
+
+impl GameOutcome {
+    /// Outcome of the game that just ended in a room
+    pub fn new(room: &GameRoom, final_state: GameState) -> Self {
+        Self {
+            winner: final_state
+                .winner
+                .and_then(|id| room.players.get(&id).cloned()),
+            ranking: final_state.ranking(),
+            final_state,
+        }
+    }
+
+    /// The `GameEnded` message announcing the outcome
+    pub fn message(&self) -> ServerMessage {
+        ServerMessage::GameEnded {
+            winner: self.winner.clone(),
+            ranking: self.ranking.clone(),
+            final_state: self.final_state.clone(),
+        }
+    }
 }
 
 /// Error types for the game
@@ -467,12 +256,65 @@ pub enum GameError {
     #[error("Name already taken: {0}")]
     NameTaken(String),
 
+    #[error("Need at least {required} players to start (current: {current})")]
+    NotEnoughPlayers { required: usize, current: usize },
+
+    #[error("Game already running")]
+    GameAlreadyRunning,
+
+    #[error("Move for tick {submitted} rejected, moves are open for tick {current}")]
+    StaleMove { submitted: u64, current: u64 },
+
+    #[error("No move is being requested right now")]
+    NoMoveRequested,
+
+    #[error("Replay not found: {0}")]
+    ReplayNotFound(Uuid),
+
+    #[error("Match not found: {0}")]
+    MatchNotFound(Uuid),
+
+    #[error("Tournament not found: {0}")]
+    TournamentNotFound(Uuid),
+
+    #[error("Unknown or expired resume token")]
+    SessionNotFound,
+
+    #[error("Room not found: {0}")]
+    RoomNotFound(String),
+
+    #[error("Room already exists: {0}")]
+    RoomExists(String),
+
+    #[error("Admin token missing or invalid")]
+    Unauthorized,
+
+    #[error("API key missing or invalid")]
+    InvalidApiKey,
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
     #[error("WebSocket error: {0}")]
     WebSocket(String),
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("Unreadable message: {0}")]
+    Codec(#[from] CodecError),
+
+    #[error(
+        "Incompatible protocol: the client speaks versions {client_min} to {client_max}, \
+         the server {server_min} to {server_max}"
+    )]
+    IncompatibleProtocol {
+        client_min: u32,
+        client_max: u32,
+        server_min: u32,
+        server_max: u32,
+    },
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
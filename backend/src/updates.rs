@@ -0,0 +1,170 @@
+//! Game updates sent to a single connection
+//!
+//! By default every tick is sent as a full `GameUpdate`. Connections that
+//! ask for deltas get a full keyframe first and then `GameDelta`s against
+//! the state they were last sent, with a fresh keyframe every
+//! `DELTA_KEYFRAME_INTERVAL` updates. Updates are numbered with `seq` so a
+//! client can tell it missed one and ask for a `Resync`.
+
+use crate::constants::DELTA_KEYFRAME_INTERVAL;
+use crate::types::*;
+
+/// Stream of game updates for one connection
+#[derive(Debug, Clone)]
+pub struct UpdateStream {
+    /// Whether the connection asked for deltas
+    deltas: bool,
+    /// Sequence number of the last update sent
+    seq: u64,
+    /// State the last update brought the connection to, if it has one
+    last: Option<GameState>,
+    /// Deltas sent since the last keyframe
+    since_keyframe: u64,
+}
+
+impl UpdateStream {
+    pub fn new(deltas: bool) -> Self {
+        Self {
+            deltas,
+            seq: 0,
+            last: None,
+            since_keyframe: 0,
+        }
+    }
+
+    /// Update bringing the connection to the state after a processed tick
+    pub fn update(&mut self, state: &GameState, phase: RoomPhase) -> ServerMessage {
+        if !self.deltas {
+            return ServerMessage::game_update(state, phase);
+        }
+
+        let delta = self
+            .last
+            .as_ref()
+            .filter(|_| self.since_keyframe < DELTA_KEYFRAME_INTERVAL)
+            .and_then(|last| last.delta_to(state));
+        match delta {
+            Some(delta) => {
+                self.seq += 1;
+                self.since_keyframe += 1;
+                self.last = Some(state.clone());
+                ServerMessage::GameDelta {
+                    seq: self.seq,
+                    delta,
+                    phase,
+                }
+            }
+            None => self.keyframe(state, phase),
+        }
+    }
+
+    /// Full update of the state, which later deltas build on
+    pub fn keyframe(&mut self, state: &GameState, phase: RoomPhase) -> ServerMessage {
+        let mut message = ServerMessage::game_update(state, phase);
+        if self.deltas {
+            self.seq += 1;
+            self.since_keyframe = 0;
+            self.last = Some(state.clone());
+            if let ServerMessage::GameUpdate { seq, .. } = &mut message {
+                *seq = Some(self.seq);
+            }
+        }
+        message
+    }
+
+    /// Send the next update in full, e.g. because a new game started
+    pub fn restart(&mut self) {
+        self.last = None;
+    }
+
+    /// Whether updates are sent as deltas
+    pub fn deltas(&self) -> bool {
+        self.deltas
+    }
+
+    /// Send deltas from the next update on, starting with a keyframe
+    pub fn enable_deltas(&mut self) {
+        if !self.deltas {
+            self.deltas = true;
+            self.restart();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameEngine;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    /// Move every live snake up; on the wrapping grid they never collide
+    fn tick(engine: &mut GameEngine) {
+        let moves = engine
+            .state
+            .snakes
+            .values()
+            .filter(|snake| snake.is_alive)
+            .map(|snake| (snake.id, Direction::Up))
+            .collect();
+        engine.process_tick(moves).unwrap();
+    }
+
+    #[test]
+    fn test_deltas_follow_a_keyframe() {
+        let players: HashMap<Uuid, LobbyPlayer> = ["alice", "bob"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let id = Uuid::new_v4();
+                let player = LobbyPlayer {
+                    id,
+                    name: name.to_string(),
+                    color_index: index,
+                    is_ready: true,
+                };
+                (id, player)
+            })
+            .collect();
+        let mut engine = GameEngine::default();
+        engine.initialize_game(&players, Some(7)).unwrap();
+
+        let mut stream = UpdateStream::new(true);
+        let mut client = match stream.update(&engine.state, RoomPhase::Running) {
+            ServerMessage::GameUpdate {
+                game_state,
+                seq: Some(1),
+                ..
+            } => game_state,
+            message => panic!("expected a keyframe, got {:?}", message),
+        };
+
+        for seq in 2..=DELTA_KEYFRAME_INTERVAL + 1 {
+            tick(&mut engine);
+            match stream.update(&engine.state, RoomPhase::Running) {
+                ServerMessage::GameDelta {
+                    seq: got, delta, ..
+                } => {
+                    assert_eq!(got, seq);
+                    assert!(client.apply_delta(&delta));
+                    assert_eq!(client, engine.state);
+                }
+                message => panic!("expected a delta, got {:?}", message),
+            }
+        }
+
+        // Deltas stop for a periodic keyframe
+        tick(&mut engine);
+        assert!(matches!(
+            stream.update(&engine.state, RoomPhase::Running),
+            ServerMessage::GameUpdate { seq: Some(_), .. }
+        ));
+
+        // Without deltas every update is full and unnumbered
+        let mut full = UpdateStream::new(false);
+        assert!(matches!(
+            full.update(&engine.state, RoomPhase::Running),
+            ServerMessage::GameUpdate { seq: None, .. }
+        ));
+    }
+}